@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A yt-dlp style download archive: one `youtube <video id>` line per processed video.
+pub struct Archive {
+    path: PathBuf,
+    ids: HashSet<String>,
+}
+
+impl Archive {
+    /// Reads the archive at `path`. A missing file is an empty archive.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+
+        let ids = content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                match (fields.next(), fields.next()) {
+                    (Some("youtube"), Some(id)) => Some(id.to_string()),
+                    _ => None,
+                }
+            })
+            .collect();
+        Ok(Archive { path, ids })
+    }
+
+    pub fn contains(&self, video_id: &str) -> bool {
+        self.ids.contains(video_id)
+    }
+
+    /// Appends `video_id` to the archive file unless it is already recorded.
+    pub fn record(&mut self, video_id: &str) -> io::Result<()> {
+        if self.ids.contains(video_id) {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "youtube {}", video_id)?;
+        self.ids.insert(video_id.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Progress of a playlist or channel run, saved after every video so an interrupted run can
+/// resume where it stopped.
+#[derive(Serialize, Deserialize)]
+pub struct BatchState {
+    /// The playlist or channel URL the run was started with.
+    pub source: String,
+    pub video_ids: Vec<String>,
+    pub completed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl BatchState {
+    pub fn new(source: &str, video_ids: Vec<String>) -> Self {
+        BatchState {
+            source: source.to_string(),
+            video_ids,
+            completed: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    /// Reads a saved state. A missing file is `None`.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Writes the state atomically, so an interruption never leaves a truncated file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Videos not completed yet, including the ones that failed, in listing order.
+    pub fn pending(&self) -> Vec<String> {
+        self.video_ids
+            .iter()
+            .filter(|id| !self.completed.contains(id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn mark_completed(&mut self, video_id: &str) {
+        self.failed.retain(|id| id != video_id);
+        if !self.completed.iter().any(|id| id == video_id) {
+            self.completed.push(video_id.to_string());
+        }
+    }
+
+    pub fn mark_failed(&mut self, video_id: &str) {
+        if !self.failed.iter().any(|id| id == video_id) {
+            self.failed.push(video_id.to_string());
+        }
+    }
+}
+
+/// Outcome of a playlist or channel run, printed at the end and written with `--summary`.
+#[derive(Serialize)]
+pub struct BatchSummary {
+    pub source: String,
+    /// Videos transcribed in this run.
+    pub processed: usize,
+    /// Of the processed videos, those whose subtitles came from the cache.
+    pub cached: usize,
+    /// Videos skipped because the archive already lists them.
+    pub archived: usize,
+    /// Videos skipped because their output file already exists (`--skip-existing`).
+    pub existing: usize,
+    pub failed: Vec<FailedVideo>,
+    pub duration_seconds: f64,
+}
+
+#[derive(Serialize)]
+pub struct FailedVideo {
+    pub video_id: String,
+    /// Error identifier, as printed by `--error-format json`.
+    pub error: String,
+    pub message: String,
+}
+
+impl BatchSummary {
+    pub fn new(source: &str) -> Self {
+        BatchSummary {
+            source: source.to_string(),
+            processed: 0,
+            cached: 0,
+            archived: 0,
+            existing: 0,
+            failed: Vec::new(),
+            duration_seconds: 0.0,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::timedtext::SubtitleFormat;
+use crate::transcript::VideoInfo;
+
+/// Subtitles as downloaded from YouTube, before any parsing options are applied, so one cache
+/// entry serves every output format and post-processing combination.
+#[derive(Serialize, Deserialize)]
+pub struct CachedSubtitles {
+    #[serde(alias = "vtt")]
+    pub content: String,
+    /// Format of `content`; entries written before other formats were fetched are WebVTT.
+    #[serde(default)]
+    pub format: SubtitleFormat,
+    pub video: Option<VideoInfo>,
+    pub auto_generated: bool,
+    pub fetched_at: String,
+    /// Fetched while the video was being broadcast live, so the captions are still growing.
+    #[serde(default)]
+    pub live: bool,
+}
+
+/// A file in the cache directory.
+pub struct CacheEntry {
+    pub key: String,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// Directory of cached subtitles, one JSON file per video, language and track.
+#[derive(Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Option<Duration>,
+}
+
+impl Cache {
+    /// Entries older than `ttl` are treated as missing; `None` keeps them forever.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Option<Duration>) -> Self {
+        Cache {
+            dir: dir.into(),
+            ttl,
+        }
+    }
+
+    /// `$YT_TRANSCRIBER_CACHE_DIR`, or `$XDG_CACHE_HOME/yt-transcriber` and the platform
+    /// equivalent.
+    pub fn default_dir() -> PathBuf {
+        if let Some(dir) = std::env::var_os("YT_TRANSCRIBER_CACHE_DIR") {
+            return PathBuf::from(dir);
+        }
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("yt-transcriber")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn key(video_id: &str, language: &str, track: &str) -> String {
+        format!("{}.{}.{}", video_id, language, track)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn is_fresh(&self, path: &Path) -> bool {
+        let Some(ttl) = self.ttl else {
+            return true;
+        };
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age <= ttl)
+    }
+
+    /// Returns the entry for `key` when it exists, is readable and has not expired.
+    pub fn get(&self, key: &str) -> Option<CachedSubtitles> {
+        let path = self.path(key);
+        if !self.is_fresh(&path) {
+            return None;
+        }
+        let content = fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn put(&self, key: &str, entry: &CachedSubtitles) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string(entry).map_err(io::Error::other)?;
+        let tmp = self.dir.join(format!(".{}.tmp", key));
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, self.path(key))
+    }
+
+    /// All cache entries, oldest first. A missing cache directory has no entries.
+    pub fn entries(&self) -> io::Result<Vec<CacheEntry>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries = Vec::new();
+        for item in dir {
+            let item = item?;
+            let name = item.file_name().to_string_lossy().into_owned();
+            let Some(key) = name.strip_suffix(".json") else {
+                continue;
+            };
+            let metadata = item.metadata()?;
+            entries.push(CacheEntry {
+                key: key.to_string(),
+                size: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+        entries.sort_by_key(|e| e.modified);
+        Ok(entries)
+    }
+
+    /// Deletes the entries older than `older_than`, or all of them, returning the entries removed.
+    pub fn clear(&self, older_than: Option<Duration>) -> io::Result<Vec<CacheEntry>> {
+        let now = SystemTime::now();
+        let mut removed = Vec::new();
+        for entry in self.entries()? {
+            let age = now.duration_since(entry.modified).unwrap_or_default();
+            if older_than.is_some_and(|limit| age <= limit) {
+                continue;
+            }
+            fs::remove_file(self.path(&entry.key))?;
+            removed.push(entry);
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,68 @@
+use regex::Regex;
+
+use crate::transcript::TranscriptSegment;
+
+/// English words masked by `--censor` when no wordlist is given. A trailing `*` also matches
+/// longer words starting with the rest.
+pub const DEFAULT_WORDS: &[&str] = &[
+    "fuck*", "motherfuck*", "shit", "shits", "shitty", "shithead*", "bullshit", "bitch", "bitches",
+    "asshole*", "bastard*", "cunt*", "dick", "dicks", "dickhead*", "cock", "cocks", "cocksucker*",
+    "piss", "pissed", "prick", "pricks", "twat*", "wanker*", "slut*", "whore*", "goddamn*",
+];
+
+/// Masks listed words, keeping their first letter: `shit` becomes `s***`.
+pub struct Censor {
+    pattern: Regex,
+}
+
+impl Censor {
+    /// Matches each word whole and ignoring case; a word ending in `*` also matches longer words
+    /// that start with it, as `fuck*` matches "fucking".
+    pub fn new<S: AsRef<str>>(words: &[S]) -> Result<Censor, String> {
+        let alternatives: Vec<String> = words
+            .iter()
+            .map(|word| word.as_ref().trim())
+            .filter(|word| !word.is_empty())
+            .map(|word| match word.strip_suffix('*') {
+                Some(prefix) => format!(r"{}\w*", regex::escape(prefix)),
+                None => regex::escape(word),
+            })
+            .collect();
+        if alternatives.is_empty() {
+            return Err("the wordlist has no words".to_string());
+        }
+        let pattern = Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|")))
+            .map_err(|e| e.to_string())?;
+        Ok(Censor { pattern })
+    }
+
+    /// Words of a wordlist file: one per line, skipping blank lines and `#` comments.
+    pub fn parse_wordlist(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.pattern
+            .replace_all(text, |caps: &regex::Captures| {
+                let mut chars = caps[0].chars();
+                let first = chars.next().map(String::from).unwrap_or_default();
+                first + &"*".repeat(chars.count())
+            })
+            .into_owned()
+    }
+
+    /// Masks the text of every segment and of its timed words.
+    pub fn apply_segments(&self, segments: &mut [TranscriptSegment]) {
+        for seg in segments {
+            seg.text = self.apply(&seg.text);
+            for word in seg.words.iter_mut().flatten() {
+                word.text = self.apply(&word.text);
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use serde_json::Value;
+
+use crate::llm::{reply_json, transcript_chunks, LlmClient};
+use crate::timing::parse_time;
+use crate::transcript::{Chapter, TranscriptSegment};
+
+/// Transcript characters sent per request, as for summaries.
+const CHUNK_CHARS: usize = 12_000;
+
+const CHAPTERS_PROMPT: &str = "You divide video transcripts into chapters. Each line of the \
+transcript starts with its [mm:ss] timestamp. Reply with only a JSON object of the form \
+{\"chapters\": [{\"time\": \"mm:ss\", \"title\": \"...\"}]}: one chapter per topic, in order, \
+each starting at the timestamp of the line where the topic begins, with a short title of a few \
+words. Chapters should be at least a few minutes long. Write in the language of the transcript.";
+
+/// Asks the model for chapters, one request per chunk of the transcript. The first chapter
+/// starts at 0, each ends where the next begins and the last at the end of the transcript;
+/// chapters proposed at the same time are merged.
+pub fn propose_chapters(
+    client: &LlmClient,
+    segments: &[TranscriptSegment],
+) -> Result<Vec<Chapter>, String> {
+    let mut starts: Vec<(f64, String)> = Vec::new();
+    let chunks = transcript_chunks(segments, CHUNK_CHARS);
+    let replies = client.map(&chunks, |chunk| {
+        reply_json(&client.chat(CHAPTERS_PROMPT, chunk)?)
+    })?;
+    for reply in replies {
+        let chapters = reply["chapters"]
+            .as_array()
+            .ok_or_else(|| "the reply has no \"chapters\"".to_string())?;
+        starts.extend(chapters.iter().filter_map(chapter_start));
+    }
+
+    let end = segments
+        .iter()
+        .map(|seg| seg.end_seconds)
+        .fold(0.0, f64::max);
+    starts.retain(|(start, _)| *start < end);
+    starts.sort_by(|a, b| a.0.total_cmp(&b.0));
+    starts.dedup_by(|later, earlier| later.0 == earlier.0);
+    if let Some(first) = starts.first_mut() {
+        first.0 = 0.0;
+    }
+
+    let ends: Vec<f64> = starts
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain([end])
+        .collect();
+    Ok(starts
+        .into_iter()
+        .zip(ends)
+        .map(|((start_time, title), end_time)| Chapter {
+            title,
+            start_time,
+            end_time,
+        })
+        .collect())
+}
+
+/// A chapter's start and title from a reply; entries without a readable time or title are
+/// dropped.
+fn chapter_start(chapter: &Value) -> Option<(f64, String)> {
+    let time = parse_time(chapter["time"].as_str()?.trim_matches(['[', ']']))?;
+    let title = chapter["title"].as_str()?.trim();
+    (!title.is_empty()).then(|| (time, title.to_string()))
+}
@@ -0,0 +1,123 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::format::youtube_link;
+use crate::tokens::count_tokens;
+use crate::transcript::TranscriptResult;
+
+/// Chunk size and overlap for `--chunk`, in tokens.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkSpec {
+    pub tokens: usize,
+    pub overlap: usize,
+}
+
+impl ChunkSpec {
+    /// Parses `tokens=<n>` with an optional `overlap=<n>`, separated by spaces or commas, e.g.
+    /// `tokens=500 overlap=50`.
+    pub fn parse(value: &str) -> Result<ChunkSpec, String> {
+        let mut tokens = None;
+        let mut overlap = 0;
+        for part in value.split([' ', ',']).filter(|p| !p.is_empty()) {
+            let (key, n) = part
+                .split_once('=')
+                .and_then(|(key, n)| Some((key, n.parse::<usize>().ok()?)))
+                .ok_or_else(|| {
+                    format!("invalid chunk setting '{}', expected e.g. tokens=500", part)
+                })?;
+            match key {
+                "tokens" => tokens = Some(n),
+                "overlap" => overlap = n,
+                _ => {
+                    return Err(format!(
+                        "unknown chunk setting '{}', expected tokens or overlap",
+                        key
+                    ))
+                }
+            }
+        }
+        match tokens {
+            Some(tokens) if tokens > 0 && overlap < tokens => Ok(ChunkSpec { tokens, overlap }),
+            Some(_) => Err("chunk overlap must be smaller than the chunk size".to_string()),
+            None => Err("missing chunk size, expected e.g. tokens=500 overlap=50".to_string()),
+        }
+    }
+}
+
+/// A run of consecutive segments with the span they cover and a link to where it starts.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Chunk {
+    pub video_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub chunk: usize,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub tokens: usize,
+    pub text: String,
+    pub url: String,
+    /// Vector of `text`, with `--embed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// Groups segments into chunks of about `spec.tokens` tokens, whole segments only: a chunk ends
+/// before the segment that would take it over the size, unless that segment is all it has. Each
+/// chunk after the first repeats the last segments of the one before, up to `spec.overlap`
+/// tokens, but always moves on by at least one segment.
+pub fn chunk(result: &TranscriptResult, spec: &ChunkSpec) -> Vec<Chunk> {
+    let segments = &result.segments;
+    let costs: Vec<usize> = segments
+        .iter()
+        .map(|seg| count_tokens(&seg.text) + 1)
+        .collect();
+    let title = result.video.as_ref().and_then(|v| v.title.clone());
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < segments.len() {
+        let mut end = start + 1;
+        let mut tokens = costs[start];
+        while end < segments.len() && tokens + costs[end] <= spec.tokens {
+            tokens += costs[end];
+            end += 1;
+        }
+
+        let text = segments[start..end]
+            .iter()
+            .map(|seg| seg.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        chunks.push(Chunk {
+            video_id: result.video_id.clone(),
+            title: title.clone(),
+            chunk: chunks.len(),
+            start_seconds: segments[start].start_seconds,
+            end_seconds: segments[end - 1].end_seconds,
+            tokens: count_tokens(&text),
+            text,
+            url: youtube_link(&result.video_id, segments[start].start_seconds),
+            embedding: None,
+        });
+        if end == segments.len() {
+            break;
+        }
+
+        let mut next = end;
+        let mut overlap = 0;
+        while next > start + 1 && overlap + costs[next - 1] <= spec.overlap {
+            overlap += costs[next - 1];
+            next -= 1;
+        }
+        start = next;
+    }
+    chunks
+}
+
+/// Chunks as JSONL, one object per line.
+pub fn format_chunks(chunks: &[Chunk]) -> String {
+    chunks
+        .iter()
+        .map(|chunk| serde_json::to_string(chunk).unwrap_or_default() + "\n")
+        .collect()
+}
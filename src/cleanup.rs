@@ -0,0 +1,177 @@
+use regex::Regex;
+
+use crate::stats::normalize_word;
+use crate::transcript::TranscriptSegment;
+
+/// Longest `k` such that the last `k` words of `previous` equal the first `k` words of `current`.
+/// A single repeated word only counts when it is all of `previous`; otherwise it is more likely
+/// speech ("the the", a sentence ending and the next starting with "so") than a rolling line.
+fn overlap(previous: &[String], current: &[String]) -> usize {
+    let max = previous.len().min(current.len());
+    (1..=max)
+        .rev()
+        .filter(|&k| k >= 2 || k == previous.len())
+        .find(|&k| previous[previous.len() - k..] == current[..k])
+        .unwrap_or(0)
+}
+
+/// Removes the repetition in YouTube's rolling auto-captions, where every cue starts with the
+/// line already shown by the cue before it. Each segment keeps only the words that continue
+/// past the end of the previous cue; segments left empty are dropped.
+pub fn dedup_rolling(segments: Vec<TranscriptSegment>) -> Vec<TranscriptSegment> {
+    let mut dedup = RollingDedup::default();
+    segments
+        .into_iter()
+        .filter_map(|seg| dedup.next(seg))
+        .collect()
+}
+
+/// [`dedup_rolling`] one segment at a time, for segments that arrive while parsing.
+#[derive(Default)]
+pub struct RollingDedup {
+    previous: Vec<String>,
+}
+
+impl RollingDedup {
+    /// The segment without the words repeated from the previous one, or `None` if nothing is
+    /// left.
+    pub fn next(&mut self, mut seg: TranscriptSegment) -> Option<TranscriptSegment> {
+        let raw: Vec<&str> = seg.text.split_whitespace().collect();
+        let words: Vec<String> = raw.iter().map(|w| normalize_word(w)).collect();
+        let skip = overlap(&self.previous, &words);
+        if skip == raw.len() {
+            self.previous = words;
+            return None;
+        }
+        if skip > 0 {
+            seg.text = raw[skip..].join(" ");
+        }
+        self.previous = words;
+        Some(seg)
+    }
+}
+
+/// Bracketed sound descriptions such as `[Music]`, `[Applause]` or `(laughs)`.
+pub const DEFAULT_ANNOTATION_PATTERNS: &[&str] = &[r"\[[^\]]{1,40}\]", r"\([^)]{1,40}\)"];
+
+/// Removes every match of `patterns` from the segment text. Segments left without text are
+/// dropped when `drop_empty` is set and kept with empty text otherwise. Segments whose text
+/// changed lose their word timings, like with [`redact`].
+pub fn strip_annotations(
+    segments: Vec<TranscriptSegment>,
+    patterns: &[Regex],
+    drop_empty: bool,
+) -> Vec<TranscriptSegment> {
+    segments
+        .into_iter()
+        .filter_map(|mut seg| {
+            for re in patterns {
+                if re.is_match(&seg.text) {
+                    seg.text = re.replace_all(&seg.text, " ").into_owned();
+                    seg.words = None;
+                }
+            }
+            seg.text = seg.text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!drop_empty || !seg.text.is_empty()).then_some(seg)
+        })
+        .collect()
+}
+
+/// A `--redact` rule: every match of `pattern` is replaced, with `$1` or `${name}` standing for
+/// a captured group.
+#[derive(Clone)]
+pub struct Redaction {
+    pub pattern: Regex,
+    pub replacement: String,
+}
+
+impl Redaction {
+    /// Parses `PATTERN=REPLACEMENT`, split at the last `=` so the pattern may contain one.
+    pub fn parse(value: &str) -> Result<Redaction, String> {
+        let (pattern, replacement) = value.rsplit_once('=').ok_or_else(|| {
+            format!(
+                "invalid redaction '{}', expected PATTERN=REPLACEMENT",
+                value
+            )
+        })?;
+        if pattern.is_empty() {
+            return Err(format!("empty pattern in redaction '{}'", value));
+        }
+        let pattern =
+            Regex::new(pattern).map_err(|e| format!("invalid pattern '{}' - {}", pattern, e))?;
+        Ok(Redaction {
+            pattern,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+/// Applies every redaction to the segment text, in order. Segments whose text changed lose
+/// their word timings, which would still hold the redacted words.
+pub fn redact(segments: &mut [TranscriptSegment], redactions: &[Redaction]) {
+    for seg in segments {
+        let mut changed = false;
+        for redaction in redactions {
+            if redaction.pattern.is_match(&seg.text) {
+                seg.text = redaction
+                    .pattern
+                    .replace_all(&seg.text, redaction.replacement.as_str())
+                    .into_owned();
+                changed = true;
+            }
+        }
+        if changed {
+            seg.words = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Word;
+
+    fn texts(segments: &[&str]) -> Vec<String> {
+        dedup_rolling(segments.iter().map(|text| TranscriptSegment::new(0.0, 1.0, text)).collect())
+            .into_iter()
+            .map(|seg| seg.text)
+            .collect()
+    }
+
+    #[test]
+    fn drops_repeated_lines() {
+        assert_eq!(
+            texts(&["hello and welcome", "hello and welcome to the show", "to the show"]),
+            ["hello and welcome", "to the show"]
+        );
+    }
+
+    #[test]
+    fn keeps_single_word_overlap() {
+        assert_eq!(
+            texts(&["that is how it works so", "so what did we learn"]),
+            ["that is how it works so", "so what did we learn"]
+        );
+    }
+
+    #[test]
+    fn stripping_annotations_drops_word_timings() {
+        let patterns: Vec<Regex> =
+            DEFAULT_ANNOTATION_PATTERNS.iter().map(|p| Regex::new(p).unwrap()).collect();
+        let word = |text: &str, start: f64| Word { text: text.to_string(), start, end: start + 0.5 };
+        let mut music = TranscriptSegment::new(0.0, 1.0, "[Music] hello");
+        music.words = Some(vec![word("[Music]", 0.0), word("hello", 0.5)]);
+        let mut plain = TranscriptSegment::new(1.0, 2.0, "world");
+        plain.words = Some(vec![word("world", 1.0)]);
+
+        let stripped = strip_annotations(vec![music, plain], &patterns, true);
+        assert_eq!(stripped[0].text, "hello");
+        assert!(stripped[0].words.is_none());
+        assert_eq!(stripped[1].words.as_ref().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn drops_repeated_single_word_cue() {
+        assert_eq!(texts(&["okay", "okay let's start"]), ["okay", "let's start"]);
+    }
+}
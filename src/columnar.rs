@@ -0,0 +1,127 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, ListArray, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Float32Type, Schema};
+use arrow::error::ArrowError;
+#[cfg(feature = "arrow")]
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parquet")]
+use parquet::basic::Compression;
+#[cfg(feature = "parquet")]
+use parquet::errors::ParquetError;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+
+use crate::chunk::Chunk;
+use crate::transcript::TranscriptResult;
+
+/// One row per segment. Every row carries the video ID and language so files from many
+/// videos can be concatenated or queried together.
+pub fn segments_batch(result: &TranscriptResult) -> Result<RecordBatch, ArrowError> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("video_id", DataType::Utf8, false),
+        Field::new("language", DataType::Utf8, false),
+        Field::new("index", DataType::UInt64, false),
+        Field::new("start_seconds", DataType::Float64, false),
+        Field::new("end_seconds", DataType::Float64, false),
+        Field::new("duration_seconds", DataType::Float64, false),
+        Field::new("text", DataType::Utf8, false),
+    ]));
+
+    let segments = &result.segments;
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![result.video_id.as_str(); segments.len()])),
+        Arc::new(StringArray::from(vec![result.language.as_str(); segments.len()])),
+        Arc::new(UInt64Array::from_iter_values(segments.iter().map(|s| s.index as u64))),
+        Arc::new(Float64Array::from_iter_values(segments.iter().map(|s| s.start_seconds))),
+        Arc::new(Float64Array::from_iter_values(segments.iter().map(|s| s.end_seconds))),
+        Arc::new(Float64Array::from_iter_values(segments.iter().map(|s| s.duration_seconds))),
+        Arc::new(StringArray::from_iter_values(segments.iter().map(|s| s.text.as_str()))),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
+
+/// One row per retrieval chunk, with its embedding as a list of floats (null without
+/// `--embed`), ready to load into a vector store.
+pub fn chunks_batch(chunks: &[Chunk]) -> Result<RecordBatch, ArrowError> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("video_id", DataType::Utf8, false),
+        Field::new("title", DataType::Utf8, true),
+        Field::new("chunk", DataType::UInt64, false),
+        Field::new("start_seconds", DataType::Float64, false),
+        Field::new("end_seconds", DataType::Float64, false),
+        Field::new("tokens", DataType::UInt64, false),
+        Field::new("text", DataType::Utf8, false),
+        Field::new("url", DataType::Utf8, false),
+        Field::new(
+            "embedding",
+            DataType::List(Arc::new(Field::new("item", DataType::Float32, true))),
+            true,
+        ),
+    ]));
+
+    let embeddings = chunks.iter().map(|c| {
+        c.embedding
+            .as_ref()
+            .map(|vector| vector.iter().copied().map(Some).collect::<Vec<_>>())
+    });
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(chunks.iter().map(|c| c.video_id.as_str()))),
+        Arc::new(StringArray::from_iter(chunks.iter().map(|c| c.title.as_deref()))),
+        Arc::new(UInt64Array::from_iter_values(chunks.iter().map(|c| c.chunk as u64))),
+        Arc::new(Float64Array::from_iter_values(chunks.iter().map(|c| c.start_seconds))),
+        Arc::new(Float64Array::from_iter_values(chunks.iter().map(|c| c.end_seconds))),
+        Arc::new(UInt64Array::from_iter_values(chunks.iter().map(|c| c.tokens as u64))),
+        Arc::new(StringArray::from_iter_values(chunks.iter().map(|c| c.text.as_str()))),
+        Arc::new(StringArray::from_iter_values(chunks.iter().map(|c| c.url.as_str()))),
+        Arc::new(ListArray::from_iter_primitive::<Float32Type, _, _>(embeddings)),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
+
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W: Write + Send>(writer: W, result: &TranscriptResult) -> Result<(), ParquetError> {
+    write_batch(writer, segments_batch(result)?)
+}
+
+#[cfg(feature = "parquet")]
+pub fn write_chunks_parquet<W: Write + Send>(writer: W, chunks: &[Chunk]) -> Result<(), ParquetError> {
+    write_batch(writer, chunks_batch(chunks)?)
+}
+
+#[cfg(feature = "parquet")]
+fn write_batch<W: Write + Send>(writer: W, batch: RecordBatch) -> Result<(), ParquetError> {
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Writes segments as an Arrow IPC file (Feather v2), which Polars, DuckDB and pyarrow map
+/// without copying.
+#[cfg(feature = "arrow")]
+pub fn write_arrow<W: Write>(writer: W, result: &TranscriptResult) -> Result<(), ArrowError> {
+    write_ipc(writer, segments_batch(result)?)
+}
+
+#[cfg(feature = "arrow")]
+pub fn write_chunks_arrow<W: Write>(writer: W, chunks: &[Chunk]) -> Result<(), ArrowError> {
+    write_ipc(writer, chunks_batch(chunks)?)
+}
+
+#[cfg(feature = "arrow")]
+fn write_ipc<W: Write>(writer: W, batch: RecordBatch) -> Result<(), ArrowError> {
+    let mut writer = FileWriter::try_new(writer, &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()
+}
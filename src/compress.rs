@@ -0,0 +1,37 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Compression applied to an output file, chosen by its extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// `.gz` for gzip and `.zst` for Zstandard; other paths are written as they are.
+    pub fn from_path(path: &str) -> Option<Compression> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        if extension.eq_ignore_ascii_case("gz") {
+            Some(Compression::Gzip)
+        } else if extension.eq_ignore_ascii_case("zst") {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Compresses `bytes` into one complete gzip member or Zstandard frame. Both formats allow
+    /// several of these back to back, so compressed transcripts can be appended to a file.
+    pub fn compress(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            Compression::Zstd => zstd::encode_all(bytes, 0),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Defaults read from `config.toml`. Every key is optional and command-line flags win over it.
+/// `[profile.<name>]` tables take the same keys and are layered on top with `--profile`.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Output format name, as given to `--format`.
+    pub format: Option<String>,
+    /// Text encoding, as given to `--encoding`.
+    pub encoding: Option<String>,
+    /// Tera template rendered instead of a built-in format.
+    pub template: Option<String>,
+    /// Caption language, or languages to try in order.
+    pub language: Option<Languages>,
+    /// Directory transcripts are written to when `--output` is not given.
+    pub output_dir: Option<PathBuf>,
+    pub no_timestamps: Option<bool>,
+    /// Link TXT and md-notes timestamps to the video, as `--link-timestamps` does.
+    pub link_timestamps: Option<bool>,
+    /// When to color TXT output: `auto`, `always` or `never`.
+    pub color: Option<String>,
+    /// Page output on a terminal, as `--pager` does.
+    pub pager: Option<bool>,
+    /// Speaker label handling: `keep`, `strip` or `group`.
+    pub speakers: Option<String>,
+    pub strip_annotations: Option<bool>,
+    pub drop_empty: Option<bool>,
+    pub sentences: Option<bool>,
+    pub paragraphs: Option<bool>,
+    pub max_line_length: Option<usize>,
+    pub max_lines: Option<usize>,
+    pub keywords: Option<usize>,
+    /// Mask profanity, as `--censor` does.
+    pub censor: Option<bool>,
+    /// Wordlist file for `--censor`, as given to `--censor-words`.
+    pub censor_words: Option<PathBuf>,
+    /// Summarize with an OpenAI-compatible endpoint, as `--summarize` does.
+    pub summarize: Option<bool>,
+    /// Generate chapters for videos without them, as `--auto-chapters` does.
+    pub auto_chapters: Option<bool>,
+    /// yt-dlp executable or command line, as given to `--yt-dlp-path`.
+    pub yt_dlp_path: Option<String>,
+    /// Extra yt-dlp arguments, as given to `--yt-dlp-args`.
+    pub yt_dlp_args: Option<String>,
+    /// Executables to try when yt-dlp is missing, as given to `--yt-dlp-candidates`.
+    pub yt_dlp_candidates: Option<Vec<String>>,
+    /// Proxy URL, as given to `--proxy`.
+    pub proxy: Option<String>,
+    /// Netscape cookies file, as given to `--cookies`.
+    pub cookies: Option<PathBuf>,
+    /// Browser to read cookies from, as given to `--cookies-from-browser`.
+    pub cookies_from_browser: Option<String>,
+    /// PO token(s), as given to `--po-token`.
+    pub po_token: Option<String>,
+    /// YouTube player clients, as given to `--player-client`.
+    pub player_client: Option<Vec<String>>,
+    /// Country code, as given to `--geo-bypass-country`.
+    pub geo_bypass_country: Option<String>,
+    /// Retries of temporary yt-dlp failures, as given to `--retries`.
+    pub retries: Option<u32>,
+    /// Time limit for yt-dlp and HTTP requests, as given to `--timeout`.
+    pub timeout: Option<String>,
+    /// Videos transcribed at the same time, as given to `--jobs`.
+    pub jobs: Option<usize>,
+    /// OpenAI-compatible endpoint of `--summarize`, `--auto-chapters` and `--embed`.
+    pub llm: Option<LlmConfig>,
+    pub profile: BTreeMap<String, Config>,
+}
+
+/// The `[llm]` table. Keys left out fall back to the `OPENAI_*` environment variables and then
+/// to OpenAI's defaults.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmConfig {
+    /// API root, e.g. `http://localhost:11434/v1` for Ollama or `http://localhost:8000/v1` for
+    /// vLLM.
+    pub base_url: Option<String>,
+    /// Environment variable the API key is read from, instead of `OPENAI_API_KEY`.
+    pub api_key_env: Option<String>,
+    /// Chat model of `--summarize` and `--auto-chapters`.
+    pub model: Option<String>,
+    /// Model of `--embed`.
+    pub embedding_model: Option<String>,
+    pub temperature: Option<f64>,
+    /// Requests in flight at once, across all videos of a run.
+    pub max_concurrency: Option<usize>,
+}
+
+impl LlmConfig {
+    /// These settings with `base`'s filling the gaps.
+    fn or(self, base: LlmConfig) -> LlmConfig {
+        LlmConfig {
+            base_url: self.base_url.or(base.base_url),
+            api_key_env: self.api_key_env.or(base.api_key_env),
+            model: self.model.or(base.model),
+            embedding_model: self.embedding_model.or(base.embedding_model),
+            temperature: self.temperature.or(base.temperature),
+            max_concurrency: self.max_concurrency.or(base.max_concurrency),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Languages {
+    One(String),
+    Chain(Vec<String>),
+}
+
+impl Languages {
+    /// The comma-separated form accepted by `--language`.
+    pub fn to_arg(&self) -> String {
+        match self {
+            Languages::One(language) => language.clone(),
+            Languages::Chain(languages) => languages.join(","),
+        }
+    }
+}
+
+impl Config {
+    /// `$YT_TRANSCRIBER_CONFIG`, or `$XDG_CONFIG_HOME/yt-transcriber/config.toml` and the
+    /// platform equivalent.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("YT_TRANSCRIBER_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        dirs::config_dir().map(|dir| dir.join("yt-transcriber").join("config.toml"))
+    }
+
+    /// The top-level settings with those of profile `name` layered on top, or `None` if the
+    /// profile is not defined.
+    pub fn with_profile(mut self, name: &str) -> Option<Config> {
+        let profile = self.profile.remove(name)?;
+        Some(Config {
+            format: profile.format.or(self.format),
+            encoding: profile.encoding.or(self.encoding),
+            template: profile.template.or(self.template),
+            language: profile.language.or(self.language),
+            output_dir: profile.output_dir.or(self.output_dir),
+            no_timestamps: profile.no_timestamps.or(self.no_timestamps),
+            link_timestamps: profile.link_timestamps.or(self.link_timestamps),
+            color: profile.color.or(self.color),
+            pager: profile.pager.or(self.pager),
+            speakers: profile.speakers.or(self.speakers),
+            strip_annotations: profile.strip_annotations.or(self.strip_annotations),
+            drop_empty: profile.drop_empty.or(self.drop_empty),
+            sentences: profile.sentences.or(self.sentences),
+            paragraphs: profile.paragraphs.or(self.paragraphs),
+            max_line_length: profile.max_line_length.or(self.max_line_length),
+            max_lines: profile.max_lines.or(self.max_lines),
+            keywords: profile.keywords.or(self.keywords),
+            censor: profile.censor.or(self.censor),
+            censor_words: profile.censor_words.or(self.censor_words),
+            summarize: profile.summarize.or(self.summarize),
+            auto_chapters: profile.auto_chapters.or(self.auto_chapters),
+            yt_dlp_path: profile.yt_dlp_path.or(self.yt_dlp_path),
+            yt_dlp_args: profile.yt_dlp_args.or(self.yt_dlp_args),
+            yt_dlp_candidates: profile.yt_dlp_candidates.or(self.yt_dlp_candidates),
+            proxy: profile.proxy.or(self.proxy),
+            cookies: profile.cookies.or(self.cookies),
+            cookies_from_browser: profile.cookies_from_browser.or(self.cookies_from_browser),
+            po_token: profile.po_token.or(self.po_token),
+            player_client: profile.player_client.or(self.player_client),
+            geo_bypass_country: profile.geo_bypass_country.or(self.geo_bypass_country),
+            retries: profile.retries.or(self.retries),
+            timeout: profile.timeout.or(self.timeout),
+            jobs: profile.jobs.or(self.jobs),
+            llm: match (profile.llm, self.llm) {
+                (Some(profile), Some(base)) => Some(profile.or(base)),
+                (profile, base) => profile.or(base),
+            },
+            profile: BTreeMap::new(),
+        })
+    }
+
+    /// Reads a config file. A missing file yields `None`.
+    pub fn load(path: &Path) -> Result<Option<Config>, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut config: Config = toml::from_str(&content).map_err(|e| e.to_string())?;
+        config.output_dir = config.output_dir.map(expand_home);
+        config.yt_dlp_path = config.yt_dlp_path.map(expand_home_str);
+        config.cookies = config.cookies.map(expand_home);
+        config.censor_words = config.censor_words.map(expand_home);
+        for profile in config.profile.values_mut() {
+            profile.output_dir = profile.output_dir.take().map(expand_home);
+            profile.yt_dlp_path = profile.yt_dlp_path.take().map(expand_home_str);
+            profile.cookies = profile.cookies.take().map(expand_home);
+            profile.censor_words = profile.censor_words.take().map(expand_home);
+        }
+        Ok(Some(config))
+    }
+}
+
+/// Expands a leading `~` to the home directory.
+fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path,
+    }
+}
+
+fn expand_home_str(path: String) -> String {
+    expand_home(PathBuf::from(path))
+        .to_string_lossy()
+        .into_owned()
+}
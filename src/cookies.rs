@@ -0,0 +1,63 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// One cookie from a Netscape `cookies.txt` file, the format yt-dlp and browser export
+/// extensions use.
+pub struct Cookie {
+    /// Domain, with a leading `.` when subdomains match too.
+    pub domain: String,
+    pub path: String,
+    pub secure: bool,
+    /// Unix time the cookie expires at; 0 for session cookies.
+    pub expires: i64,
+    pub name: String,
+    pub value: String,
+}
+
+/// Reads a Netscape cookie file. Comments and malformed lines are skipped; `#HttpOnly_` lines
+/// are cookies.
+pub fn load(path: &Path) -> io::Result<Vec<Cookie>> {
+    Ok(parse(&fs::read_to_string(path)?))
+}
+
+pub fn parse(content: &str) -> Vec<Cookie> {
+    content
+        .lines()
+        .map(|line| line.strip_prefix("#HttpOnly_").unwrap_or(line))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.trim_end_matches('\r').split('\t').collect();
+            let [domain, _, path, secure, expires, name, value] = fields[..] else {
+                return None;
+            };
+            Some(Cookie {
+                domain: domain.to_ascii_lowercase(),
+                path: path.to_string(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                expires: expires.parse().unwrap_or(0),
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The `Cookie` header value for a request to `url`, if any cookie applies.
+pub fn header_for(cookies: &[Cookie], url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    let now = chrono::Utc::now().timestamp();
+    let pairs: Vec<String> = cookies
+        .iter()
+        .filter(|cookie| {
+            let domain = cookie.domain.trim_start_matches('.');
+            host == domain || host.ends_with(&format!(".{}", domain))
+        })
+        .filter(|cookie| url.path().starts_with(&cookie.path))
+        .filter(|cookie| !cookie.secure || url.scheme() == "https")
+        .filter(|cookie| cookie.expires == 0 || cookie.expires > now)
+        .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+        .collect();
+    (!pairs.is_empty()).then(|| pairs.join("; "))
+}
@@ -0,0 +1,190 @@
+use serde::Serialize;
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+use crate::stats::normalize_word;
+use crate::transcript::TranscriptSegment;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One contiguous difference, positioned in time on both sides.
+#[derive(Serialize)]
+pub struct DiffHunk {
+    pub kind: ChangeKind,
+    pub old_seconds: f64,
+    pub new_seconds: f64,
+    pub removed: String,
+    pub added: String,
+}
+
+struct Word<'a> {
+    text: &'a str,
+    seconds: f64,
+}
+
+fn words(segments: &[TranscriptSegment]) -> Vec<Word<'_>> {
+    segments
+        .iter()
+        .flat_map(|seg| {
+            seg.text.split_whitespace().map(move |text| Word {
+                text,
+                seconds: seg.start_seconds,
+            })
+        })
+        .collect()
+}
+
+fn join(words: &[Word]) -> String {
+    words.iter().map(|w| w.text).collect::<Vec<_>>().join(" ")
+}
+
+/// Word-level diff of two transcripts. Words are compared case- and punctuation-insensitively,
+/// so differences in caption styling alone are not reported.
+pub fn diff_transcripts(old: &[TranscriptSegment], new: &[TranscriptSegment]) -> Vec<DiffHunk> {
+    let old_words = words(old);
+    let new_words = words(new);
+    let old_keys: Vec<String> = old_words.iter().map(|w| normalize_word(w.text)).collect();
+    let new_keys: Vec<String> = new_words.iter().map(|w| normalize_word(w.text)).collect();
+
+    let time_at = |words: &[Word], i: usize| {
+        words
+            .get(i)
+            .or_else(|| words.last())
+            .map_or(0.0, |w| w.seconds)
+    };
+
+    capture_diff_slices(Algorithm::Myers, &old_keys, &new_keys)
+        .iter()
+        .filter_map(|op| {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            let kind = match tag {
+                DiffTag::Equal => return None,
+                DiffTag::Delete => ChangeKind::Removed,
+                DiffTag::Insert => ChangeKind::Added,
+                DiffTag::Replace => ChangeKind::Changed,
+            };
+            Some(DiffHunk {
+                kind,
+                old_seconds: time_at(&old_words, old_range.start),
+                new_seconds: time_at(&new_words, new_range.start),
+                removed: join(&old_words[old_range]),
+                added: join(&new_words[new_range]),
+            })
+        })
+        .collect()
+}
+
+/// Minimum number of word substitutions, deletions and insertions turning `reference` into
+/// `hypothesis`.
+pub fn word_edit_distance(reference: &[String], hypothesis: &[String]) -> usize {
+    let mut prev: Vec<usize> = (0..=hypothesis.len()).collect();
+    let mut cur = vec![0; hypothesis.len() + 1];
+
+    for (i, r) in reference.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, h) in hypothesis.iter().enumerate() {
+            let cost = if r == h { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[hypothesis.len()]
+}
+
+fn normalized_words<'a>(segments: impl IntoIterator<Item = &'a TranscriptSegment>) -> Vec<String> {
+    segments
+        .into_iter()
+        .flat_map(|seg| seg.text.split_whitespace())
+        .map(normalize_word)
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn error_rate(errors: usize, reference_words: usize) -> f64 {
+    if reference_words == 0 {
+        if errors == 0 {
+            0.0
+        } else {
+            1.0
+        }
+    } else {
+        errors as f64 / reference_words as f64
+    }
+}
+
+#[derive(Serialize)]
+pub struct SegmentMismatch {
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub word_error_rate: f64,
+    pub reference: String,
+    pub hypothesis: String,
+}
+
+#[derive(Serialize)]
+pub struct CaptionAudit {
+    pub reference_words: usize,
+    pub hypothesis_words: usize,
+    pub word_errors: usize,
+    pub word_error_rate: f64,
+    pub mismatches: Vec<SegmentMismatch>,
+}
+
+/// Scores `hypothesis` (typically auto-generated captions) against `reference` (human
+/// captions). Each hypothesis segment is assigned to the reference segment in effect at its
+/// midpoint, and reference segments whose error rate exceeds `threshold` are listed.
+pub fn audit_captions(
+    reference: &[TranscriptSegment],
+    hypothesis: &[TranscriptSegment],
+    threshold: f64,
+) -> CaptionAudit {
+    let reference_words = normalized_words(reference);
+    let hypothesis_words = normalized_words(hypothesis);
+    let word_errors = word_edit_distance(&reference_words, &hypothesis_words);
+
+    let mut mismatches = Vec::new();
+    for (i, seg) in reference.iter().enumerate() {
+        let window_end = reference
+            .get(i + 1)
+            .map_or(f64::INFINITY, |next| next.start_seconds);
+        let overlapping: Vec<&TranscriptSegment> = hypothesis
+            .iter()
+            .filter(|h| {
+                let mid = (h.start_seconds + h.end_seconds) / 2.0;
+                mid >= seg.start_seconds && mid < window_end
+            })
+            .collect();
+
+        let ref_words = normalized_words([seg]);
+        let hyp_words = normalized_words(overlapping.iter().copied());
+        let rate = error_rate(word_edit_distance(&ref_words, &hyp_words), ref_words.len());
+
+        if rate > threshold {
+            mismatches.push(SegmentMismatch {
+                start_seconds: seg.start_seconds,
+                end_seconds: seg.end_seconds,
+                word_error_rate: rate,
+                reference: seg.text.clone(),
+                hypothesis: overlapping
+                    .iter()
+                    .map(|h| h.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            });
+        }
+    }
+
+    CaptionAudit {
+        reference_words: reference_words.len(),
+        hypothesis_words: hypothesis_words.len(),
+        word_errors,
+        word_error_rate: error_rate(word_errors, reference_words.len()),
+        mismatches,
+    }
+}
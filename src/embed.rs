@@ -0,0 +1,22 @@
+use std::sync::Mutex;
+
+use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+
+use crate::cache::Cache;
+
+/// Loaded on first use and shared by every video of a run.
+static MODEL: Mutex<Option<TextEmbedding>> = Mutex::new(None);
+
+/// Embeds each text on the CPU with all-MiniLM-L6-v2 (384 dimensions). The model, about 90 MB,
+/// is downloaded to `models/` in the cache directory the first time.
+pub fn embed_local(texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let mut model = MODEL.lock().map_err(|e| e.to_string())?;
+    if model.is_none() {
+        let options = InitOptions::new(EmbeddingModel::AllMiniLML6V2)
+            .with_cache_dir(Cache::default_dir().join("models"))
+            .with_show_download_progress(false);
+        *model = Some(TextEmbedding::try_new(options).map_err(|e| e.to_string())?);
+    }
+    let model = model.as_ref().expect("model was just loaded");
+    model.embed(texts.to_vec(), None).map_err(|e| e.to_string())
+}
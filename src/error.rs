@@ -0,0 +1,153 @@
+use std::io;
+
+use thiserror::Error;
+
+use crate::exit::Exit;
+use crate::transcriber::Backend;
+use crate::yt_dlp;
+
+/// Why a transcript could not be fetched or parsed.
+#[derive(Debug, Error)]
+pub enum TranscriberError {
+    /// The input is neither a YouTube URL nor a video ID.
+    #[error("Invalid YouTube URL or video ID")]
+    InvalidUrl(String),
+    /// The video has no captions of the requested track in `language`. `langs_available` lists
+    /// the languages it does have, when the backend could tell.
+    #[error(
+        "No subtitles available for this video in '{language}' language{}",
+        available(.langs_available)
+    )]
+    NoCaptions {
+        language: String,
+        langs_available: Vec<String>,
+    },
+    /// The subtitles were found but hold no text.
+    #[error("No transcript content found")]
+    EmptyTranscript,
+    /// The video is private, deleted or otherwise restricted.
+    #[error("Video is unavailable ({0})")]
+    VideoUnavailable(String),
+    #[error("Video is not available in this region - {0}")]
+    GeoRestricted(String),
+    /// YouTube wants proof that the client is not a bot, such as cookies or a PO token.
+    #[error("YouTube asked to confirm this is not a bot - {0}")]
+    BotCheck(String),
+    #[error("YouTube is rate limiting requests - {0}")]
+    RateLimited(String),
+    #[error("Failed to reach YouTube - {0}")]
+    Network(String),
+    /// yt-dlp did not finish within [`TranscriberBuilder::timeout`](crate::transcriber::TranscriberBuilder::timeout).
+    #[error("{0}")]
+    TimedOut(String),
+    /// yt-dlp is not installed or does not run. `hint` says how to get it, when known.
+    #[error("{message}")]
+    YtDlpMissing {
+        message: String,
+        hint: Option<String>,
+    },
+    /// The backend failed for any other reason.
+    #[error("{message}")]
+    BackendFailure { backend: Backend, message: String },
+    #[error("I/O error - {0}")]
+    Io(#[from] io::Error),
+}
+
+fn available(languages: &[String]) -> String {
+    if languages.is_empty() {
+        String::new()
+    } else {
+        format!(" (available: {})", languages.join(", "))
+    }
+}
+
+impl TranscriberError {
+    /// Classifies a failed yt-dlp run by its stderr.
+    pub fn from_yt_dlp(stderr: &str) -> Self {
+        let stderr = stderr.trim();
+        if yt_dlp::is_bot_check(stderr) {
+            TranscriberError::BotCheck(stderr.to_string())
+        } else if yt_dlp::is_geo_restricted(stderr) {
+            TranscriberError::GeoRestricted(stderr.to_string())
+        } else if stderr.contains("unavailable")
+            || stderr.contains("private")
+            || stderr.contains("deleted")
+        {
+            TranscriberError::VideoUnavailable("private/deleted/restricted".to_string())
+        } else if yt_dlp::is_rate_limited(stderr) {
+            TranscriberError::RateLimited(stderr.to_string())
+        } else if yt_dlp::is_network_error(stderr) {
+            TranscriberError::Network(stderr.to_string())
+        } else {
+            TranscriberError::BackendFailure {
+                backend: Backend::YtDlp,
+                message: format!("yt-dlp failed - {}", stderr),
+            }
+        }
+    }
+
+    /// Why yt-dlp could not be run to completion.
+    pub fn from_run_failure(e: &io::Error) -> Self {
+        if e.kind() == io::ErrorKind::TimedOut {
+            TranscriberError::TimedOut(e.to_string())
+        } else {
+            TranscriberError::YtDlpMissing {
+                message: format!("Failed to run yt-dlp - {}", e),
+                hint: None,
+            }
+        }
+    }
+
+    /// What to do about the error, when the code that raised it knew.
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            TranscriberError::YtDlpMissing { hint, .. } => hint.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Stable identifier, as in the `error` field of `--error-format json`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TranscriberError::InvalidUrl(_) => "invalid_url",
+            TranscriberError::NoCaptions { .. } | TranscriberError::EmptyTranscript => {
+                "no_subtitles"
+            }
+            TranscriberError::VideoUnavailable(_) => "video_unavailable",
+            TranscriberError::GeoRestricted(_) => "geo_restricted",
+            TranscriberError::BotCheck(_) => "bot_check",
+            TranscriberError::RateLimited(_) => "rate_limited",
+            TranscriberError::Network(_) => "network_error",
+            TranscriberError::TimedOut(_) => "timed_out",
+            TranscriberError::YtDlpMissing { .. } => "yt_dlp_missing",
+            TranscriberError::BackendFailure {
+                backend: Backend::YtDlp,
+                ..
+            } => "yt_dlp_failed",
+            TranscriberError::BackendFailure {
+                backend: Backend::Native,
+                ..
+            } => "native_failed",
+            TranscriberError::Io(_) => "io_error",
+        }
+    }
+
+    /// The exit status the CLI ends with.
+    pub fn exit(&self) -> Exit {
+        match self {
+            TranscriberError::InvalidUrl(_) => Exit::InvalidUrl,
+            TranscriberError::NoCaptions { .. } | TranscriberError::EmptyTranscript => {
+                Exit::NoCaptions
+            }
+            TranscriberError::VideoUnavailable(_)
+            | TranscriberError::GeoRestricted(_)
+            | TranscriberError::BotCheck(_)
+            | TranscriberError::BackendFailure { .. } => Exit::Unavailable,
+            TranscriberError::RateLimited(_)
+            | TranscriberError::Network(_)
+            | TranscriberError::TimedOut(_) => Exit::Network,
+            TranscriberError::YtDlpMissing { .. } => Exit::DependencyMissing,
+            TranscriberError::Io(_) => Exit::WriteFailed,
+        }
+    }
+}
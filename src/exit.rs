@@ -0,0 +1,93 @@
+use std::process::ExitCode;
+
+/// Process exit statuses. The numbers and names are part of the command-line interface and do
+/// not change between releases; new outcomes get new numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    Success,
+    /// An option, input file or config value could not be used.
+    InvalidArgs,
+    /// The video is private, deleted or restricted, or yt-dlp could not extract it.
+    Unavailable,
+    /// YouTube or another remote could not be reached.
+    Network,
+    /// Output, archive or state files could not be written.
+    WriteFailed,
+    /// The input is not a YouTube URL or video ID, or the wrong kind of URL for the command.
+    InvalidUrl,
+    /// The video has no captions in any requested language.
+    NoCaptions,
+    /// yt-dlp is not installed and could not be installed.
+    DependencyMissing,
+    /// Some videos of a playlist, channel or batch failed while others succeeded.
+    PartialFailure,
+    /// A downloaded update did not match its published checksum.
+    ChecksumMismatch,
+}
+
+impl Exit {
+    pub const ALL: [Exit; 10] = [
+        Exit::Success,
+        Exit::InvalidArgs,
+        Exit::Unavailable,
+        Exit::Network,
+        Exit::WriteFailed,
+        Exit::InvalidUrl,
+        Exit::NoCaptions,
+        Exit::DependencyMissing,
+        Exit::PartialFailure,
+        Exit::ChecksumMismatch,
+    ];
+
+    pub fn code(self) -> u8 {
+        match self {
+            Exit::Success => 0,
+            Exit::InvalidArgs => 1,
+            Exit::Unavailable => 2,
+            Exit::Network => 3,
+            Exit::WriteFailed => 4,
+            Exit::InvalidUrl => 5,
+            Exit::NoCaptions => 6,
+            Exit::DependencyMissing => 7,
+            Exit::PartialFailure => 8,
+            Exit::ChecksumMismatch => 9,
+        }
+    }
+
+    /// Stable identifier, as printed by `--list-exit-codes`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Exit::Success => "success",
+            Exit::InvalidArgs => "invalid_args",
+            Exit::Unavailable => "unavailable",
+            Exit::Network => "network",
+            Exit::WriteFailed => "write_failed",
+            Exit::InvalidUrl => "invalid_url",
+            Exit::NoCaptions => "no_captions",
+            Exit::DependencyMissing => "dependency_missing",
+            Exit::PartialFailure => "partial_failure",
+            Exit::ChecksumMismatch => "checksum_mismatch",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Exit::Success => "Success",
+            Exit::InvalidArgs => "Invalid arguments, input file or config",
+            Exit::Unavailable => "Video unavailable (private, deleted or restricted)",
+            Exit::Network => "Network error",
+            Exit::WriteFailed => "File write error",
+            Exit::InvalidUrl => "Invalid YouTube URL or video ID",
+            Exit::NoCaptions => "No captions in the requested language",
+            Exit::DependencyMissing => "yt-dlp not available",
+            Exit::PartialFailure => "Some videos of a playlist or channel failed",
+            Exit::ChecksumMismatch => "Downloaded update did not match its checksum",
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit.code())
+    }
+}
@@ -0,0 +1,22 @@
+use regex::Regex;
+
+use crate::video_id::extract_video_id;
+
+/// Video IDs listed in an RSS or Atom feed, in feed order without duplicates. YouTube feeds
+/// carry `<yt:videoId>` elements; other feeds are searched for links to YouTube videos.
+pub fn feed_video_ids(xml: &str) -> Vec<String> {
+    let id_re = Regex::new(r"<yt:videoId>\s*([A-Za-z0-9_-]{11})\s*</yt:videoId>").unwrap();
+    let link_re = Regex::new(r#"(?:href="|<link>|<guid[^>]*>)\s*([^"<\s]+)"#).unwrap();
+
+    let mut ids: Vec<String> = id_re.captures_iter(xml).map(|c| c[1].to_string()).collect();
+    if ids.is_empty() {
+        ids = link_re
+            .captures_iter(xml)
+            .filter_map(|c| extract_video_id(&c[1].replace("&amp;", "&")))
+            .collect();
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+    ids
+}
@@ -0,0 +1,708 @@
+use std::collections::BTreeMap;
+
+use crate::transcript::{CueSettings, TranscriptResult, TranscriptSegment, Word};
+
+/// Renders a transcript into one output format.
+pub trait Formatter: Send + Sync {
+    fn render(&self, result: &TranscriptResult) -> String;
+}
+
+impl<F> Formatter for F
+where
+    F: Fn(&TranscriptResult) -> String + Send + Sync,
+{
+    fn render(&self, result: &TranscriptResult) -> String {
+        self(result)
+    }
+}
+
+pub struct TxtFormatter {
+    pub timestamps: bool,
+    /// Write timestamps as links that open the video at that point.
+    pub links: bool,
+    /// Separate segments with blank lines, for transcripts merged into paragraphs.
+    pub paragraphs: bool,
+}
+
+impl Formatter for TxtFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_txt(result, self.timestamps, self.links, self.paragraphs)
+    }
+}
+
+pub struct SrtFormatter;
+
+impl Formatter for SrtFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_srt(result)
+    }
+}
+
+pub struct VttFormatter;
+
+impl Formatter for VttFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_vtt(result)
+    }
+}
+
+pub struct AssFormatter {
+    /// Highlight each word with `\k` tags using the captions' word timings.
+    pub karaoke: bool,
+}
+
+impl Formatter for AssFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_ass(result, self.karaoke)
+    }
+}
+
+pub struct LrcFormatter;
+
+impl Formatter for LrcFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_lrc(result)
+    }
+}
+
+pub struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_json(result)
+    }
+}
+
+pub struct JsonlFormatter;
+
+impl Formatter for JsonlFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_jsonl(result)
+    }
+}
+
+pub struct CsvFormatter {
+    /// Start with the column names; off when adding rows to an existing file.
+    pub header: bool,
+}
+
+impl Formatter for CsvFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_csv(result, self.header)
+    }
+}
+
+pub struct MdNotesFormatter {
+    pub paragraphs: bool,
+    /// Write timestamps as links that open the video at that point.
+    pub links: bool,
+}
+
+impl Formatter for MdNotesFormatter {
+    fn render(&self, result: &TranscriptResult) -> String {
+        format_md_notes(result, self.paragraphs, self.links)
+    }
+}
+
+/// Output formats by name. Embedders can register their own formats next to the built-in ones.
+#[derive(Default)]
+pub struct FormatRegistry {
+    formatters: BTreeMap<String, Box<dyn Formatter>>,
+}
+
+impl FormatRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(
+            "txt",
+            TxtFormatter {
+                timestamps: true,
+                links: false,
+                paragraphs: false,
+            },
+        );
+        registry.register("srt", SrtFormatter);
+        registry.register("vtt", VttFormatter);
+        registry.register("ass", AssFormatter { karaoke: false });
+        registry.register("ass-karaoke", AssFormatter { karaoke: true });
+        registry.register("lrc", LrcFormatter);
+        registry.register("json", JsonFormatter);
+        registry.register("jsonl", JsonlFormatter);
+        registry.register("csv", CsvFormatter { header: true });
+        registry.register("md-notes", MdNotesFormatter { paragraphs: false, links: false });
+        registry
+    }
+
+    /// Adds a format, replacing any existing one with the same name.
+    pub fn register(&mut self, name: impl Into<String>, formatter: impl Formatter + 'static) {
+        self.formatters.insert(name.into(), Box::new(formatter));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Formatter> {
+        self.formatters.get(name).map(|f| f.as_ref())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.formatters.keys().map(String::as_str)
+    }
+
+    pub fn render(&self, name: &str, result: &TranscriptResult) -> Option<String> {
+        self.get(name).map(|f| f.render(result))
+    }
+}
+
+/// MIME type for an output file extension.
+pub fn content_type(extension: &str) -> &'static str {
+    match extension {
+        "txt" | "lrc" => "text/plain; charset=utf-8",
+        "srt" => "application/x-subrip",
+        "vtt" => "text/vtt",
+        "ass" => "text/x-ssa",
+        "json" => "application/json",
+        "jsonl" => "application/x-ndjson",
+        "csv" => "text/csv; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "parquet" => "application/vnd.apache.parquet",
+        "arrow" => "application/vnd.apache.arrow.file",
+        "msgpack" => "application/vnd.msgpack",
+        "pb" => "application/x-protobuf",
+        "gz" => "application/gzip",
+        "zst" => "application/zstd",
+        _ => "application/octet-stream",
+    }
+}
+
+pub fn format_timestamp_bracket(seconds: f64) -> String {
+    let mins = (seconds / 60.0).floor() as u32;
+    let secs = (seconds % 60.0).floor() as u32;
+    format!("[{:02}:{:02}]", mins, secs)
+}
+
+pub fn format_timestamp_srt(seconds: f64) -> String {
+    let hours = (seconds / 3600.0).floor() as u32;
+    let mins = ((seconds % 3600.0) / 60.0).floor() as u32;
+    let secs = (seconds % 60.0).floor() as u32;
+    let millis = ((seconds % 1.0) * 1000.0).floor() as u32;
+    format!("{:02}:{:02}:{:02},{:03}", hours, mins, secs, millis)
+}
+
+pub fn format_timestamp_vtt(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+pub fn format_timestamp_ass(seconds: f64) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u64;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centis / 360_000,
+        centis / 6000 % 60,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+pub fn format_timestamp_lrc(seconds: f64) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u64;
+    format!("{:02}:{:02}.{:02}", centis / 6000, centis / 100 % 60, centis % 100)
+}
+
+/// Short link that opens the video at the given offset.
+pub fn youtube_link(video_id: &str, seconds: f64) -> String {
+    format!("https://youtu.be/{}?t={}", video_id, seconds.floor() as u64)
+}
+
+/// Watch page link that starts the video at the given offset.
+pub fn watch_link(video_id: &str, seconds: f64) -> String {
+    format!("https://www.youtube.com/watch?v={}&t={}s", video_id, seconds.floor() as u64)
+}
+
+/// A timestamp as a Markdown link to the video at that point: `[01:05](https://...&t=65s)`.
+pub fn linked_timestamp(video_id: &str, seconds: f64) -> String {
+    format!(
+        "[{}]({})",
+        format_timestamp_bracket(seconds).trim_matches(['[', ']']),
+        watch_link(video_id, seconds)
+    )
+}
+
+pub fn format_txt(result: &TranscriptResult, include_timestamps: bool, links: bool, paragraphs: bool) -> String {
+    let mut out = String::new();
+    if let Some(toc) = result.toc.as_ref().filter(|t| !t.is_empty()) {
+        out.push_str("=== Contents ===\n\n");
+        for entry in toc {
+            out.push_str(&format!("{} {} - {}\n", format_timestamp_bracket(entry.seconds), entry.text, entry.url));
+        }
+        out.push_str("\n=== Transcript ===\n\n");
+    }
+    out += &result
+        .segments
+        .iter()
+        .map(|seg| {
+            if links && include_timestamps {
+                format!("{} {}", linked_timestamp(&result.video_id, seg.start_seconds), seg.text)
+            } else {
+                txt_line(seg, include_timestamps)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(if paragraphs { "\n\n" } else { "\n" });
+    if let Some(summary) = &result.summary {
+        out.push_str("\n\n=== Summary ===\n\n");
+        out.push_str(&summary.text);
+        out.push('\n');
+        if !summary.key_points.is_empty() {
+            out.push('\n');
+        }
+        for point in &summary.key_points {
+            let time = if links {
+                linked_timestamp(&result.video_id, point.seconds)
+            } else {
+                format_timestamp_bracket(point.seconds)
+            };
+            out.push_str(&format!("{} {}\n", time, point.text));
+        }
+    }
+    if let Some(marks) = result.marks.as_ref().filter(|m| !m.is_empty()) {
+        out.truncate(out.trim_end().len());
+        out.push_str("\n\n=== Marks ===\n\n");
+        for mark in marks {
+            out.push_str(&format!(
+                "{} {} - {}\n    {}\n",
+                format_timestamp_bracket(mark.seconds),
+                mark.keyword,
+                mark.url,
+                mark.text.replace('\n', " ")
+            ));
+        }
+    }
+    out
+}
+
+/// One segment of TXT output.
+pub fn txt_line(seg: &TranscriptSegment, include_timestamps: bool) -> String {
+    if include_timestamps {
+        format!("{} {}", format_timestamp_bracket(seg.start_seconds), seg.text)
+    } else {
+        seg.text.clone()
+    }
+}
+
+pub fn format_srt(result: &TranscriptResult) -> String {
+    result
+        .segments
+        .iter()
+        .enumerate()
+        .map(|(i, seg)| {
+            format!(
+                "{}\n{} --> {}\n{}",
+                i + 1,
+                format_timestamp_srt(seg.start_seconds),
+                format_timestamp_srt(seg.end_seconds),
+                seg.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn vtt_settings(settings: &CueSettings) -> String {
+    [
+        ("vertical", &settings.vertical),
+        ("line", &settings.line),
+        ("position", &settings.position),
+        ("size", &settings.size),
+        ("align", &settings.align),
+        ("region", &settings.region),
+    ]
+    .iter()
+    .filter_map(|(name, value)| value.as_ref().map(|v| format!(" {}:{}", name, v)))
+    .collect()
+}
+
+pub fn format_vtt(result: &TranscriptResult) -> String {
+    let mut out = String::from("WEBVTT\n");
+    out.push_str(&format!("Language: {}\n", result.language));
+    for seg in &result.segments {
+        out.push_str(&format!(
+            "\n{} --> {}{}\n{}\n",
+            format_timestamp_vtt(seg.start_seconds),
+            format_timestamp_vtt(seg.end_seconds),
+            seg.settings.as_ref().map(vtt_settings).unwrap_or_default(),
+            seg.text.replace("-->", "->")
+        ));
+    }
+    out
+}
+
+const ASS_PLAY_RES: (f64, f64) = (384.0, 288.0);
+const ASS_MARGIN_V: f64 = 10.0;
+
+fn percent(value: &str) -> Option<f64> {
+    value.split(',').next()?.strip_suffix('%')?.parse().ok()
+}
+
+/// Translates VTT positioning into an ASS override block: `align` picks the column and `line`
+/// the row of the `\an` alignment, and a percentage `position` pins the cue with `\pos`.
+fn ass_override(settings: &CueSettings) -> String {
+    let column = match settings.align.as_deref() {
+        Some("start" | "left") => 1,
+        Some("end" | "right") => 3,
+        _ => 2,
+    };
+    let line = settings.line.as_deref();
+    let line_percent = line.and_then(percent);
+    let row = match (line_percent, line.and_then(|l| l.split(',').next()?.parse::<i32>().ok())) {
+        (Some(p), _) if p < 100.0 / 3.0 => 6,
+        (Some(p), _) if p < 200.0 / 3.0 => 3,
+        (None, Some(n)) if n >= 0 => 6,
+        _ => 0,
+    };
+    let alignment = row + column;
+
+    match settings.position.as_deref().and_then(percent) {
+        Some(x) => {
+            let (width, height) = ASS_PLAY_RES;
+            let y = match (line_percent, row) {
+                (Some(p), _) => p / 100.0 * height,
+                (None, 6) => ASS_MARGIN_V,
+                (None, 3) => height / 2.0,
+                _ => height - ASS_MARGIN_V,
+            };
+            format!("{{\\an{}\\pos({:.0},{:.0})}}", alignment, x / 100.0 * width, y)
+        }
+        None if alignment != 2 => format!("{{\\an{}}}", alignment),
+        None => String::new(),
+    }
+}
+
+fn escape_ass_text(text: &str) -> String {
+    text.replace('{', "\\{").replace('}', "\\}").replace('\n', "\\N")
+}
+
+/// Word timings that cover the whole segment text, the only ones usable for highlighting.
+fn segment_words(seg: &TranscriptSegment) -> Option<&[Word]> {
+    seg.words
+        .as_deref()
+        .filter(|words| !words.is_empty() && words.len() == seg.text.split_whitespace().count())
+}
+
+fn centiseconds(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 100.0).round() as u64
+}
+
+/// `{\kN}` tags before each word, where N is the centiseconds until the next word starts.
+/// Segments without word timings fall back to highlighting the whole line at once.
+fn ass_karaoke_text(seg: &TranscriptSegment) -> String {
+    let Some(words) = segment_words(seg) else {
+        return format!(
+            "{{\\k{}}}{}",
+            centiseconds(seg.duration_seconds),
+            escape_ass_text(&seg.text)
+        );
+    };
+
+    let mut text = String::new();
+    let lead = centiseconds(words[0].start).saturating_sub(centiseconds(seg.start_seconds));
+    if lead > 0 {
+        text.push_str(&format!("{{\\k{}}}", lead));
+    }
+    for (i, word) in words.iter().enumerate() {
+        let next = words.get(i + 1).map_or(word.end, |w| w.start);
+        let duration = centiseconds(next).saturating_sub(centiseconds(word.start));
+        if i > 0 {
+            text.push(' ');
+        }
+        text.push_str(&format!("{{\\k{}}}{}", duration, escape_ass_text(&word.text)));
+    }
+    text
+}
+
+/// Renders an Advanced SubStation Alpha script. Speakers go in the `Name` field and VTT cue
+/// settings become alignment and position overrides. With `karaoke`, words are highlighted as
+/// they are spoken.
+pub fn format_ass(result: &TranscriptResult, karaoke: bool) -> String {
+    let title = result
+        .video
+        .as_ref()
+        .and_then(|v| v.title.clone())
+        .unwrap_or_else(|| result.video_id.clone());
+    let (width, height) = ASS_PLAY_RES;
+
+    let mut out = String::from("[Script Info]\n");
+    out.push_str(&format!("Title: {}\n", title.replace('\n', " ")));
+    out.push_str("ScriptType: v4.00+\n");
+    out.push_str(&format!("PlayResX: {}\nPlayResY: {}\n", width, height));
+    out.push_str("WrapStyle: 0\nScaledBorderAndShadow: yes\n\n");
+    out.push_str("[V4+ Styles]\n");
+    out.push_str("Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n");
+    out.push_str(&format!(
+        "Style: Default,Arial,16,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,1,0,2,10,10,{},1\n\n",
+        ASS_MARGIN_V
+    ));
+    out.push_str("[Events]\n");
+    out.push_str("Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n");
+
+    for seg in &result.segments {
+        out.push_str(&format!(
+            "Dialogue: 0,{},{},Default,{},0,0,0,,{}{}\n",
+            format_timestamp_ass(seg.start_seconds),
+            format_timestamp_ass(seg.end_seconds),
+            seg.speaker.as_deref().unwrap_or_default().replace(',', " "),
+            seg.settings.as_ref().map(ass_override).unwrap_or_default(),
+            if karaoke {
+                ass_karaoke_text(seg)
+            } else {
+                escape_ass_text(&seg.text)
+            }
+        ));
+    }
+    out
+}
+
+/// Renders enhanced LRC: one `[mm:ss.xx]` line per segment, with a `<mm:ss.xx>` tag before every
+/// word and after the last one when word timings are available.
+pub fn format_lrc(result: &TranscriptResult) -> String {
+    let mut out = String::new();
+    if let Some(info) = &result.video {
+        if let Some(title) = &info.title {
+            out.push_str(&format!("[ti:{}]\n", title));
+        }
+        if let Some(channel) = &info.channel {
+            out.push_str(&format!("[ar:{}]\n", channel));
+        }
+    }
+    out.push_str(&format!("[la:{}]\n", result.language));
+
+    for seg in &result.segments {
+        out.push_str(&format!("[{}]", format_timestamp_lrc(seg.start_seconds)));
+        match segment_words(seg) {
+            Some(words) => {
+                for word in words {
+                    out.push_str(&format!("<{}>{} ", format_timestamp_lrc(word.start), word.text));
+                }
+                let end = words.last().map_or(seg.end_seconds, |w| w.end);
+                out.push_str(&format!("<{}>", format_timestamp_lrc(end)));
+            }
+            None => out.push_str(&seg.text),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+pub fn format_json(result: &TranscriptResult) -> String {
+    serde_json::to_string_pretty(result).unwrap_or_default()
+}
+
+/// The JSON structure as MessagePack, with field names kept as map keys.
+pub fn format_msgpack(result: &TranscriptResult) -> Vec<u8> {
+    rmp_serde::to_vec_named(result).unwrap_or_default()
+}
+
+/// One segment per line, each naming its video, so lines from many videos can share a file.
+#[derive(serde::Serialize)]
+struct JsonlLine<'a> {
+    video_id: &'a str,
+    language: &'a str,
+    index: usize,
+    start_seconds: f64,
+    end_seconds: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speaker: Option<&'a str>,
+    text: &'a str,
+}
+
+pub fn format_jsonl(result: &TranscriptResult) -> String {
+    result
+        .segments
+        .iter()
+        .map(|seg| jsonl_line(&result.video_id, &result.language, seg))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One segment of JSONL output.
+pub fn jsonl_line(video_id: &str, language: &str, seg: &TranscriptSegment) -> String {
+    let line = JsonlLine {
+        video_id,
+        language,
+        index: seg.index,
+        start_seconds: seg.start_seconds,
+        end_seconds: seg.end_seconds,
+        speaker: seg.speaker.as_deref(),
+        text: &seg.text,
+    };
+    serde_json::to_string(&line).unwrap_or_default()
+}
+
+/// Column names of CSV output.
+pub const CSV_HEADER: &str = "video_id,index,start_seconds,end_seconds,speaker,text";
+
+pub fn format_csv(result: &TranscriptResult, header: bool) -> String {
+    let mut lines = Vec::with_capacity(result.segments.len() + 1);
+    if header {
+        lines.push(CSV_HEADER.to_string());
+    }
+    for seg in &result.segments {
+        lines.push(format!(
+            "{},{},{:.3},{:.3},{},{}",
+            csv_field(&result.video_id),
+            seg.index,
+            seg.start_seconds,
+            seg.end_seconds,
+            csv_field(seg.speaker.as_deref().unwrap_or("")),
+            csv_field(&seg.text)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Line that starts each video's transcript when several are appended to one TXT file.
+pub fn txt_delimiter(result: &TranscriptResult) -> String {
+    match result.video.as_ref().and_then(|v| v.title.as_deref()) {
+        Some(title) => format!("=== {} - {} ===", result.video_id, title),
+        None => format!("=== {} ===", result.video_id),
+    }
+}
+
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn escape_wiki_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '[' | ']' | '#' | '|' | '^' | '*' | '_' | '`' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub fn format_md_notes(result: &TranscriptResult, paragraphs: bool, links: bool) -> String {
+    let info = result.video.as_ref();
+    let title = info.and_then(|v| v.title.clone()).unwrap_or_else(|| result.video_id.clone());
+    let date = info
+        .and_then(|v| v.upload_date.as_deref())
+        .filter(|d| d.len() == 8)
+        .map(|d| format!("{}-{}-{}", &d[0..4], &d[4..6], &d[6..8]))
+        .unwrap_or_else(|| result.metadata.extracted_at[..10].to_string());
+
+    let mut out = String::from("---\n");
+    out.push_str(&format!("video_id: {}\n", yaml_string(&result.video_id)));
+    out.push_str(&format!("title: {}\n", yaml_string(&title)));
+    if let Some(channel) = info.and_then(|v| v.channel.as_deref()) {
+        out.push_str(&format!("channel: {}\n", yaml_string(channel)));
+    }
+    out.push_str(&format!(
+        "url: {}\n",
+        yaml_string(&format!("https://www.youtube.com/watch?v={}", result.video_id))
+    ));
+    out.push_str(&format!("date: {}\n", date));
+    out.push_str("tags:\n  - youtube\n");
+    for tag in info.and_then(|v| v.tags.as_ref()).into_iter().flatten() {
+        let tag: String = tag
+            .trim()
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' || c == '/' { c } else { '-' })
+            .collect();
+        if !tag.is_empty() {
+            out.push_str(&format!("  - {}\n", yaml_string(&tag)));
+        }
+    }
+    out.push_str("---\n\n");
+    out.push_str(&format!("# {}\n\n", escape_wiki_text(&title)));
+
+    if let Some(toc) = result.toc.as_ref().filter(|t| !t.is_empty()) {
+        out.push_str("## Contents\n\n");
+        for entry in toc {
+            out.push_str(&format!(
+                "- [{}]({}) {}\n",
+                format_timestamp_bracket(entry.seconds).trim_matches(['[', ']']),
+                entry.url,
+                escape_wiki_text(&entry.text)
+            ));
+        }
+        out.push_str("\n## Transcript\n\n");
+    }
+
+    let time = |seconds: f64| {
+        if links {
+            linked_timestamp(&result.video_id, seconds)
+        } else {
+            format!("**{}**", format_timestamp_bracket(seconds).trim_matches(['[', ']']))
+        }
+    };
+
+    for seg in &result.segments {
+        out.push_str(&format!("{} {}\n", time(seg.start_seconds), escape_wiki_text(&seg.text)));
+        if paragraphs {
+            out.push('\n');
+        }
+    }
+
+    if let Some(chapters) = info.and_then(|v| v.chapters.as_ref()).filter(|c| !c.is_empty()) {
+        out.push_str("\n## Chapters\n\n");
+        for chapter in chapters {
+            out.push_str(&format!("- {} {}\n", time(chapter.start_time), escape_wiki_text(&chapter.title)));
+        }
+    }
+
+    if let Some(summary) = &result.summary {
+        out.push_str(&format!("\n## Summary\n\n{}\n", escape_wiki_text(&summary.text)));
+        if !summary.key_points.is_empty() {
+            out.push_str("\n### Key points\n\n");
+        }
+        for point in &summary.key_points {
+            out.push_str(&format!("- {} {}\n", time(point.seconds), escape_wiki_text(&point.text)));
+        }
+    }
+
+    if let Some(keywords) = result.keywords.as_ref().filter(|k| !k.is_empty()) {
+        out.push_str("\n## Keywords\n\n");
+        for keyword in keywords {
+            let first = if links {
+                linked_timestamp(&result.video_id, keyword.first_seconds)
+            } else {
+                format_timestamp_bracket(keyword.first_seconds).trim_matches(['[', ']']).to_string()
+            };
+            out.push_str(&format!("- {} ({})\n", escape_wiki_text(&keyword.phrase), first));
+        }
+    }
+
+    if let Some(marks) = result.marks.as_ref().filter(|m| !m.is_empty()) {
+        out.push_str("\n## Marks\n\n");
+        for mark in marks {
+            out.push_str(&format!(
+                "- [{}]({}) **{}** {}\n",
+                format_timestamp_bracket(mark.seconds).trim_matches(['[', ']']),
+                mark.url,
+                escape_wiki_text(&mark.keyword),
+                escape_wiki_text(&mark.text.replace('\n', " "))
+            ));
+        }
+    }
+
+    out
+}
@@ -0,0 +1,31 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
+/// Sets up the agent used for every HTTP request (feeds, webhooks, S3, downloads) to go through
+/// `proxy`, as [`build_agent`] does. Only the first call has an effect.
+pub fn init(proxy: Option<&str>, timeout: Option<Duration>) -> Result<(), String> {
+    let _ = AGENT.set(build_agent(proxy, timeout)?);
+    Ok(())
+}
+
+/// An agent going through `proxy`, such as `http://proxy:3128` or `socks5://127.0.0.1:9050`.
+/// Without a proxy, `HTTPS_PROXY`, `HTTP_PROXY` and `ALL_PROXY` are honored. A request fails once
+/// it has taken longer than `timeout` altogether.
+pub fn build_agent(proxy: Option<&str>, timeout: Option<Duration>) -> Result<ureq::Agent, String> {
+    let mut builder = ureq::AgentBuilder::new().try_proxy_from_env(true);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = proxy {
+        let proxy = ureq::Proxy::new(proxy).map_err(|e| e.to_string())?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build())
+}
+
+/// The shared agent, configured from the environment if [`init`] was not called.
+pub fn agent() -> &'static ureq::Agent {
+    AGENT.get_or_init(|| ureq::AgentBuilder::new().try_proxy_from_env(true).build())
+}
@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::Path;
+
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexWriter, TantivyDocument, TantivyError, Term};
+
+use crate::transcript::TranscriptResult;
+
+const WRITER_MEMORY_BYTES: usize = 50_000_000;
+
+struct Fields {
+    /// `video_id:language`, so each language of a video is replaced on its own.
+    key: Field,
+    video_id: Field,
+    language: Field,
+    title: Field,
+    start_seconds: Field,
+    text: Field,
+}
+
+/// Full-text index over segments of previously extracted transcripts.
+pub struct TranscriptIndex {
+    index: Index,
+    fields: Fields,
+}
+
+pub struct IndexHit {
+    pub video_id: String,
+    pub language: String,
+    pub title: Option<String>,
+    pub start_seconds: f64,
+    pub text: String,
+    pub score: f32,
+}
+
+fn schema() -> Schema {
+    let mut builder = Schema::builder();
+    builder.add_text_field("key", STRING);
+    builder.add_text_field("video_id", STRING | STORED);
+    builder.add_text_field("language", STRING | STORED);
+    builder.add_text_field("title", TEXT | STORED);
+    builder.add_f64_field("start_seconds", STORED);
+    builder.add_text_field("text", TEXT | STORED);
+    builder.build()
+}
+
+impl TranscriptIndex {
+    /// Opens the index in `dir`, creating it if needed.
+    pub fn open(dir: &Path) -> tantivy::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let directory = MmapDirectory::open(dir)?;
+        let index = Index::open_or_create(directory, schema()).map_err(|e| match e {
+            TantivyError::SchemaError(_) => TantivyError::SchemaError(format!(
+                "{} was built by an older version; delete it and index again",
+                dir.display()
+            )),
+            e => e,
+        })?;
+
+        let schema = index.schema();
+        let fields = Fields {
+            key: schema.get_field("key")?,
+            video_id: schema.get_field("video_id")?,
+            language: schema.get_field("language")?,
+            title: schema.get_field("title")?,
+            start_seconds: schema.get_field("start_seconds")?,
+            text: schema.get_field("text")?,
+        };
+
+        Ok(TranscriptIndex { index, fields })
+    }
+
+    /// Adds transcripts to the index. A video already indexed in the same language is replaced.
+    pub fn add(&self, results: &[TranscriptResult]) -> tantivy::Result<()> {
+        let f = &self.fields;
+        let mut writer: IndexWriter = self.index.writer(WRITER_MEMORY_BYTES)?;
+
+        for result in results {
+            let key = format!("{}:{}", result.video_id, result.language);
+            writer.delete_term(Term::from_field_text(f.key, &key));
+            let title = result.video.as_ref().and_then(|v| v.title.clone()).unwrap_or_default();
+
+            for seg in &result.segments {
+                writer.add_document(doc!(
+                    f.key => key.as_str(),
+                    f.video_id => result.video_id.as_str(),
+                    f.language => result.language.as_str(),
+                    f.title => title.as_str(),
+                    f.start_seconds => seg.start_seconds,
+                    f.text => seg.text.as_str(),
+                ))?;
+            }
+        }
+
+        writer.commit()?;
+        Ok(())
+    }
+
+    pub fn search(&self, query: &str, limit: usize) -> tantivy::Result<Vec<IndexHit>> {
+        let f = &self.fields;
+        let searcher = self.index.reader()?.searcher();
+        let parser = QueryParser::for_index(&self.index, vec![f.text, f.title]);
+        let query = parser
+            .parse_query(query)
+            .map_err(|e| TantivyError::InvalidArgument(e.to_string()))?;
+
+        let mut hits = Vec::new();
+        for (score, address) in searcher.search(&query, &TopDocs::with_limit(limit))? {
+            let doc: TantivyDocument = searcher.doc(address)?;
+            let text_of = |field: Field| {
+                doc.get_first(field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+
+            let title = text_of(f.title);
+            hits.push(IndexHit {
+                video_id: text_of(f.video_id),
+                language: text_of(f.language),
+                title: (!title.is_empty()).then_some(title),
+                start_seconds: doc.get_first(f.start_seconds).and_then(|v| v.as_f64()).unwrap_or(0.0),
+                text: text_of(f.text),
+                score,
+            });
+        }
+
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{Metadata, TranscriptSegment, SCHEMA_VERSION};
+
+    fn transcript(language: &str, text: &str) -> TranscriptResult {
+        TranscriptResult {
+            schema_version: SCHEMA_VERSION,
+            video_id: "dQw4w9WgXcQ".to_string(),
+            language: language.to_string(),
+            segments: vec![TranscriptSegment::new(0.0, 2.0, text)],
+            metadata: Metadata {
+                total_segments: 1,
+                extracted_at: String::new(),
+                auto_generated: false,
+                total_tokens: None,
+            },
+            video: None,
+            keywords: None,
+            marks: None,
+            toc: None,
+            summary: None,
+            chunks: None,
+        }
+    }
+
+    fn languages(index: &TranscriptIndex, query: &str) -> Vec<String> {
+        let mut languages: Vec<_> = index
+            .search(query, 10)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.language)
+            .collect();
+        languages.sort();
+        languages
+    }
+
+    #[test]
+    fn keeps_each_language_of_a_video() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = TranscriptIndex::open(dir.path()).unwrap();
+        index
+            .add(&[transcript("en", "never gonna give"), transcript("de", "niemals give")])
+            .unwrap();
+        index.add(&[transcript("fr", "jamais give")]).unwrap();
+        assert_eq!(languages(&index, "give"), ["de", "en", "fr"]);
+    }
+
+    #[test]
+    fn replaces_a_reindexed_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = TranscriptIndex::open(dir.path()).unwrap();
+        index.add(&[transcript("en", "old words")]).unwrap();
+        index.add(&[transcript("en", "new words")]).unwrap();
+        assert!(languages(&index, "old").is_empty());
+        assert_eq!(languages(&index, "words"), ["en"]);
+    }
+}
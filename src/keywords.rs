@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::stats::normalize_word;
+use crate::transcript::TranscriptSegment;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Keyword {
+    pub phrase: String,
+    pub score: f64,
+    pub first_seconds: f64,
+}
+
+const MAX_PHRASE_WORDS: usize = 3;
+
+const STOPWORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any",
+    "are", "aren't", "as", "at", "be", "because", "been", "before", "being", "below", "between",
+    "both", "but", "by", "can", "could", "did", "didn't", "do", "does", "doesn't", "doing", "don't",
+    "down", "during", "each", "even", "few", "for", "from", "further", "get", "gets", "go", "going",
+    "gonna", "got", "had", "has", "have", "having", "he", "her", "here", "hers", "herself", "him",
+    "himself", "his", "how", "i", "i'm", "i've", "if", "in", "into", "is", "isn't", "it", "it's",
+    "its", "itself", "just", "know", "let", "let's", "like", "me", "more", "most", "my", "myself",
+    "no", "nor", "not", "now", "of", "off", "oh", "ok", "okay", "on", "once", "one", "only", "or",
+    "other", "our", "ours", "ourselves", "out", "over", "own", "really", "right", "said", "same",
+    "say", "see", "she", "should", "so", "some", "such", "than", "that", "that's", "the", "their",
+    "theirs", "them", "themselves", "then", "there", "there's", "these", "they", "they're", "thing",
+    "things", "think", "this", "those", "through", "to", "too", "um", "uh", "under", "until", "up",
+    "very", "want", "was", "we", "we're", "well", "were", "what", "what's", "when", "where", "which",
+    "while", "who", "whom", "why", "will", "with", "would", "yeah", "you", "you're", "your", "yours",
+    "yourself", "yourselves",
+];
+
+/// Extracts the `limit` highest-scoring key phrases with RAKE: candidate phrases are runs of
+/// non-stopwords within a segment, each word scores its co-occurrence degree divided by its
+/// frequency, and phrases that recur get a logarithmic boost.
+pub fn extract_keywords(segments: &[TranscriptSegment], limit: usize) -> Vec<Keyword> {
+    let mut phrases: Vec<(Vec<String>, f64)> = Vec::new();
+
+    for seg in segments {
+        let mut current: Vec<String> = Vec::new();
+        for raw in seg.text.split_whitespace() {
+            let word = normalize_word(raw);
+            let ends_clause = raw.ends_with(['.', ',', '!', '?', ';', ':']);
+            let is_stop = word.is_empty()
+                || STOPWORDS.contains(&word.as_str())
+                || word.chars().all(|c| c.is_numeric());
+
+            if is_stop {
+                flush(&mut current, &mut phrases, seg.start_seconds);
+            } else {
+                current.push(word);
+                if current.len() == MAX_PHRASE_WORDS || ends_clause {
+                    flush(&mut current, &mut phrases, seg.start_seconds);
+                }
+            }
+        }
+        flush(&mut current, &mut phrases, seg.start_seconds);
+    }
+
+    let mut frequency: HashMap<&str, f64> = HashMap::new();
+    let mut degree: HashMap<&str, f64> = HashMap::new();
+    for (words, _) in &phrases {
+        for word in words {
+            *frequency.entry(word).or_default() += 1.0;
+            *degree.entry(word).or_default() += words.len() as f64;
+        }
+    }
+
+    let mut occurrences: HashMap<String, (usize, f64)> = HashMap::new();
+    for (words, start) in &phrases {
+        let entry = occurrences.entry(words.join(" ")).or_insert((0, *start));
+        entry.0 += 1;
+    }
+
+    let mut keywords: Vec<Keyword> = occurrences
+        .into_iter()
+        .map(|(phrase, (count, first_seconds))| {
+            let rake: f64 = phrase.split(' ').map(|w| degree[w] / frequency[w]).sum();
+            Keyword {
+                score: rake * (1.0 + (count as f64).ln()),
+                phrase,
+                first_seconds,
+            }
+        })
+        .collect();
+
+    keywords.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.first_seconds.total_cmp(&b.first_seconds))
+    });
+    keywords.truncate(limit);
+    keywords
+}
+
+fn flush(current: &mut Vec<String>, phrases: &mut Vec<(Vec<String>, f64)>, start: f64) {
+    if !current.is_empty() {
+        phrases.push((std::mem::take(current), start));
+    }
+}
@@ -0,0 +1,65 @@
+//! Extract YouTube video transcripts with timestamps.
+
+pub mod archive;
+pub mod batch;
+pub mod cache;
+pub mod censor;
+pub mod chapters;
+pub mod chunk;
+pub mod cleanup;
+#[cfg(any(feature = "parquet", feature = "arrow"))]
+pub mod columnar;
+pub mod compress;
+pub mod config;
+pub mod cookies;
+pub mod diff;
+#[cfg(feature = "local-embeddings")]
+pub mod embed;
+pub mod error;
+pub mod exit;
+pub mod feed;
+pub mod format;
+pub mod http;
+#[cfg(feature = "index")]
+pub mod index;
+pub mod keywords;
+pub mod llm;
+pub mod marks;
+pub mod mcp;
+pub mod mt;
+pub mod output_path;
+pub mod pacing;
+pub mod proto;
+pub mod release;
+pub mod resegment;
+pub mod s3;
+pub mod search;
+pub mod speakers;
+pub mod split;
+pub mod sqlite;
+pub mod srt;
+pub mod stats;
+pub mod summary;
+pub mod template;
+pub mod timedtext;
+pub mod timing;
+pub mod toc;
+pub mod tokens;
+pub mod transcriber;
+pub mod transcript;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod video_id;
+pub mod vtt;
+pub mod webhook;
+pub mod yt_dlp;
+
+pub use error::TranscriberError;
+pub use format::{FormatRegistry, Formatter};
+pub use srt::parse_srt;
+pub use transcriber::{Backend, Transcriber};
+pub use transcript::{
+    Chapter, CueSettings, Metadata, TranscriptResult, TranscriptSegment, VideoInfo, Word, SCHEMA_VERSION,
+};
+pub use video_id::extract_video_id;
+pub use vtt::parse_vtt;
@@ -0,0 +1,234 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+
+use serde_json::Value;
+
+use crate::config::LlmConfig;
+use crate::format::format_timestamp_bracket;
+use crate::http;
+use crate::transcript::TranscriptSegment;
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Texts sent per embeddings request.
+const EMBEDDING_BATCH: usize = 64;
+
+/// Requests in flight, shared by every client so parallel jobs stay within `max_concurrency`.
+static IN_FLIGHT: Mutex<usize> = Mutex::new(0);
+static FINISHED: Condvar = Condvar::new();
+
+/// Client for the chat completions and embeddings APIs of OpenAI or any compatible server
+/// (Ollama, vLLM, LM Studio, OpenRouter...).
+#[derive(Clone)]
+pub struct LlmClient {
+    /// API root, e.g. `https://api.openai.com/v1` or `http://localhost:11434/v1`.
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub embedding_model: String,
+    pub temperature: Option<f64>,
+    /// Requests in flight at once, across all threads.
+    pub max_concurrency: usize,
+}
+
+impl LlmClient {
+    /// Configures the client from `OPENAI_BASE_URL`, `OPENAI_MODEL` and
+    /// `OPENAI_EMBEDDING_MODEL`, which win over the `[llm]` table, and the API key from the
+    /// variable `api_key_env` names (`OPENAI_API_KEY` by default).
+    pub fn from_config(config: &LlmConfig) -> LlmClient {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let setting = |name: &str, value: &Option<String>, default: &str| {
+            var(name)
+                .or_else(|| value.clone())
+                .unwrap_or_else(|| default.to_string())
+        };
+        LlmClient {
+            base_url: setting("OPENAI_BASE_URL", &config.base_url, DEFAULT_BASE_URL),
+            api_key: var(config.api_key_env.as_deref().unwrap_or("OPENAI_API_KEY")),
+            model: setting("OPENAI_MODEL", &config.model, DEFAULT_MODEL),
+            embedding_model: setting(
+                "OPENAI_EMBEDDING_MODEL",
+                &config.embedding_model,
+                DEFAULT_EMBEDDING_MODEL,
+            ),
+            temperature: config.temperature,
+            max_concurrency: config
+                .max_concurrency
+                .unwrap_or(DEFAULT_MAX_CONCURRENCY)
+                .max(1),
+        }
+    }
+
+    /// Calls `f` on every item, up to `max_concurrency` at a time, keeping the order. Stops at
+    /// the first error.
+    pub fn map<T: Sync, R: Send>(
+        &self,
+        items: &[T],
+        f: impl Fn(&T) -> Result<R, String> + Sync,
+    ) -> Result<Vec<R>, String> {
+        let next = AtomicUsize::new(0);
+        let failed = Mutex::new(None);
+        let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+        std::thread::scope(|scope| {
+            for _ in 0..self.max_concurrency.min(items.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(i) else { break };
+                    if failed.lock().map_or(true, |failed| failed.is_some()) {
+                        break;
+                    }
+                    match f(item) {
+                        Ok(result) => {
+                            if let Ok(mut results) = results.lock() {
+                                results[i] = Some(result);
+                            }
+                        }
+                        Err(e) => {
+                            if let Ok(mut failed) = failed.lock() {
+                                failed.get_or_insert(e);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        if let Some(e) = failed.into_inner().map_err(|e| e.to_string())? {
+            return Err(e);
+        }
+        results
+            .into_inner()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|result| result.ok_or_else(|| "a request did not finish".to_string()))
+            .collect()
+    }
+
+    /// Sends one system and one user message and returns the reply's text.
+    pub fn chat(&self, system: &str, user: &str) -> Result<String, String> {
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": user },
+            ],
+        });
+        if let Some(temperature) = self.temperature {
+            body["temperature"] = temperature.into();
+        }
+        let response = self.post("chat/completions", body)?;
+        response["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "no message in the response".to_string())
+    }
+
+    /// Embeds each text with `embedding_model`, in batches.
+    pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let batches: Vec<&[String]> = texts.chunks(EMBEDDING_BATCH).collect();
+        let vectors = self.map(&batches, |batch| self.embed_batch(batch))?;
+        Ok(vectors.into_iter().flatten().collect())
+    }
+
+    fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let body = serde_json::json!({ "model": self.embedding_model, "input": batch });
+        let response = self.post("embeddings", body)?;
+        let mut data: Vec<(u64, Vec<f32>)> = response["data"]
+            .as_array()
+            .ok_or_else(|| "no data in the response".to_string())?
+            .iter()
+            .map(|item| {
+                let index = item["index"].as_u64().unwrap_or_default();
+                serde_json::from_value(item["embedding"].clone())
+                    .map(|vector| (index, vector))
+                    .map_err(|e| format!("invalid embedding in the response - {}", e))
+            })
+            .collect::<Result<_, _>>()?;
+        if data.len() != batch.len() {
+            return Err(format!(
+                "{} embeddings in the response for {} texts",
+                data.len(),
+                batch.len()
+            ));
+        }
+        data.sort_by_key(|(index, _)| *index);
+        Ok(data.into_iter().map(|(_, vector)| vector).collect())
+    }
+
+    /// POSTs a JSON body to an API path and returns the JSON response, waiting first while
+    /// `max_concurrency` requests are in flight.
+    fn post(&self, path: &str, body: Value) -> Result<Value, String> {
+        let _slot = Slot::acquire(self.max_concurrency);
+        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), path);
+        let mut request = http::agent()
+            .post(&url)
+            .set("Content-Type", "application/json");
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        match request.send_json(body) {
+            Ok(response) => response
+                .into_json()
+                .map_err(|e| format!("invalid response from {} - {}", url, e)),
+            Err(ureq::Error::Status(code, response)) => {
+                let detail = response.into_string().unwrap_or_default();
+                Err(format!("HTTP {} from {} {}", code, url, detail.trim()))
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// One of the requests in flight, given back when dropped.
+struct Slot;
+
+impl Slot {
+    fn acquire(limit: usize) -> Slot {
+        let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+        while *in_flight >= limit {
+            in_flight = FINISHED.wait(in_flight).unwrap_or_else(|e| e.into_inner());
+        }
+        *in_flight += 1;
+        Slot
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        *IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+        FINISHED.notify_one();
+    }
+}
+
+/// Writes segments as `[mm:ss] text` lines, grouped into chunks of at most `max_chars` so each
+/// fits in one request. A segment is never split across chunks.
+pub fn transcript_chunks(segments: &[TranscriptSegment], max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for seg in segments {
+        let line = format!(
+            "{} {}\n",
+            format_timestamp_bracket(seg.start_seconds),
+            seg.text
+        );
+        if !current.is_empty() && current.len() + line.len() > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Parses the JSON object in a reply, ignoring any prose or code fence around it.
+pub fn reply_json(reply: &str) -> Result<Value, String> {
+    let object = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Err("the reply contains no JSON object".to_string()),
+    };
+    serde_json::from_str(object).map_err(|e| format!("the reply is not valid JSON - {}", e))
+}
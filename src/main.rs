@@ -1,404 +1,4322 @@
-use clap::{Parser, ValueEnum};
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use indicatif::{ProgressBar, ProgressStyle};
 use regex::Regex;
-use serde::Serialize;
+use std::ffi::OsStr;
+use std::cell::{Cell, RefCell};
 use std::fs;
-use std::process::{Command, ExitCode};
-use tempfile::TempDir;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicI8, Ordering};
+use std::sync::mpsc::{self, TrySendError};
+use std::sync::{Arc, Mutex, Once, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use yt_transcriber::archive::Archive;
+use yt_transcriber::batch::{BatchState, BatchSummary, FailedVideo};
+use yt_transcriber::cache::{Cache, CachedSubtitles};
+use yt_transcriber::censor::{Censor, DEFAULT_WORDS};
+use yt_transcriber::chapters::propose_chapters;
+use yt_transcriber::chunk::{chunk, format_chunks, Chunk, ChunkSpec};
+use yt_transcriber::cleanup::{
+    redact, strip_annotations, Redaction, RollingDedup, DEFAULT_ANNOTATION_PATTERNS,
+};
+use yt_transcriber::compress::Compression;
+use yt_transcriber::config::{Config, LlmConfig};
+use yt_transcriber::cookies;
+use yt_transcriber::diff::{audit_captions, diff_transcripts, ChangeKind};
+use yt_transcriber::error::TranscriberError;
+use yt_transcriber::exit::Exit;
+use yt_transcriber::feed::feed_video_ids;
+use yt_transcriber::format::{
+    content_type, format_csv, format_json, format_jsonl, format_msgpack, format_timestamp_bracket, format_timestamp_srt,
+    format_txt, jsonl_line, linked_timestamp, txt_delimiter, txt_line, MdNotesFormatter, TxtFormatter,
+};
+use yt_transcriber::http;
+#[cfg(feature = "index")]
+use yt_transcriber::{format::youtube_link, index::TranscriptIndex};
+use yt_transcriber::keywords::extract_keywords;
+use yt_transcriber::marks::find_marks;
+use yt_transcriber::llm::LlmClient;
+use yt_transcriber::mcp::{self, TOOL_FORMATS};
+use yt_transcriber::mt::{translate_segments, Translator};
+use yt_transcriber::output_path;
+use yt_transcriber::pacing::{backoff, SleepRange};
+use yt_transcriber::release::{self, is_newer, verify_checksum, Release};
+use yt_transcriber::resegment::{
+    merge_paragraphs, split_long_segments, split_sentences, wrap_cues,
+};
+use yt_transcriber::s3::{is_s3_url, join_key, S3Client, S3Location};
+use yt_transcriber::search::{find_matches, Query};
+use yt_transcriber::stats::{compute_readability, compute_stats, cps_warnings};
+use yt_transcriber::summary::summarize;
+use yt_transcriber::speakers::{label_speakers, SpeakerMode};
+use yt_transcriber::split::{split, Part, SplitBy};
+use yt_transcriber::sqlite::write_sqlite;
+use yt_transcriber::srt;
+use yt_transcriber::template::render_template;
+use yt_transcriber::timedtext::SubtitleFormat;
+use yt_transcriber::timing::{clip_range, parse_duration, parse_offset, parse_time, retime};
+use yt_transcriber::toc::{parse_interval, table_of_contents};
+use yt_transcriber::transcriber::{LogLevel, Track, Transcriber, TranscriberBuilder};
+use yt_transcriber::transcript::json_schema;
+use yt_transcriber::tokens::count_tokens;
+use yt_transcriber::video_id::{
+    collection_listing_url, extract_time_window, is_clip_url, is_collection_url, is_feed_url,
+    is_web_url, url_key,
+};
+use yt_transcriber::vtt::{decode_entities, VttReader};
+use yt_transcriber::webhook::{post_json, WebhookSummary};
+use yt_transcriber::yt_dlp::{self, split_args};
+use yt_transcriber::{
+    extract_video_id, FormatRegistry, TranscriptResult, TranscriptSegment, VideoInfo,
+};
+
+/// -1 with --quiet, otherwise the number of -v flags.
+static VERBOSITY: AtomicI8 = AtomicI8::new(0);
+
+/// Whether diagnostics are written as JSON lines (--log-format json).
+static JSON_LOGS: AtomicBool = AtomicBool::new(false);
+
+/// Whether failures are printed as JSON objects (--error-format json).
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// yt-dlp executable, or command line split into words, given with --yt-dlp-path.
+static YT_DLP_PATH: OnceLock<Vec<String>> = OnceLock::new();
+
+/// yt-dlp release to download and run instead of the latest (--yt-dlp-version).
+static PINNED_YT_DLP: OnceLock<String> = OnceLock::new();
+
+/// Executables tried in order when yt-dlp is not installed by this tool (--yt-dlp-candidates).
+const DEFAULT_YT_DLP_CANDIDATES: &[&str] = &["yt-dlp", "yt-dlp_x86", "yt-dlp-nightly", "youtube-dl"];
+
+static YT_DLP_CANDIDATES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// The first candidate found on PATH, looked up once per run.
+static FOUND_CANDIDATE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Cookies from --cookies, sent with native requests to matching sites.
+static COOKIES: OnceLock<Vec<cookies::Cookie>> = OnceLock::new();
+
+/// Never install anything (--no-install).
+static NO_INSTALL: AtomicBool = AtomicBool::new(false);
+
+/// Install without asking (--yes).
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// How videos are fetched: the yt-dlp, network and cache options, set up once in `main`.
+static FETCH_SETTINGS: OnceLock<TranscriberBuilder> = OnceLock::new();
+
+/// Pass URLs of sites other than YouTube to yt-dlp (--any-site).
+static ANY_SITE: AtomicBool = AtomicBool::new(false);
+
+/// The endpoint of --summarize, --auto-chapters and --embed, from [llm] and the environment.
+static LLM: OnceLock<LlmClient> = OnceLock::new();
+
+/// Held while yt-dlp is being installed.
+static INSTALLING: Mutex<()> = Mutex::new(());
+
+/// Held while a transcript is appended to a file, so parallel jobs do not interleave.
+static APPENDING: Mutex<()> = Mutex::new(());
+
+/// Warns about an old yt-dlp once per run rather than once per video.
+static STALE_WARNING: Once = Once::new();
+
+/// Whether spinners and progress bars may be drawn; set for interactive transcription runs.
+static SHOW_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// The spinner or batch bar currently on stderr. Diagnostics are printed above it.
+static PROGRESS: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+thread_local! {
+    /// What this thread is working on (video_id, language), attached to JSON logs and errors.
+    static LOG_CONTEXT: RefCell<Vec<(&'static str, String)>> = const { RefCell::new(Vec::new()) };
+
+    /// The last failure reported on this thread as (error identifier, message), for batch summaries.
+    static LAST_ERROR: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+
+    /// Whether the last subtitles fetched on this thread came from the cache.
+    static CACHE_HIT: Cell<bool> = const { Cell::new(false) };
+
+    /// Whether the last output on this thread was skipped because its file exists (--skip-existing).
+    static OUTPUT_EXISTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Prints to stderr when running with at least `level` -v flags.
+macro_rules! debug {
+    ($level:expr, $($arg:tt)*) => {
+        if VERBOSITY.load(Ordering::Relaxed) >= $level {
+            log_message(if $level > 0 { "debug" } else { "info" }, format!($($arg)*));
+        }
+    };
+}
+
+/// Prints a progress notice to stderr unless --quiet is given.
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        debug!(0, $($arg)*)
+    };
+}
+
+/// Reports a failure. `kind` is the stable identifier printed by --error-format json, and `hint`
+/// tells the user what to do about it.
+macro_rules! error {
+    ($kind:ident, hint: $hint:expr; $($arg:tt)*) => {
+        log_error(stringify!($kind), format!($($arg)*), Some($hint))
+    };
+    ($kind:ident: $($arg:tt)*) => {
+        log_error(stringify!($kind), format!($($arg)*), None)
+    };
+}
+
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        log_message("warning", format!($($arg)*))
+    };
+}
+
+/// The current context fields followed by `fields`.
+fn context_fields(fields: serde_json::Map<String, serde_json::Value>) -> serde_json::Map<String, serde_json::Value> {
+    let mut event = serde_json::Map::new();
+    LOG_CONTEXT.with(|context| {
+        for (key, value) in context.borrow().iter() {
+            event.insert(key.to_string(), value.clone().into());
+        }
+    });
+    event.extend(fields);
+    event
+}
+
+/// Writes one JSON log event with a timestamp, level, the current context and `fields`.
+fn log_event(level: &str, message: &str, fields: serde_json::Map<String, serde_json::Value>) {
+    let mut event = context_fields(fields);
+    event.insert("ts".into(), chrono::Utc::now().to_rfc3339().into());
+    event.insert("level".into(), level.into());
+    event.insert("message".into(), message.into());
+    write_stderr(&serde_json::Value::Object(event).to_string());
+}
+
+/// Writes a diagnostic line to stderr without breaking the progress bar.
+fn write_stderr(line: &str) {
+    let bar = PROGRESS.lock().ok().and_then(|progress| progress.clone());
+    match bar {
+        Some(bar) => bar.suspend(|| eprintln!("{}", line)),
+        None => eprintln!("{}", line),
+    }
+}
+
+fn log_message(level: &str, message: String) {
+    if JSON_LOGS.load(Ordering::Relaxed) {
+        log_event(level, &message, serde_json::Map::new());
+    } else if level == "warning" {
+        write_stderr(&format!("Warning: {}", message));
+    } else {
+        write_stderr(&message);
+    }
+}
+
+fn log_error(kind: &str, message: String, hint: Option<&str>) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some((kind.to_string(), message.clone())));
+    let mut fields = serde_json::Map::new();
+    fields.insert("error".into(), kind.into());
+    if let Some(hint) = hint {
+        fields.insert("hint".into(), hint.into());
+    }
+    if JSON_LOGS.load(Ordering::Relaxed) {
+        log_event("error", &message, fields);
+    } else if JSON_ERRORS.load(Ordering::Relaxed) {
+        let mut object = context_fields(fields);
+        object.insert("message".into(), message.into());
+        write_stderr(&serde_json::Value::Object(object).to_string());
+    } else {
+        write_stderr(&format!("Error: {}", message));
+        if let Some(hint) = hint {
+            write_stderr(hint);
+        }
+    }
+}
+
+/// Records a finished pipeline stage: always as a JSON event with --log-format json, and as a
+/// text line with -v.
+fn log_stage(stage: &str, started: Instant, message: String, fields: serde_json::Value) {
+    if !JSON_LOGS.load(Ordering::Relaxed) {
+        debug!(1, "{} in {:.2}s", message, started.elapsed().as_secs_f64());
+        return;
+    }
+    if VERBOSITY.load(Ordering::Relaxed) < 0 {
+        return;
+    }
+    let mut event = match fields {
+        serde_json::Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    event.insert("stage".into(), stage.into());
+    event.insert("duration_ms".into(), (started.elapsed().as_millis() as u64).into());
+    log_event("info", &message, event);
+}
+
+/// Adds a field to the log context of this thread until dropped.
+struct LogContext;
+
+impl LogContext {
+    fn enter(key: &'static str, value: &str) -> Self {
+        LOG_CONTEXT.with(|context| context.borrow_mut().push((key, value.to_string())));
+        LogContext
+    }
+}
+
+impl Drop for LogContext {
+    fn drop(&mut self) {
+        LOG_CONTEXT.with(|context| context.borrow_mut().pop());
+    }
+}
+
+/// A spinner, or a bar with ETA for playlists and channels, on stderr until dropped.
+struct Progress {
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Spins next to `message` while yt-dlp runs. During a batch the message is shown on the
+    /// batch bar instead.
+    fn spinner(message: String) -> Self {
+        let Ok(mut current) = PROGRESS.lock() else {
+            return Progress { bar: None };
+        };
+        if let Some(bar) = current.as_ref() {
+            bar.set_message(message);
+            return Progress { bar: None };
+        }
+        if !SHOW_PROGRESS.load(Ordering::Relaxed) {
+            return Progress { bar: None };
+        }
+        let style = ProgressStyle::with_template("{spinner} {msg} ({elapsed})").expect("valid template");
+        let bar = ProgressBar::new_spinner().with_style(style).with_message(message);
+        bar.enable_steady_tick(Duration::from_millis(100));
+        *current = Some(bar.clone());
+        Progress { bar: Some(bar) }
+    }
+
+    /// A bar over `len` videos with the position, elapsed time and ETA.
+    fn batch(len: usize) -> Self {
+        if len == 0 || !SHOW_PROGRESS.load(Ordering::Relaxed) {
+            return Progress { bar: None };
+        }
+        let style = ProgressStyle::with_template("{spinner} [{bar:30}] {pos}/{len} videos, ETA {eta} {msg}")
+            .expect("valid template")
+            .progress_chars("=> ");
+        let bar = ProgressBar::new(len as u64).with_style(style);
+        bar.enable_steady_tick(Duration::from_millis(100));
+        if let Ok(mut current) = PROGRESS.lock() {
+            *current = Some(bar.clone());
+        }
+        Progress { bar: Some(bar) }
+    }
+
+    fn advance(&self) {
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+            if let Ok(mut current) = PROGRESS.lock() {
+                *current = None;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line with ts, level, stage, video_id and duration_ms
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ErrorFormat {
+    /// "Error: ..." lines
+    Text,
+    /// {"error":"no_subtitles","message":"...","video_id":"...","language":"en"}
+    Json,
+}
 
 #[derive(Parser)]
 #[command(name = "yt-transcriber")]
 #[command(version = "1.0.0")]
 #[command(about = "Extract YouTube video transcripts with timestamps")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    /// YouTube URL or video ID
-    url: String,
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// YouTube URLs or video IDs, or local .vtt or .srt files to convert. With more than one,
+    /// each video is written to its own file as for a playlist
+    #[arg(value_name = "URL", required_unless_present_any = ["list_exit_codes", "stdin_vtt"])]
+    urls: Vec<String>,
+
+    /// Read WebVTT or SRT subtitles from standard input instead of downloading them
+    #[arg(long, conflicts_with = "urls")]
+    stdin_vtt: bool,
+
+    /// Videos of a playlist, channel or several URLs transcribed at the same time
+    #[arg(short, long, default_value_t = 1, env = "YT_TRANSCRIBER_JOBS")]
+    jobs: usize,
+
+    /// Print every exit code with its stable name and meaning, then exit
+    #[arg(long)]
+    list_exit_codes: bool,
+
+    /// Skip videos listed in this yt-dlp style archive file and add each video transcribed
+    #[arg(long)]
+    archive: Option<PathBuf>,
+
+    /// Continue an interrupted playlist or channel run from its state file
+    #[arg(long)]
+    resume: bool,
+
+    /// State file for playlist and channel runs (default: .yt-transcriber-state.json in the
+    /// output directory, or the current directory for s3:// output)
+    #[arg(long)]
+    state_file: Option<PathBuf>,
+
+    /// Write the summary of a playlist or channel run to this file as JSON
+    #[arg(long)]
+    summary: Option<PathBuf>,
+
+    /// Print diagnostics on stderr: -v for cache use, chosen subtitles and timings, -vv also for
+    /// yt-dlp command lines
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only print errors and warnings
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Format of diagnostics on stderr
+    #[arg(long, value_enum, default_value = "text", global = true, env = "YT_TRANSCRIBER_LOG_FORMAT")]
+    log_format: LogFormat,
+
+    /// Print failures as JSON objects with a stable error identifier instead of prose
+    #[arg(long, value_enum, default_value = "text", global = true)]
+    error_format: ErrorFormat,
+
+    /// Do not draw spinners or progress bars on stderr
+    #[arg(long, global = true)]
+    no_progress: bool,
+
+    /// yt-dlp release to download and use (e.g. 2024.08.06) instead of the latest
+    #[arg(long, global = true, env = "YT_TRANSCRIBER_YT_DLP_VERSION")]
+    yt_dlp_version: Option<String>,
+
+    /// yt-dlp executable to run, or a command line such as "python3 -m yt_dlp" (never replaced by
+    /// a download)
+    #[arg(long, global = true, env = "YT_TRANSCRIBER_YT_DLP_PATH")]
+    yt_dlp_path: Option<String>,
+
+    /// Executables to look for on PATH, in order, when yt-dlp was not downloaded by this tool
+    /// (default: yt-dlp,yt-dlp_x86,yt-dlp-nightly,youtube-dl)
+    #[arg(long, global = true, value_delimiter = ',', env = "YT_TRANSCRIBER_YT_DLP_CANDIDATES")]
+    yt_dlp_candidates: Vec<String>,
+
+    /// Proxy for yt-dlp and every other request, e.g. socks5://127.0.0.1:9050 (default:
+    /// HTTPS_PROXY, HTTP_PROXY or ALL_PROXY)
+    #[arg(long, global = true, env = "YT_TRANSCRIBER_PROXY")]
+    proxy: Option<String>,
+
+    /// Netscape cookies.txt passed to yt-dlp and sent with other YouTube requests, for
+    /// members-only, age-gated and region-locked videos
+    #[arg(long, global = true, env = "YT_TRANSCRIBER_COOKIES")]
+    cookies: Option<PathBuf>,
+
+    /// Let yt-dlp read cookies from a browser: chrome, firefox, edge, safari, brave, ...
+    /// (BROWSER[+KEYRING][:PROFILE][::CONTAINER])
+    #[arg(long, global = true, value_parser = browser_arg, env = "YT_TRANSCRIBER_COOKIES_FROM_BROWSER")]
+    cookies_from_browser: Option<String>,
+
+    /// Proof-of-origin token(s) for YouTube's bot check, as CLIENT.CONTEXT+TOKEN (e.g.
+    /// web.gvs+XXX), comma-separated
+    #[arg(long, global = true, env = "YT_TRANSCRIBER_PO_TOKEN")]
+    po_token: Option<String>,
+
+    /// YouTube player clients for yt-dlp to use, in order (e.g. mweb,web)
+    #[arg(long, global = true, value_delimiter = ',')]
+    player_client: Vec<String>,
+
+    /// Pretend to be in this country (two-letter ISO code) for region-locked videos. Without it,
+    /// a region-restricted video is retried once with a country it is available in
+    #[arg(long, global = true, value_parser = country_arg, env = "YT_TRANSCRIBER_GEO_BYPASS_COUNTRY")]
+    geo_bypass_country: Option<String>,
+
+    /// Accept URLs of any site yt-dlp supports (Vimeo, Twitch VODs, PeerTube, ...), not only
+    /// YouTube
+    #[arg(long, global = true, env = "YT_TRANSCRIBER_ANY_SITE")]
+    any_site: bool,
+
+    /// Never download or install anything; fail if yt-dlp is missing
+    #[arg(long, global = true, env = "YT_TRANSCRIBER_NO_INSTALL")]
+    no_install: bool,
+
+    /// Install missing dependencies without asking
+    #[arg(short = 'y', long, global = true, conflicts_with = "no_install")]
+    yes: bool,
+
+    /// Retry yt-dlp this many times after network errors, rate limiting (HTTP 429) or server
+    /// errors (5xx), waiting longer each time
+    #[arg(long, global = true, default_value_t = 3, env = "YT_TRANSCRIBER_RETRIES")]
+    retries: u32,
+
+    /// Stop yt-dlp and fail HTTP requests that take longer than this (e.g. 60s, 5m; 0 for no
+    /// limit)
+    #[arg(long, global = true, value_parser = duration_arg, default_value = "5m", env = "YT_TRANSCRIBER_TIMEOUT")]
+    timeout: Duration,
+
+    /// Extra arguments appended to every yt-dlp call, e.g. "--sleep-requests 2 --force-ipv4"
+    #[arg(long, global = true, allow_hyphen_values = true, env = "YT_TRANSCRIBER_YT_DLP_ARGS")]
+    yt_dlp_args: Option<String>,
+
+    /// Reuse subtitles downloaded by earlier runs (default)
+    #[arg(long, global = true, overrides_with = "no_cache")]
+    cache: bool,
+
+    /// Always download subtitles again and leave the cache untouched
+    #[arg(long, global = true, overrides_with = "cache")]
+    no_cache: bool,
+
+    /// How long cached subtitles stay valid (e.g. 30m, 12h, 7d)
+    #[arg(long, global = true, value_parser = duration_arg, default_value = "24h", env = "YT_TRANSCRIBER_CACHE_TTL")]
+    cache_ttl: Duration,
+
+    #[command(flatten)]
+    options: TranscribeArgs,
+}
+
+/// Options controlling how a single video is fetched, processed and written.
+#[derive(Args, Clone)]
+struct TranscribeArgs {
+    /// Output format
+    #[arg(
+        short,
+        long,
+        default_value = "txt",
+        default_value_if("chunk", clap::builder::ArgPredicate::IsPresent, "jsonl"),
+        value_enum,
+        env = "YT_TRANSCRIBER_FORMAT"
+    )]
+    format: OutputFormat,
+
+    /// Print each segment as soon as it is parsed instead of after the whole transcript (txt or
+    /// jsonl on stdout), so `head` or `grep` see output right away
+    #[arg(long, conflicts_with_all = [
+        "output", "output_dir", "template", "clipboard", "split_by", "keywords", "mark",
+        "toc", "summarize", "auto_chapters", "chunk", "mt_translate", "sentences", "paragraphs",
+        "max_segment", "max_line_length", "cps_report", "speakers", "pager",
+    ])]
+    stream: bool,
+
+    /// Follow a live broadcast: print its captions as they appear until the stream ends (txt or
+    /// jsonl on stdout)
+    #[arg(long, conflicts_with_all = [
+        "stream", "output", "output_dir", "template", "clipboard", "split_by", "keywords",
+        "mark", "toc", "summarize", "auto_chapters", "chunk", "mt_translate", "sentences",
+        "paragraphs", "max_segment", "max_line_length", "cps_report", "speakers", "pager",
+    ])]
+    live: bool,
+
+    /// How often --live checks for new captions
+    #[arg(long, value_parser = duration_arg, default_value = "15s", requires = "live")]
+    live_interval: Duration,
+
+    /// Copy the transcript to the clipboard instead of printing it (as well as writing --output)
+    #[arg(long)]
+    clipboard: bool,
+
+    /// Character encoding of text output
+    #[arg(long, default_value = "utf-8", value_enum, env = "YT_TRANSCRIBER_ENCODING")]
+    encoding: TextEncoding,
+
+    /// Render output with a Tera template file instead of a built-in format
+    #[arg(long, env = "YT_TRANSCRIBER_TEMPLATE")]
+    template: Option<String>,
+
+    /// Output file path (a directory for playlists and channels), sqlite:<db> to upsert into a
+    /// SQLite database, or s3://bucket/key (a key prefix for playlists) (default: stdout). A path
+    /// with {id}, {title}, {channel}, {upload_date}, {language} or {ext} is a template filled in
+    /// for each video, e.g. "{channel}/{upload_date}-{title}.{ext}"
+    #[arg(short, long, value_parser = output_arg)]
+    output: Option<String>,
+
+    /// Write each transcript to <video id>.<format> in this directory instead of stdout (--output
+    /// takes precedence)
+    #[arg(long, env = "YT_TRANSCRIBER_OUTPUT_DIR")]
+    output_dir: Option<String>,
+
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+
+    /// Leave output files that already exist alone and skip their videos, so a playlist or
+    /// channel run can be repeated to fill in the gaps
+    #[arg(long, conflicts_with = "force")]
+    skip_existing: bool,
+
+    /// Add to the --output file instead of writing a new one, so several videos collect in one
+    /// corpus file (txt, jsonl and csv)
+    #[arg(long, requires = "output", conflicts_with_all = ["force", "skip_existing", "template"])]
+    append: bool,
+
+    /// Write one file per chapter (chapter) or per stretch of time (e.g. duration=10m), numbered
+    /// after the output file name
+    #[arg(long, value_name = "chapter|duration=<d>", value_parser = split_arg, conflicts_with = "append")]
+    split_by: Option<SplitBy>,
+
+    /// S3-compatible endpoint for s3:// output, e.g. http://localhost:9000 for MinIO
+    #[arg(long)]
+    s3_endpoint: Option<String>,
+
+    /// POST to this URL after each video is written
+    #[arg(long, env = "YT_TRANSCRIBER_WEBHOOK")]
+    webhook: Option<String>,
+
+    /// What the webhook receives: the full JSON transcript or a short summary with the output path
+    #[arg(long, value_enum, default_value = "full", requires = "webhook")]
+    webhook_payload: WebhookPayload,
+
+    /// Settings profile from the config file ([profile.<name>]) to apply on top of its defaults
+    #[arg(long, env = "YT_TRANSCRIBER_PROFILE")]
+    profile: Option<String>,
+
+    /// Language code for transcript; a comma-separated list is tried in order (e.g. en,en-GB,de)
+    #[arg(short, long, default_value = "en", env = "YT_TRANSCRIBER_LANGUAGE", value_parser = LanguageParser, hide_possible_values = true)]
+    language: String,
+
+    /// Exclude timestamps from TXT output
+    #[arg(long)]
+    no_timestamps: bool,
+
+    /// Write TXT and md-notes timestamps as links that open the video at that point
+    /// ([01:05](https://www.youtube.com/watch?v=ID&t=65s))
+    #[arg(long, conflicts_with = "no_timestamps")]
+    link_timestamps: bool,
+
+    /// Color timestamps and headings of TXT output on standard output (auto: on a terminal,
+    /// unless NO_COLOR is set)
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "auto")]
+    color: ColorMode,
+
+    /// Show output on a terminal through a pager ($PAGER, or less)
+    #[arg(long)]
+    pager: bool,
+
+    /// Keep the repeated lines of rolling auto-generated captions
+    #[arg(long)]
+    no_dedup: bool,
+
+    /// Pause between videos of a playlist, channel or watched feed, picked at random from a
+    /// range of seconds (e.g. 2..5) or fixed (e.g. 3), to avoid YouTube's rate limiting
+    #[arg(long, value_parser = sleep_arg, env = "YT_TRANSCRIBER_SLEEP")]
+    sleep: Option<SleepRange>,
+
+    /// Keep HTML entities such as &amp; and &#39; in the caption text
+    #[arg(long)]
+    raw_entities: bool,
+
+    /// Keep VTT cue positioning (line, position, align) in JSON, VTT and ASS output
+    #[arg(long)]
+    cue_settings: bool,
+
+    /// Keep per-word timings from auto-generated captions in JSON output
+    #[arg(long)]
+    word_timestamps: bool,
+
+    /// How to treat speaker labels (>> and NAME: prefixes)
+    #[arg(long, value_enum, default_value = "keep")]
+    speakers: SpeakerLabels,
+
+    /// Remove non-speech annotations such as [Music], [Applause] and (laughs)
+    #[arg(long)]
+    strip_annotations: bool,
+
+    /// Regex of an annotation to strip; repeatable, replaces the default bracket patterns
+    #[arg(long = "annotation-pattern", value_name = "REGEX", requires = "strip_annotations")]
+    annotation_patterns: Vec<String>,
+
+    /// Drop segments left empty after stripping annotations
+    #[arg(long, requires = "strip_annotations")]
+    drop_empty: bool,
+
+    /// Mask profanity in the text, keeping each word's first letter (f***)
+    #[arg(long)]
+    censor: bool,
+
+    /// Wordlist for --censor, one word per line; a trailing * also matches longer words
+    #[arg(long, value_name = "FILE", requires = "censor")]
+    censor_words: Option<PathBuf>,
+
+    /// Replace every match of a regex in the text, e.g. '[\w.+-]+@[\w-]+\.[\w.]+=[email]'
+    /// ($1 inserts a group); repeatable, applied in order
+    #[arg(long, value_name = "PATTERN=REPLACEMENT", value_parser = redact_arg)]
+    redact: Vec<Redaction>,
+
+    /// Machine-translate the segment text into this language (e.g. de, pt-br), keeping the timing
+    #[arg(long, value_name = "LANG")]
+    mt_translate: Option<String>,
+
+    /// Service for --mt-translate (DEEPL_API_KEY; LIBRETRANSLATE_URL, LIBRETRANSLATE_API_KEY)
+    #[arg(long, value_enum, default_value = "deepl", requires = "mt_translate")]
+    mt_provider: MtProvider,
+
+    /// Add the top N key phrases with first-occurrence timestamps to JSON/Markdown output
+    #[arg(long, value_name = "N")]
+    keywords: Option<usize>,
+
+    /// List every mention of these comma-separated keywords with its timestamp and a youtu.be
+    /// link that jumps to it (e.g. "rust,borrow checker"); added to txt, json and md-notes output
+    #[arg(long, value_name = "KEYWORDS", value_delimiter = ',')]
+    mark: Vec<String>,
+
+    /// Add a table of contents with the first sentence of every interval and a link to it
+    /// (e.g. "every=5m"), to skim long streams; added to txt, json and md-notes output
+    #[arg(long, value_name = "every=DURATION", value_parser = toc_arg)]
+    toc: Option<f64>,
+
+    /// Summarize the transcript with timestamped key points using an OpenAI-compatible endpoint
+    /// (OPENAI_BASE_URL, OPENAI_API_KEY, OPENAI_MODEL); added to txt, json and md-notes output
+    #[arg(long)]
+    summarize: bool,
+
+    /// For videos without chapters, have the --summarize endpoint propose chapter titles with
+    /// timestamps; added to JSON and md-notes output and followed by --split-by chapter
+    #[arg(long)]
+    auto_chapters: bool,
+
+    /// Write retrieval chunks of about N tokens instead of segments, as JSONL with their text,
+    /// start and end times and a link to the start (e.g. "tokens=500 overlap=50")
+    #[arg(
+        long,
+        value_name = "tokens=N [overlap=N]",
+        value_parser = chunk_arg,
+        conflicts_with_all = ["template", "split_by"]
+    )]
+    chunk: Option<ChunkSpec>,
+
+    /// Add an embedding vector to every chunk, from the OpenAI-compatible embeddings API or a
+    /// local model
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "api", requires = "chunk")]
+    embed: Option<Embedder>,
+
+    /// Add each segment's token count and the total to JSON output, to budget LLM context
+    /// (estimated unless built with the tokenizer feature)
+    #[arg(long)]
+    count_tokens: bool,
+
+    /// Only output segments after this time (e.g. 12:30, 1:02:03, 90s)
+    #[arg(long, value_parser = time_arg)]
+    from: Option<f64>,
+
+    /// Only output segments before this time
+    #[arg(long, value_parser = time_arg)]
+    to: Option<f64>,
+
+    /// Ignore start/end times in the URL (t=, start=, end=, clips) and output the whole video
+    #[arg(long)]
+    ignore_url_time: bool,
+
+    /// Shift all cue times, e.g. +3.5s or -1:30 (applied after --stretch)
+    #[arg(long, allow_hyphen_values = true, value_parser = offset_arg)]
+    offset: Option<f64>,
+
+    /// Multiply all cue times by this factor, e.g. 1.001 for 23.976/24 fps re-encodes
+    #[arg(long, value_parser = stretch_arg)]
+    stretch: Option<f64>,
+
+    /// Re-split the transcript into full sentences using punctuation
+    #[arg(long)]
+    sentences: bool,
+
+    /// Merge segments into paragraphs separated by pauses
+    #[arg(long)]
+    paragraphs: bool,
+
+    /// Pause in seconds that starts a new paragraph
+    #[arg(long, default_value_t = 2.0, requires = "paragraphs")]
+    gap: f64,
+
+    /// Longest paragraph in seconds before a new one is started anyway
+    #[arg(long, default_value_t = 60.0, requires = "paragraphs")]
+    max_paragraph: f64,
+
+    /// Split cues longer than this at word boundaries (e.g. 7s)
+    #[arg(long, value_parser = time_arg)]
+    max_segment: Option<f64>,
+
+    /// Wrap cue text into lines of at most this many characters (for SRT/VTT, e.g. 42)
+    #[arg(long)]
+    max_line_length: Option<usize>,
+
+    /// Split cues needing more than this many wrapped lines into consecutive cues
+    #[arg(long, requires = "max_line_length")]
+    max_lines: Option<usize>,
+
+    /// Report cues whose reading speed exceeds --max-cps on stderr
+    #[arg(long)]
+    cps_report: bool,
+
+    /// Reading speed limit in characters per second
+    #[arg(long, default_value_t = 17.0, requires = "cps_report")]
+    max_cps: f64,
+}
+
+/// Caption languages offered by shell completion; any other code is accepted too.
+const COMMON_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("sv", "Swedish"),
+    ("tr", "Turkish"),
+    ("ru", "Russian"),
+    ("uk", "Ukrainian"),
+    ("ar", "Arabic"),
+    ("hi", "Hindi"),
+    ("id", "Indonesian"),
+    ("vi", "Vietnamese"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("zh-Hans", "Chinese (Simplified)"),
+    ("zh-Hant", "Chinese (Traditional)"),
+];
+
+/// Parses a language code, listing the common ones as possible values for completion scripts.
+#[derive(Clone)]
+struct LanguageParser;
+
+impl TypedValueParser for LanguageParser {
+    type Value = String;
+
+    fn parse_ref(&self, cmd: &clap::Command, arg: Option<&clap::Arg>, value: &OsStr) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            COMMON_LANGUAGES
+                .iter()
+                .map(|(code, name)| PossibleValue::new(*code).help(*name)),
+        ))
+    }
+}
+
+fn offset_arg(value: &str) -> Result<f64, String> {
+    parse_offset(value).ok_or_else(|| format!("invalid offset '{}', expected e.g. +3.5s or -1:30", value))
+}
+
+fn stretch_arg(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(factor) if factor.is_finite() && factor > 0.0 => Ok(factor),
+        _ => Err(format!("invalid factor '{}', expected a positive number such as 1.001", value)),
+    }
+}
+
+fn duration_arg(value: &str) -> Result<Duration, String> {
+    parse_duration(value)
+        .map(Duration::from_secs_f64)
+        .ok_or_else(|| format!("invalid duration '{}', expected e.g. 30m, 12h or 7d", value))
+}
+
+fn sleep_arg(value: &str) -> Result<SleepRange, String> {
+    SleepRange::parse(value).ok_or_else(|| format!("invalid sleep '{}', expected seconds such as 3 or 2..5", value))
+}
+
+fn split_arg(value: &str) -> Result<SplitBy, String> {
+    SplitBy::parse(value)
+}
+
+fn chunk_arg(value: &str) -> Result<ChunkSpec, String> {
+    ChunkSpec::parse(value)
+}
+
+fn redact_arg(value: &str) -> Result<Redaction, String> {
+    Redaction::parse(value)
+}
+
+fn toc_arg(value: &str) -> Result<f64, String> {
+    parse_interval(value)
+}
+
+/// Sleeps for a random pause from --sleep, if given.
+fn pause(sleep: Option<&SleepRange>) {
+    if let Some(range) = sleep {
+        let pause = range.pick();
+        debug!(1, "Sleeping {:.1}s", pause.as_secs_f64());
+        thread::sleep(pause);
+    }
+}
+
+/// Browsers yt-dlp can read cookies from.
+const COOKIE_BROWSERS: &[&str] = &["brave", "chrome", "chromium", "edge", "firefox", "opera", "safari", "vivaldi", "whale"];
+
+/// Checks the browser of a yt-dlp `BROWSER[+KEYRING][:PROFILE][::CONTAINER]` value.
+fn browser_arg(value: &str) -> Result<String, String> {
+    let browser = value.split([':', '+']).next().unwrap_or_default();
+    if COOKIE_BROWSERS.contains(&browser.to_ascii_lowercase().as_str()) {
+        Ok(value.to_string())
+    } else {
+        Err(format!("unsupported browser '{}', expected one of {}", browser, COOKIE_BROWSERS.join(", ")))
+    }
+}
+
+fn country_arg(value: &str) -> Result<String, String> {
+    if value.len() == 2 && value.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(value.to_ascii_uppercase())
+    } else {
+        Err(format!("invalid country '{}', expected a two-letter code such as US", value))
+    }
+}
+
+fn output_arg(value: &str) -> Result<String, String> {
+    output_path::check(value)?;
+    Ok(value.to_string())
+}
+
+fn time_arg(value: &str) -> Result<f64, String> {
+    parse_time(value).ok_or_else(|| format!("invalid time '{}', expected [[hh:]mm:]ss", value))
+}
+
+/// Formats `convert` and local input files are read in.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum InputFormat {
+    Vtt,
+    Srt,
+    /// YouTube's JSON timedtext
+    Json3,
+    /// YouTube's XML timedtext
+    Srv3,
+    /// A transcript written with --format json
+    Json,
+}
+
+impl InputFormat {
+    fn from_path(path: &Path) -> Option<InputFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        InputFormat::value_variants()
+            .iter()
+            .copied()
+            .find(|format| format.to_possible_value().is_some_and(|value| value.get_name() == extension))
+    }
+
+    /// Guesses the format from the start of the content.
+    fn sniff(content: &str) -> InputFormat {
+        let start = content.trim_start_matches('\u{feff}').trim_start();
+        if start.starts_with("WEBVTT") {
+            InputFormat::Vtt
+        } else if start.starts_with('<') {
+            InputFormat::Srv3
+        } else if start.starts_with('{') && content.contains("\"events\"") {
+            InputFormat::Json3
+        } else if start.starts_with('{') {
+            InputFormat::Json
+        } else {
+            InputFormat::Srt
+        }
+    }
+}
+
+#[derive(Clone, ValueEnum)]
+enum WebhookPayload {
+    /// The transcript as JSON, like --format json
+    Full,
+    /// Video ID, title, segment count and output path
+    Summary,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorMode {
+    /// Color when standard output is a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, ValueEnum)]
+enum SpeakerLabels {
+    /// Record speakers but leave the text unchanged
+    Keep,
+    /// Remove the labels from the text
+    Strip,
+    /// Merge each speaker turn into one NAME:-prefixed block
+    Group,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Print transcript segments matching a query, with surrounding context
+    Search(SearchArgs),
+
+    /// Browse a transcript in the terminal: scroll, search as you type, copy timestamped quotes
+    /// and open the video at a segment
+    Tui(TuiArgs),
+
+    /// Add previously extracted JSON transcripts to the local search index
+    Index(IndexArgs),
+
+    /// Search every transcript in the local index
+    Query(QueryArgs),
+
+    /// Report word counts, speaking rate and silences for a video
+    Stats(StatsArgs),
+
+    /// Compare two transcripts word by word
+    Diff(DiffArgs),
+
+    /// Score a video's auto-generated captions against its manual captions
+    Audit(AuditArgs),
+
+    /// Inspect and prune the subtitle cache
+    Cache(CacheArgs),
+
+    /// Poll a channel or playlist and transcribe new uploads into the output directory
+    Watch(Box<WatchArgs>),
+
+    /// Serve transcripts over HTTP at GET /transcript/<id>?lang=en&format=json
+    Serve(Box<ServeArgs>),
+
+    /// Run a Model Context Protocol server on stdio with a get_transcript tool
+    Mcp(Box<McpArgs>),
+
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate the script for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Print the man page in roff format
+    Man,
+
+    /// Print the JSON Schema of JSON transcripts (--format json)
+    Schema {
+        /// Print the protobuf definition of --format proto instead
+        #[arg(long)]
+        proto: bool,
+    },
+
+    /// Check yt-dlp, network access to YouTube and write access, and suggest fixes
+    Doctor(DoctorArgs),
+
+    /// Replace this executable with the latest release for this platform
+    SelfUpdate(SelfUpdateArgs),
+
+    /// Show or update the yt-dlp used for downloads
+    Deps(DepsArgs),
+
+    /// Convert a subtitle or transcript file to another format without touching the network
+    Convert(Box<ConvertArgs>),
+}
+
+#[derive(Args)]
+struct ConvertArgs {
+    /// Subtitle or transcript file (vtt, srt, json3, srv3 or a JSON transcript), or - for stdin
+    input: String,
+
+    /// Format of the input (default: from the file extension, or guessed from the content)
+    #[arg(long, value_enum)]
+    from: Option<InputFormat>,
+
+    #[command(flatten)]
+    options: TranscribeArgs,
+}
+
+#[derive(Args)]
+struct DepsArgs {
+    #[command(subcommand)]
+    action: DepsAction,
+}
+
+#[derive(Subcommand)]
+enum DepsAction {
+    /// Print the yt-dlp version, location and age
+    Status,
+
+    /// Download the latest standalone yt-dlp (or the one given) into the data directory
+    Update {
+        /// Release to install instead of the latest (e.g. 2024.08.06)
+        version: Option<String>,
+    },
+}
+
+#[derive(Args)]
+struct SelfUpdateArgs {
+    /// Only report whether a newer release is available
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Args)]
+struct DoctorArgs {
+    /// Output directory to check for write access (default: current directory)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SearchArgs {
+    /// YouTube URL or video ID
+    url: String,
+
+    /// Text to search for (case-insensitive)
+    query: String,
+
+    /// Treat the query as a regular expression
+    #[arg(long, conflicts_with = "fuzzy")]
+    regex: bool,
+
+    /// Match approximately, tolerating typos and misremembered words
+    #[arg(long)]
+    fuzzy: bool,
+
+    /// Maximum character edits for --fuzzy (default: one per four characters)
+    #[arg(long, requires = "fuzzy")]
+    max_distance: Option<usize>,
+
+    /// Number of segments to show before and after each match
+    #[arg(short = 'C', long, default_value_t = 1)]
+    context: usize,
+
+    /// Language code for transcript
+    #[arg(short, long, default_value = "en", env = "YT_TRANSCRIBER_LANGUAGE", value_parser = LanguageParser, hide_possible_values = true)]
+    language: String,
+}
+
+#[derive(Args)]
+struct TuiArgs {
+    /// YouTube URL or video ID
+    url: String,
+
+    /// Language code for transcript
+    #[arg(short, long, default_value = "en", env = "YT_TRANSCRIBER_LANGUAGE", value_parser = LanguageParser, hide_possible_values = true)]
+    language: String,
+}
+
+#[derive(Args)]
+struct IndexArgs {
+    /// JSON transcripts (from --format json) or directories containing them
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Index location (default: <data dir>/yt-transcriber/index)
+    #[arg(long)]
+    index_dir: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct QueryArgs {
+    /// Search query: words, "quoted phrases", AND/OR, or field:word (text, title)
+    query: String,
+
+    /// Maximum number of results
+    #[arg(short = 'n', long, default_value_t = 10)]
+    limit: usize,
+
+    /// Index location (default: <data dir>/yt-transcriber/index)
+    #[arg(long)]
+    index_dir: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    /// YouTube URL or video ID
+    url: String,
+
+    /// Print statistics as JSON instead of a table
+    #[arg(long)]
+    json: bool,
+
+    /// Window size in seconds for words-per-minute over time
+    #[arg(long, default_value_t = 60.0)]
+    bucket: f64,
+
+    /// Number of longest silence gaps to report
+    #[arg(long, default_value_t = 5)]
+    gaps: usize,
+
+    /// Include readability scores, sentence length and vocabulary richness
+    #[arg(long)]
+    readability: bool,
+
+    /// Language code for transcript
+    #[arg(short, long, default_value = "en", env = "YT_TRANSCRIBER_LANGUAGE", value_parser = LanguageParser, hide_possible_values = true)]
+    language: String,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Original transcript: YouTube URL, video ID, subtitle file, or saved JSON transcript
+    old: String,
+
+    /// Revised transcript: YouTube URL, video ID, subtitle file, or saved JSON transcript
+    new: String,
+
+    /// Print differences as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Language code for transcripts fetched from YouTube
+    #[arg(short, long, default_value = "en", env = "YT_TRANSCRIBER_LANGUAGE", value_parser = LanguageParser, hide_possible_values = true)]
+    language: String,
+}
+
+#[derive(Args)]
+struct AuditArgs {
+    /// YouTube URL or video ID
+    url: String,
+
+    /// List segments whose word error rate exceeds this fraction
+    #[arg(long, default_value_t = 0.3)]
+    threshold: f64,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Language code for both caption tracks
+    #[arg(short, long, default_value = "en", env = "YT_TRANSCRIBER_LANGUAGE", value_parser = LanguageParser, hide_possible_values = true)]
+    language: String,
+}
+
+#[derive(Args)]
+struct WatchArgs {
+    /// Channel or playlist URL
+    url: String,
+
+    /// Time between checks for new uploads (e.g. 15m, 1h)
+    #[arg(long, value_parser = duration_arg, default_value = "1h")]
+    interval: Duration,
+
+    /// Archive of transcribed videos (default: .yt-transcriber-archive.txt in the output
+    /// directory, next to the database, or in the current directory for s3:// output)
+    #[arg(long)]
+    archive: Option<PathBuf>,
+
+    /// On the first check, only record the existing videos; transcribe uploads after that
+    #[arg(long)]
+    new_only: bool,
+
+    /// Check once and exit instead of polling
+    #[arg(long)]
+    once: bool,
+
+    #[command(flatten)]
+    options: TranscribeArgs,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Port to listen on
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+
+    /// Address to bind to
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Transcripts fetched at the same time
+    #[arg(long, default_value_t = 4)]
+    workers: usize,
+
+    /// Requests waiting for a worker before new ones are turned away with 503
+    #[arg(long, default_value_t = 32)]
+    queue: usize,
+
+    /// Defaults for every request; lang and format query parameters override them
+    #[command(flatten)]
+    options: TranscribeArgs,
+}
+
+#[derive(Args)]
+struct McpArgs {
+    /// Defaults for every tool call; the tool's language and format arguments override them
+    #[command(flatten)]
+    options: TranscribeArgs,
+}
+
+#[derive(Args)]
+struct CacheArgs {
+    #[command(subcommand)]
+    action: CacheAction,
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// List cached videos, oldest first
+    List,
+
+    /// Delete cached subtitles
+    Clear {
+        /// Only delete entries older than this (e.g. 7d)
+        #[arg(long, value_parser = duration_arg)]
+        older_than: Option<Duration>,
+    },
+
+    /// Show entry count, total size and the oldest and newest entries
+    Stats,
+
+    /// Print the cache directory
+    Path,
+}
+
+#[derive(Clone, ValueEnum)]
+enum OutputFormat {
+    Txt,
+    Srt,
+    Vtt,
+    Ass,
+    AssKaraoke,
+    Lrc,
+    Json,
+    Jsonl,
+    Csv,
+    MdNotes,
+    Parquet,
+    Arrow,
+    Msgpack,
+    Proto,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MtProvider {
+    Deepl,
+    Libretranslate,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Embedder {
+    /// The embeddings endpoint of OPENAI_BASE_URL, with OPENAI_EMBEDDING_MODEL
+    Api,
+    /// all-MiniLM-L6-v2 on the CPU (needs the local-embeddings feature)
+    Local,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TextEncoding {
+    /// UTF-8 without a byte order mark
+    #[value(name = "utf-8")]
+    Utf8,
+    /// UTF-8 with a byte order mark, for Windows tools that guess the encoding otherwise
+    #[value(name = "utf-8-bom")]
+    Utf8Bom,
+    /// UTF-16 little-endian with a byte order mark, for legacy subtitle editors
+    #[value(name = "utf-16le")]
+    Utf16le,
+}
+
+/// Encodes text output. `bom` is false for text added to the end of an existing file.
+fn encode_text(text: &str, encoding: TextEncoding, bom: bool) -> Vec<u8> {
+    match encoding {
+        TextEncoding::Utf8 => text.as_bytes().to_vec(),
+        TextEncoding::Utf8Bom => {
+            let mut bytes = if bom { vec![0xEF, 0xBB, 0xBF] } else { Vec::new() };
+            bytes.extend_from_slice(text.as_bytes());
+            bytes
+        }
+        TextEncoding::Utf16le => {
+            let mut bytes = if bom { vec![0xFF, 0xFE] } else { Vec::new() };
+            bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+            bytes
+        }
+    }
+}
+
+/// Encodes the transcript's chunks when it has them, its segments otherwise.
+#[cfg(feature = "parquet")]
+fn encode_parquet(result: &TranscriptResult) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::new();
+    match &result.chunks {
+        Some(chunks) => yt_transcriber::columnar::write_chunks_parquet(&mut buf, chunks),
+        None => yt_transcriber::columnar::write_parquet(&mut buf, result),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(buf)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn encode_parquet(_result: &TranscriptResult) -> Result<Vec<u8>, String> {
+    Err("this build has no Parquet support (rebuild with --features parquet)".to_string())
+}
+
+/// Encodes the transcript's chunks when it has them, its segments otherwise, as an Arrow IPC file.
+#[cfg(feature = "arrow")]
+fn encode_arrow(result: &TranscriptResult) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::new();
+    match &result.chunks {
+        Some(chunks) => yt_transcriber::columnar::write_chunks_arrow(&mut buf, chunks),
+        None => yt_transcriber::columnar::write_arrow(&mut buf, result),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(buf)
+}
+
+#[cfg(not(feature = "arrow"))]
+fn encode_arrow(_result: &TranscriptResult) -> Result<Vec<u8>, String> {
+    Err("this build has no Arrow support (rebuild with --features arrow)".to_string())
+}
+
+/// Compresses output for a `.gz` or `.zst` path.
+fn compress_output(path: &str, bytes: &[u8]) -> Result<Option<Vec<u8>>, Exit> {
+    let Some(compression) = Compression::from_path(path) else {
+        return Ok(None);
+    };
+    compression.compress(bytes).map(Some).map_err(|e| {
+        error!(encode_failed: "Failed to compress output - {}", e);
+        Exit::InvalidArgs
+    })
+}
+
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_text: &str) -> Result<(), String> {
+    Err("this build has no clipboard support (rebuild with --features clipboard)".to_string())
+}
+
+/// Advice printed when a request to the LLM endpoint fails.
+const LLM_SETUP_HINT: &str = "Configure your endpoint in the [llm] section of the config file or with OPENAI_BASE_URL";
+
+fn llm_client() -> &'static LlmClient {
+    LLM.get_or_init(|| LlmClient::from_config(&LlmConfig::default()))
+}
+
+/// Adds embeddings to chunks.
+fn embed_chunks(embedder: Embedder, chunks: &mut [Chunk]) -> Result<(), String> {
+    let texts: Vec<String> = chunks.iter().map(|chunk| chunk.text.clone()).collect();
+    let vectors = match embedder {
+        Embedder::Api => llm_client().embed(&texts)?,
+        Embedder::Local => embed_locally(&texts)?,
+    };
+    for (chunk, vector) in chunks.iter_mut().zip(vectors) {
+        chunk.embedding = Some(vector);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "local-embeddings")]
+fn embed_locally(texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    yt_transcriber::embed::embed_local(texts)
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+fn embed_locally(_texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    Err("this build has no local embedding model (rebuild with --features local-embeddings)".to_string())
+}
+
+/// Writes output to a file, or uploads it when `path` is an `s3://` URL. Output for a `.gz` or
+/// `.zst` path is compressed first.
+fn save_output(args: &TranscribeArgs, path: &str, bytes: &[u8]) -> Exit {
+    let compressed = match compress_output(path, bytes) {
+        Ok(compressed) => compressed,
+        Err(code) => return code,
+    };
+    let bytes = compressed.as_deref().unwrap_or(bytes);
+    if is_s3_url(path) {
+        let Some(location) = S3Location::parse(path) else {
+            error!(invalid_argument: "Invalid S3 URL {} (expected s3://bucket/key)", path);
+            return Exit::InvalidArgs;
+        };
+        let client = match S3Client::from_env(args.s3_endpoint.as_deref()) {
+            Ok(client) => client,
+            Err(e) => {
+                error!(missing_credentials: "{}", e);
+                return Exit::InvalidArgs;
+            }
+        };
+        let extension = Path::new(&location.key).extension().and_then(|e| e.to_str());
+        if let Err(e) = client.put_object(&location, bytes, content_type(extension.unwrap_or(""))) {
+            error!(upload_failed: "Failed to upload to {} - {}", path, e);
+            return Exit::Network;
+        }
+    } else {
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .create_new(!args.force)
+            .open(path);
+        match file.and_then(|mut file| file.write_all(bytes)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && args.skip_existing => {
+                OUTPUT_EXISTED.with(|existed| existed.set(true));
+                notice!("Skipping {} - already exists", path);
+                return Exit::Success;
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                error!(
+                    output_exists, hint: "Use --force to overwrite it or --skip-existing to keep it";
+                    "{} already exists", path
+                );
+                return Exit::WriteFailed;
+            }
+            Err(e) => {
+                error!(write_failed: "Failed to write file - {}", e);
+                return Exit::WriteFailed;
+            }
+        }
+    }
+    notice!("Transcript saved to {}", path);
+    Exit::Success
+}
+
+/// Runs yt-dlp with the fetch settings of the command line, as [`Transcriber::run_yt_dlp`].
+fn run_yt_dlp(args: &[&str]) -> Result<Output, TranscriberError> {
+    fetch_settings().build().run_yt_dlp(args)
+}
+
+/// The yt-dlp command to run: --yt-dlp-path, then the managed standalone binary once downloaded,
+/// then the first of the candidates on PATH that runs.
+fn yt_dlp_program() -> Vec<String> {
+    if let Some(path) = YT_DLP_PATH.get() {
+        return path.clone();
+    }
+    let managed = yt_dlp::managed_path();
+    if managed.is_file() {
+        return vec![managed.to_string_lossy().into_owned()];
+    }
+    let found = FOUND_CANDIDATE.get_or_init(|| {
+        let candidates: Vec<&str> = match YT_DLP_CANDIDATES.get().filter(|candidates| !candidates.is_empty()) {
+            Some(candidates) => candidates.iter().map(String::as_str).collect(),
+            None => DEFAULT_YT_DLP_CANDIDATES.to_vec(),
+        };
+        let found = candidates
+            .into_iter()
+            .find(|candidate| Command::new(candidate).arg("--version").output().is_ok_and(|o| o.status.success()))
+            .map(str::to_string);
+        match found.as_deref() {
+            Some("youtube-dl") => warning!("yt-dlp not found, using youtube-dl; clips and some caption tracks may not work"),
+            Some(candidate) if candidate != "yt-dlp" => debug!(1, "yt-dlp not found, using {}", candidate),
+            _ => {}
+        }
+        found
+    });
+    vec![found.clone().unwrap_or_else(|| "yt-dlp".to_string())]
+}
+
+fn yt_dlp_command() -> Command {
+    let program = yt_dlp_program();
+    let mut command = Command::new(&program[0]);
+    command.args(&program[1..]);
+    command
+}
+
+/// The version yt-dlp reports, such as `2024.08.06`.
+fn yt_dlp_version() -> Option<String> {
+    let output = yt_dlp_command().arg("--version").output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
+/// Asks on the terminal whether to go ahead with `action`. --yes answers yes; --no-install, or
+/// no terminal to ask on (CI, MCP over stdio), answers no.
+fn confirm_install(action: &str) -> bool {
+    if NO_INSTALL.load(Ordering::Relaxed) {
+        return false;
+    }
+    if ASSUME_YES.load(Ordering::Relaxed) {
+        return true;
+    }
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return false;
+    }
+    let ask = || {
+        eprint!("{}? [y/N] ", action);
+        let _ = io::stderr().flush();
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+    };
+    // yt-dlp may be installed halfway through a fetch, with a spinner on the line.
+    let bar = PROGRESS.lock().ok().and_then(|progress| progress.clone());
+    match bar {
+        Some(bar) => bar.suspend(ask),
+        None => ask(),
+    }
+}
+
+/// Downloads the standalone yt-dlp binary (the pinned version, if any) into the data directory
+/// once the user agrees.
+fn install_yt_dlp() -> Result<(), TranscriberError> {
+    let version = PINNED_YT_DLP.get().map(String::as_str);
+    let dir = yt_dlp::managed_path().parent().map(Path::to_path_buf).unwrap_or_default();
+    let action = format!(
+        "Download yt-dlp {} from github.com/{} into {}",
+        version.unwrap_or("(latest)"),
+        yt_dlp::REPO,
+        dir.display()
+    );
+    if !confirm_install(&action) {
+        let reason = if NO_INSTALL.load(Ordering::Relaxed) {
+            "--no-install is set"
+        } else if io::stdin().is_terminal() && io::stderr().is_terminal() {
+            "declined"
+        } else {
+            "no terminal to confirm on"
+        };
+        return Err(TranscriberError::YtDlpMissing {
+            message: format!("yt-dlp is required but was not installed ({})", reason),
+            hint: Some("Install it yourself (pip install yt-dlp), run `yt-transcriber deps update`, or rerun with --yes".into()),
+        });
+    }
+    install_yt_dlp_version(version)
+}
+
+fn install_yt_dlp_version(version: Option<&str>) -> Result<(), TranscriberError> {
+    let progress = Progress::spinner(format!("Downloading yt-dlp {}", version.unwrap_or("(latest)")));
+    let installed = yt_dlp::install(version);
+    drop(progress);
+    match installed {
+        Ok(tag) => {
+            notice!("Installed yt-dlp {} to {}", tag, yt_dlp::managed_path().display());
+            Ok(())
+        }
+        Err(e) => Err(TranscriberError::YtDlpMissing {
+            message: format!("yt-dlp is required but could not be downloaded - {}", e),
+            hint: Some("Please install it manually: pip install yt-dlp".into()),
+        }),
+    }
+}
+
+/// Makes sure a usable yt-dlp is installed, downloading it when missing or when the installed
+/// version differs from --yt-dlp-version. An explicit --yt-dlp-path is never replaced.
+fn ensure_yt_dlp() -> Result<(), TranscriberError> {
+    let installed = yt_dlp_version();
+    if let Some(path) = YT_DLP_PATH.get() {
+        if installed.is_none() {
+            return Err(TranscriberError::YtDlpMissing {
+                message: format!("yt-dlp at '{}' (--yt-dlp-path) does not run", path.join(" ")),
+                hint: None,
+            });
+        }
+        return Ok(());
+    }
+    let up_to_date = |installed: &Option<String>| match (installed, PINNED_YT_DLP.get()) {
+        (Some(installed), Some(pinned)) => installed == pinned,
+        (installed, None) => installed.is_some(),
+        (None, Some(_)) => false,
+    };
+    if up_to_date(&installed) {
+        if let (Some(version), None) = (&installed, PINNED_YT_DLP.get()) {
+            STALE_WARNING.call_once(|| {
+                if let Some(days) = yt_dlp::stale_days(version) {
+                    warning!(
+                        "yt-dlp {} is {} days old; outdated extractors often cause missing subtitles or HTTP 403 \
+                         errors. Run `yt-transcriber deps update`",
+                        version,
+                        days
+                    );
+                }
+            });
+        }
+        return Ok(());
+    }
+    // Server workers may all find yt-dlp missing at once; only the first one asks and installs.
+    let _installing = INSTALLING.lock();
+    if up_to_date(&yt_dlp_version()) {
+        return Ok(());
+    }
+    if installed.is_none() {
+        notice!("yt-dlp not found.");
+    }
+    install_yt_dlp()?;
+    if yt_dlp_version().is_none() {
+        return Err(TranscriberError::YtDlpMissing {
+            message: format!("yt-dlp was downloaded to {} but does not run", yt_dlp::managed_path().display()),
+            hint: None,
+        });
+    }
+    Ok(())
+}
+
+/// Asks yt-dlp for the underlying video ID and time window of a clip URL.
+fn resolve_clip(url: &str) -> Result<(String, Option<f64>, Option<f64>), Exit> {
+    let _progress = Progress::spinner("Resolving clip".to_string());
+    let output = run_yt_dlp(&[
+        "--skip-download",
+        "--no-warnings",
+        "--print",
+        "%(id)s %(section_start)s %(section_end)s",
+        url,
+    ]);
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        Ok(o) => return Err(yt_dlp_failure(&String::from_utf8_lossy(&o.stderr))),
+        Err(e) => return Err(report_error(&e)),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.split_whitespace();
+    let id = fields.next().unwrap_or_default().to_string();
+    let start = fields.next().and_then(|v| v.parse().ok());
+    let end = fields.next().and_then(|v| v.parse().ok());
+    Ok((id, start, end))
+}
+
+/// The yt-dlp, network and cache options of the command line.
+fn fetch_settings() -> TranscriberBuilder {
+    FETCH_SETTINGS.get().cloned().unwrap_or_else(Transcriber::builder)
+}
+
+/// Fetching and parsing in `language` with the options of the command line and otherwise the
+/// defaults, for subcommands without the transcription options.
+fn fetch_defaults(language: &str) -> TranscriberBuilder {
+    fetch_settings().language(language)
+}
+
+const BOT_CHECK_HELP: &str = "YouTube blocks requests it suspects are automated. To get past it, either:
+  - use the cookies of a logged-in browser: --cookies-from-browser firefox (or --cookies cookies.txt)
+  - pass a PO token: --po-token web.gvs+<token> (see https://github.com/yt-dlp/yt-dlp/wiki/PO-Token-Guide)
+  - try another player client: --player-client mweb
+and run the same command again.";
+
+/// Reports why a yt-dlp run failed, judging by its stderr, and returns the matching exit code.
+fn yt_dlp_failure(stderr: &str) -> Exit {
+    report_error(&TranscriberError::from_yt_dlp(stderr))
+}
+
+/// Reports a failure to fetch or parse a transcript, with advice where there is some, and returns
+/// the matching exit code.
+fn report_error(e: &TranscriberError) -> Exit {
+    let hint = match e {
+        TranscriberError::BotCheck(_) => Some(BOT_CHECK_HELP),
+        TranscriberError::GeoRestricted(_) => {
+            Some("Try another country with --geo-bypass-country, or a proxy in an allowed region with --proxy.")
+        }
+        TranscriberError::RateLimited(_) => {
+            Some("Wait a while before trying again, and slow down large runs with --sleep 2..5.")
+        }
+        _ => e.hint(),
+    };
+    log_error(e.kind(), e.to_string(), hint);
+    e.exit()
+}
+
+fn fetch_transcript(input: &str, options: &Transcriber) -> Result<TranscriptResult, Exit> {
+    let language = options.language();
+
+    let _language = LogContext::enter("language", language);
+    if input == STDIN_INPUT || is_subtitle_file(input) {
+        CACHE_HIT.with(|hit| hit.set(false));
+        return read_local_transcript(input, None, options);
+    }
+    let key = video_key(input);
+    if key.is_none() && is_web_url(input) {
+        error!(invalid_url: "Not a YouTube URL; use --any-site for other sites");
+        return Err(Exit::InvalidUrl);
+    }
+
+    let started = Instant::now();
+    let progress = Progress::spinner(format!(
+        "Fetching {} subtitles for {}",
+        language,
+        key.as_deref().unwrap_or(input.trim())
+    ));
+    let fetched = options.fetch_subtitles(input);
+    drop(progress);
+    let fetched = fetched.map_err(|e| report_error(&e))?;
+    CACHE_HIT.with(|hit| hit.set(fetched.cached));
+    let message = if fetched.cached {
+        let key = Cache::key(&fetched.video_id, language, options.track().name());
+        format!("Using cached subtitles {} from {}", key, fetched.subtitles.fetched_at)
+    } else {
+        format!("Downloaded subtitles for {}", fetched.video_id)
+    };
+    log_stage(
+        "fetch",
+        started,
+        message,
+        serde_json::json!({ "language": language, "cached": fetched.cached }),
+    );
+    transcript_from_subtitles(fetched.video_id, fetched.subtitles, options)
+}
+
+/// The YouTube video ID of `input`, or with --any-site a key derived from the URL of another
+/// site.
+fn video_key(input: &str) -> Option<String> {
+    extract_video_id(input).or_else(|| (ANY_SITE.load(Ordering::Relaxed) && is_web_url(input)).then(|| url_key(input)))
+}
+
+/// Input standing for standard input (--stdin-vtt, `convert -`).
+const STDIN_INPUT: &str = "-";
+
+/// Whether `input` names a local subtitle file rather than a video.
+fn is_subtitle_file(input: &str) -> bool {
+    let path = Path::new(input);
+    InputFormat::from_path(path).is_some_and(|format| format != InputFormat::Json) && path.is_file()
+}
+
+/// Reads a local subtitle or JSON transcript file, or standard input for `-`, in `format` or the
+/// one its extension or content suggests. The file name stands in for the video ID.
+fn read_local_transcript(input: &str, format: Option<InputFormat>, options: &Transcriber) -> Result<TranscriptResult, Exit> {
+    let (name, content) = if input == STDIN_INPUT {
+        let mut content = String::new();
+        let read = io::Read::read_to_string(&mut io::stdin(), &mut content).map(|_| content);
+        ("stdin".to_string(), read)
+    } else {
+        let path = Path::new(input);
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        (name, fs::read_to_string(path))
+    };
+    let content = match content {
+        Ok(content) => content,
+        Err(e) => {
+            error!(read_failed: "Failed to read {} - {}", input, e);
+            return Err(Exit::InvalidArgs);
+        }
+    };
+
+    let format = format
+        .or_else(|| InputFormat::from_path(Path::new(input)))
+        .unwrap_or_else(|| InputFormat::sniff(&content));
+    debug!(1, "Reading {} as {}", input, format.to_possible_value().expect("no skipped formats").get_name());
+    let (content, format) = match format {
+        InputFormat::Json => {
+            return serde_json::from_str(&content).map_err(|e| {
+                error!(invalid_transcript: "{} is not a JSON transcript - {}", input, e);
+                Exit::InvalidArgs
+            })
+        }
+        InputFormat::Srt => (srt::srt_to_vtt(&content), SubtitleFormat::Vtt),
+        InputFormat::Vtt => (content, SubtitleFormat::Vtt),
+        InputFormat::Json3 => (content, SubtitleFormat::Json3),
+        InputFormat::Srv3 => (content, SubtitleFormat::Srv3),
+    };
+    let subtitles = CachedSubtitles {
+        // Speech recognition leaves per-word timing tags in WebVTT and confidence values in the
+        // native formats.
+        auto_generated: ["<c>", "acAsrConf", " ac=\""].iter().any(|marker| content.contains(marker)),
+        content,
+        format,
+        video: None,
+        fetched_at: chrono::Utc::now().to_rfc3339(),
+        live: false,
+    };
+    transcript_from_subtitles(name, subtitles, options)
+}
+
+/// Parses fetched or local subtitles into a transcript, applying the parsing options.
+fn transcript_from_subtitles(
+    video_id: String,
+    subtitles: CachedSubtitles,
+    options: &Transcriber,
+) -> Result<TranscriptResult, Exit> {
+    options.parse(video_id, subtitles).map_err(|e| report_error(&e))
+}
+
+/// Loads a transcript saved with `--format json`, or fetches or parses it when `input` is a
+/// video or a subtitle file.
+fn load_transcript(input: &str, language: &str) -> Result<TranscriptResult, Exit> {
+    if !Path::new(input).is_file() || is_subtitle_file(input) {
+        return fetch_transcript(input, &fetch_defaults(language).build());
+    }
+
+    let content = match fs::read_to_string(input) {
+        Ok(c) => c,
+        Err(e) => {
+            error!(read_failed: "Failed to read {} - {}", input, e);
+            return Err(Exit::InvalidArgs);
+        }
+    };
+
+    serde_json::from_str(&content).map_err(|e| {
+        error!(invalid_transcript: "{} is not a JSON transcript - {}", input, e);
+        Exit::InvalidArgs
+    })
+}
+
+fn search(args: SearchArgs) -> Exit {
+    let query = if args.regex {
+        match Query::regex(&args.query) {
+            Ok(q) => q,
+            Err(e) => {
+                error!(invalid_argument: "Invalid regular expression - {}", e);
+                return Exit::InvalidArgs;
+            }
+        }
+    } else if args.fuzzy {
+        Query::fuzzy(&args.query, args.max_distance)
+    } else {
+        Query::text(&args.query)
+    };
+
+    let result = match fetch_transcript(&args.url, &fetch_defaults(&args.language).build()) {
+        Ok(r) => r,
+        Err(code) => return code,
+    };
+
+    let groups = find_matches(&result.segments, &query, args.context);
+    if groups.is_empty() {
+        notice!("No matches for '{}'", args.query);
+        return Exit::Success;
+    }
+
+    for (n, group) in groups.iter().enumerate() {
+        if n > 0 {
+            println!("--");
+        }
+        for i in group.start..group.end {
+            let seg = &result.segments[i];
+            let marker = if group.matches.contains(&i) { '>' } else { ' ' };
+            println!("{} {} {}", marker, format_timestamp_bracket(seg.start_seconds), seg.text);
+        }
+    }
+
+    Exit::Success
+}
+
+#[cfg(feature = "tui")]
+fn tui(args: TuiArgs) -> Exit {
+    if !io::stdout().is_terminal() {
+        error!(invalid_argument: "tui needs a terminal on standard output");
+        return Exit::InvalidArgs;
+    }
+    let result = match fetch_transcript(&args.url, &fetch_defaults(&args.language).build()) {
+        Ok(r) => r,
+        Err(code) => return code,
+    };
+    if let Err(e) = yt_transcriber::tui::browse(&result, &copy_to_clipboard) {
+        error!(write_failed: "Terminal error - {}", e);
+        return Exit::WriteFailed;
+    }
+    Exit::Success
+}
+
+#[cfg(not(feature = "tui"))]
+fn tui(_args: TuiArgs) -> Exit {
+    error!(unsupported: "This build has no terminal browser (rebuild with --features tui)");
+    Exit::InvalidArgs
+}
+
+fn format_duration(seconds: f64) -> String {
+    let total = seconds.max(0.0).round() as u64;
+    format!("{}:{:02}:{:02}", total / 3600, (total % 3600) / 60, total % 60)
+}
+
+fn stats(args: StatsArgs) -> Exit {
+    let result = match fetch_transcript(&args.url, &fetch_defaults(&args.language).build()) {
+        Ok(r) => r,
+        Err(code) => return code,
+    };
+
+    let mut stats = compute_stats(&result.segments, args.bucket, args.gaps);
+    if args.readability {
+        stats.readability = Some(compute_readability(&result.segments));
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats).unwrap_or_default());
+        return Exit::Success;
+    }
+
+    println!("{:<18} {}", "Segments", stats.segment_count);
+    println!("{:<18} {}", "Words", stats.word_count);
+    println!("{:<18} {}", "Unique words", stats.unique_words);
+    println!("{:<18} {}", "Speaking time", format_duration(stats.speaking_seconds));
+    println!("{:<18} {}", "Total time", format_duration(stats.total_seconds));
+    println!("{:<18} {:.1}", "Words per minute", stats.words_per_minute);
+
+    if let Some(r) = &stats.readability {
+        println!("\nReadability");
+        println!("  {:<26} {:.1}", "Flesch reading ease", r.flesch_reading_ease);
+        println!("  {:<26} {:.1}", "Flesch-Kincaid grade", r.flesch_kincaid_grade);
+        println!("  {:<26} {}", "Sentences", r.sentence_count);
+        println!("  {:<26} {:.1} words", "Average sentence length", r.average_sentence_length);
+        println!("  {:<26} {:.2}", "Syllables per word", r.average_syllables_per_word);
+        println!("  {:<26} {:.3}", "Type-token ratio", r.type_token_ratio);
+        println!("  {:<26} {:.3}", "Moving-average TTR", r.moving_average_ttr);
+    }
+
+    if !stats.wpm_over_time.is_empty() {
+        println!("\nWords per minute over time");
+        for bucket in &stats.wpm_over_time {
+            println!(
+                "  {} - {}  {:>6.1}",
+                format_duration(bucket.start_seconds),
+                format_duration(bucket.end_seconds),
+                bucket.words_per_minute
+            );
+        }
+    }
+
+    if !stats.longest_gaps.is_empty() {
+        println!("\nLongest silences");
+        for gap in &stats.longest_gaps {
+            println!(
+                "  {} - {}  {:>6.1}s",
+                format_duration(gap.start_seconds),
+                format_duration(gap.end_seconds),
+                gap.duration_seconds
+            );
+        }
+    }
+
+    Exit::Success
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn format_system_time(time: std::time::SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn cache(args: CacheArgs) -> Exit {
+    let cache = Cache::new(Cache::default_dir(), None);
+
+    let result = match args.action {
+        CacheAction::Path => {
+            println!("{}", cache.dir().display());
+            return Exit::Success;
+        }
+        CacheAction::List => cache.entries().map(|entries| {
+            for entry in &entries {
+                println!(
+                    "{}  {:>10}  {}",
+                    format_system_time(entry.modified),
+                    format_bytes(entry.size),
+                    entry.key
+                );
+            }
+        }),
+        CacheAction::Stats => cache.entries().map(|entries| {
+            let total: u64 = entries.iter().map(|e| e.size).sum();
+            println!("{:<12} {}", "Directory", cache.dir().display());
+            println!("{:<12} {}", "Entries", entries.len());
+            println!("{:<12} {}", "Total size", format_bytes(total));
+            if let (Some(oldest), Some(newest)) = (entries.first(), entries.last()) {
+                println!("{:<12} {}  {}", "Oldest", format_system_time(oldest.modified), oldest.key);
+                println!("{:<12} {}  {}", "Newest", format_system_time(newest.modified), newest.key);
+            }
+        }),
+        CacheAction::Clear { older_than } => cache.clear(older_than).map(|removed| {
+            let freed: u64 = removed.iter().map(|e| e.size).sum();
+            notice!("Removed {} entries ({})", removed.len(), format_bytes(freed));
+        }),
+    };
+
+    match result {
+        Ok(()) => Exit::Success,
+        Err(e) => {
+            error!(cache_failed: "Failed to access cache at {} - {}", cache.dir().display(), e);
+            Exit::WriteFailed
+        }
+    }
+}
+
+fn print_cps_report(segments: &[TranscriptSegment], max_cps: f64) {
+    let warnings = cps_warnings(segments, max_cps);
+    eprintln!(
+        "{} of {} cues exceed {:.1} characters per second",
+        warnings.len(),
+        segments.len(),
+        max_cps
+    );
+    for warning in &warnings {
+        eprintln!(
+            "  #{:<5} {} --> {}  {:>5.1} cps  {}",
+            warning.index + 1,
+            format_timestamp_srt(warning.start_seconds),
+            format_timestamp_srt(warning.end_seconds),
+            warning.characters_per_second,
+            warning.text
+        );
+    }
+}
+
+fn diff(args: DiffArgs) -> Exit {
+    let old = match load_transcript(&args.old, &args.language) {
+        Ok(r) => r,
+        Err(code) => return code,
+    };
+    let new = match load_transcript(&args.new, &args.language) {
+        Ok(r) => r,
+        Err(code) => return code,
+    };
+
+    let hunks = diff_transcripts(&old.segments, &new.segments);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&hunks).unwrap_or_default());
+        return Exit::Success;
+    }
+
+    if hunks.is_empty() {
+        notice!("Transcripts are identical");
+    }
+
+    for hunk in &hunks {
+        let kind = match hunk.kind {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        };
+        println!(
+            "[{} | {}] {}",
+            format_timestamp_bracket(hunk.old_seconds).trim_matches(['[', ']']),
+            format_timestamp_bracket(hunk.new_seconds).trim_matches(['[', ']']),
+            kind
+        );
+        if !hunk.removed.is_empty() {
+            println!("  - {}", hunk.removed);
+        }
+        if !hunk.added.is_empty() {
+            println!("  + {}", hunk.added);
+        }
+    }
+
+    Exit::Success
+}
+
+fn audit(args: AuditArgs) -> Exit {
+    let manual_options = fetch_defaults(&args.language).track(Track::Manual).build();
+    let manual = match fetch_transcript(&args.url, &manual_options) {
+        Ok(r) => r,
+        Err(code) => return code,
+    };
+    let auto_options = fetch_defaults(&args.language).track(Track::Auto).build();
+    let auto = match fetch_transcript(&args.url, &auto_options) {
+        Ok(r) => r,
+        Err(code) => return code,
+    };
+
+    let report = audit_captions(&manual.segments, &auto.segments, args.threshold);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+        return Exit::Success;
+    }
+
+    println!("{:<18} {}", "Manual words", report.reference_words);
+    println!("{:<18} {}", "Auto words", report.hypothesis_words);
+    println!("{:<18} {}", "Word errors", report.word_errors);
+    println!("{:<18} {:.1}%", "Word error rate", report.word_error_rate * 100.0);
+
+    if !report.mismatches.is_empty() {
+        println!(
+            "\n{} segment(s) above {:.0}% word error rate",
+            report.mismatches.len(),
+            args.threshold * 100.0
+        );
+        for m in &report.mismatches {
+            println!(
+                "\n{} {:.0}%",
+                format_timestamp_bracket(m.start_seconds),
+                m.word_error_rate * 100.0
+            );
+            println!("  manual: {}", m.reference);
+            println!("  auto:   {}", m.hypothesis);
+        }
+    }
+
+    Exit::Success
+}
+
+#[cfg(feature = "index")]
+fn default_index_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("yt-transcriber")
+        .join("index")
+}
+
+#[cfg(feature = "index")]
+fn collect_json_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_json_files(&path, files);
+            } else if path.extension().map(|e| e == "json").unwrap_or(false) {
+                files.push(path);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "index")]
+fn index(args: IndexArgs) -> Exit {
+    let mut files = Vec::new();
+    for path in &args.paths {
+        if path.is_dir() {
+            collect_json_files(path, &mut files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    let mut results = Vec::new();
+    for file in &files {
+        let parsed = fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str::<TranscriptResult>(&c).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(r) => results.push(r),
+            Err(e) => warning!("Skipping {} - {}", file.display(), e),
+        }
+    }
+
+    if results.is_empty() {
+        error!(no_input: "No JSON transcripts found to index");
+        return Exit::InvalidArgs;
+    }
+
+    let dir = args.index_dir.unwrap_or_else(default_index_dir);
+    let added = TranscriptIndex::open(&dir).and_then(|index| index.add(&results));
+    if let Err(e) = added {
+        error!(index_failed: "Failed to update index - {}", e);
+        return Exit::WriteFailed;
+    }
+
+    notice!("Indexed {} transcript(s) into {}", results.len(), dir.display());
+    Exit::Success
+}
+
+#[cfg(feature = "index")]
+fn query(args: QueryArgs) -> Exit {
+    let dir = args.index_dir.unwrap_or_else(default_index_dir);
+    let hits = match TranscriptIndex::open(&dir).and_then(|index| index.search(&args.query, args.limit)) {
+        Ok(h) => h,
+        Err(e) => {
+            error!(index_failed: "Index search failed - {}", e);
+            return Exit::InvalidArgs;
+        }
+    };
+
+    if hits.is_empty() {
+        notice!("No matches for '{}'", args.query);
+    }
+
+    for hit in hits {
+        println!("{} {}", format_timestamp_bracket(hit.start_seconds), hit.text);
+        println!(
+            "        {} ({}) {}",
+            hit.title.as_deref().unwrap_or(&hit.video_id),
+            hit.language,
+            youtube_link(&hit.video_id, hit.start_seconds)
+        );
+    }
+
+    Exit::Success
+}
+
+#[cfg(not(feature = "index"))]
+fn index(_args: IndexArgs) -> Exit {
+    error!(unsupported: "This build has no index support (rebuild with --features index)");
+    Exit::InvalidArgs
+}
+
+#[cfg(not(feature = "index"))]
+fn query(_args: QueryArgs) -> Exit {
+    index(IndexArgs { paths: Vec::new(), index_dir: None })
+}
+
+fn output_extension(args: &TranscribeArgs) -> &'static str {
+    if args.template.is_some() {
+        return "txt";
+    }
+    match args.format {
+        OutputFormat::Txt => "txt",
+        OutputFormat::Srt => "srt",
+        OutputFormat::Vtt => "vtt",
+        OutputFormat::Ass | OutputFormat::AssKaraoke => "ass",
+        OutputFormat::Lrc => "lrc",
+        OutputFormat::Json => "json",
+        OutputFormat::Jsonl => "jsonl",
+        OutputFormat::Csv => "csv",
+        OutputFormat::MdNotes => "md",
+        OutputFormat::Parquet => "parquet",
+        OutputFormat::Arrow => "arrow",
+        OutputFormat::Msgpack => "msgpack",
+        OutputFormat::Proto => "pb",
+    }
+}
+
+/// Video IDs listed in an RSS or Atom feed.
+fn list_feed(url: &str) -> Result<Vec<String>, Exit> {
+    let mut request = http::agent().get(url);
+    if let Some(cookie) = COOKIES.get().and_then(|cookies| cookies::header_for(cookies, url)) {
+        request = request.set("Cookie", &cookie);
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(code, _)) => {
+            error!(network_error: "Feed request failed with HTTP {}", code);
+            return Err(Exit::Unavailable);
+        }
+        Err(e) => {
+            error!(network_error: "Failed to fetch feed - {}", e);
+            return Err(Exit::Network);
+        }
+    };
+
+    match response.into_string() {
+        Ok(xml) => Ok(feed_video_ids(&xml)),
+        Err(e) => {
+            error!(network_error: "Failed to read feed - {}", e);
+            Err(Exit::Network)
+        }
+    }
+}
+
+/// Video IDs of a playlist, channel or feed, in listing order.
+fn list_collection(url: &str) -> Result<Vec<String>, Exit> {
+    if is_feed_url(url) {
+        return list_feed(url);
+    }
+
+    let _progress = Progress::spinner(format!("Listing videos in {}", url));
+    let output = run_yt_dlp(&[
+        "--flat-playlist",
+        "--no-warnings",
+        "--print",
+        "id",
+        &collection_listing_url(url),
+    ]);
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        Ok(o) => return Err(yt_dlp_failure(&String::from_utf8_lossy(&o.stderr))),
+        Err(e) => return Err(report_error(&e)),
+    };
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(extract_video_id)
+        .collect())
+}
+
+/// Output target for a playlist or channel: `sqlite:<db>`, an `s3://bucket/prefix`, a path
+/// template, or a directory that is created if needed (--output-dir or the current directory by
+/// default).
+fn prepare_collection_output(args: &TranscribeArgs) -> Result<String, Exit> {
+    let target = args
+        .output
+        .clone()
+        .or_else(|| args.output_dir.clone())
+        .unwrap_or_else(|| ".".to_string());
+    if args.append {
+        return Ok(target);
+    }
+    if !target.starts_with("sqlite:") && !is_s3_url(&target) && !output_path::is_template(&target) {
+        if let Err(e) = fs::create_dir_all(&target) {
+            error!(write_failed: "Failed to create output directory - {}", e);
+            return Err(Exit::WriteFailed);
+        }
+    }
+    Ok(target)
+}
+
+/// Whether `output` is a local file that already exists. Databases, S3 keys and templates are
+/// never known to exist before the transcript is fetched.
+fn output_exists(output: &str) -> bool {
+    !output.starts_with("sqlite:")
+        && !is_s3_url(output)
+        && !output_path::is_template(output)
+        && Path::new(output).exists()
+}
+
+/// Every video of a collection is written to its own file in the output directory, or upserted
+/// into the same database. A path template is filled in per video once its metadata is known.
+fn collection_output(args: &TranscribeArgs, target: &str, video_id: &str) -> String {
+    if target.starts_with("sqlite:") || output_path::is_template(target) || args.append {
+        return target.to_string();
+    }
+    let name = format!("{}.{}", video_id, output_extension(args));
+    if is_s3_url(target) {
+        return join_key(target, &name);
+    }
+    Path::new(target)
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn transcribe(cli: Cli) -> Exit {
+    let args = &cli.options;
+    if let Err(code) = check_format(args) {
+        return code;
+    }
+    let mut archive = match cli.archive.as_deref().map(Archive::open).transpose() {
+        Ok(archive) => archive,
+        Err(e) => {
+            error!(read_failed: "Failed to read archive - {}", e);
+            return Exit::WriteFailed;
+        }
+    };
+    if (args.stream || args.live) && (cli.urls.len() > 1 || cli.urls.first().is_some_and(|url| is_collection_url(url))) {
+        let flag = if args.live { "--live" } else { "--stream" };
+        error!(invalid_argument: "{} works with a single video or subtitle file", flag);
+        return Exit::InvalidArgs;
+    }
+    if cli.urls.len() > 1 {
+        return transcribe_inputs(&cli, archive.as_mut());
+    }
+    let input = if cli.stdin_vtt { STDIN_INPUT.to_string() } else { cli.urls.first().cloned().unwrap_or_default() };
+    if args.stream {
+        return stream_transcript(args, &input);
+    }
+    if args.live {
+        return follow_live(args, &input);
+    }
+
+    if !is_collection_url(&input) {
+        let output = match (&args.output, video_key(&input)) {
+            (None, Some(video_id)) if args.output_dir.is_some() => match prepare_collection_output(args) {
+                Ok(target) => Some(collection_output(args, &target, &video_id)),
+                Err(code) => return code,
+            },
+            _ => args.output.clone(),
+        };
+        return transcribe_archived(args, &input, output.as_deref(), archive.as_mut());
+    }
+
+    let target = match prepare_collection_output(args) {
+        Ok(target) => target,
+        Err(code) => return code,
+    };
+    let state_path = cli.state_file.clone().unwrap_or_else(|| match target.strip_prefix("sqlite:") {
+        Some(db) => PathBuf::from(format!("{}.state.json", db)),
+        None if args.append => PathBuf::from(format!("{}.state.json", target)),
+        None if is_s3_url(&target) || output_path::is_template(&target) => PathBuf::from(".yt-transcriber-state.json"),
+        None => Path::new(&target).join(".yt-transcriber-state.json"),
+    });
+    let saved = if cli.resume {
+        match BatchState::load(&state_path) {
+            Ok(state) => state.filter(|s| s.source == input),
+            Err(e) => {
+                error!(read_failed: "Failed to read state file {} - {}", state_path.display(), e);
+                return Exit::WriteFailed;
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut state = match saved {
+        Some(state) => {
+            notice!(
+                "Resuming: {} of {} videos already done",
+                state.completed.len(),
+                state.video_ids.len()
+            );
+            state
+        }
+        None => match list_collection(&input) {
+            Ok(ids) if ids.is_empty() => {
+                error!(no_videos: "No videos found in {}", input);
+                return Exit::Unavailable;
+            }
+            Ok(ids) => BatchState::new(&input, ids),
+            Err(code) => return code,
+        },
+    };
+
+    let started = Instant::now();
+    let mut summary = BatchSummary::new(&input);
+    let mut status = Exit::Success;
+    let mut succeeded = !state.completed.is_empty();
+    transcribe_batch(args, state.pending(), &target, archive.as_mut(), cli.jobs, |outcome| {
+        if outcome.code == Exit::Success {
+            state.mark_completed(&outcome.video_id);
+            succeeded = true;
+        } else {
+            state.mark_failed(&outcome.video_id);
+            status = outcome.code;
+        }
+        add_to_summary(&mut summary, outcome);
+        if let Err(e) = state.save(&state_path) {
+            warning!("Failed to save state file {} - {}", state_path.display(), e);
+        }
+    });
+    if let Err(code) = finish_summary(&cli, &mut summary, started) {
+        return code;
+    }
+
+    if state.failed.is_empty() {
+        let _ = fs::remove_file(&state_path);
+    } else {
+        notice!(
+            "{} videos failed; rerun with --resume to retry them",
+            state.failed.len()
+        );
+    }
+    batch_status(status, succeeded)
+}
+
+/// Prints a transcript a segment at a time (--stream). Standard input and local WebVTT and SRT
+/// files are parsed while they are read, applying the options that work on single segments; other inputs
+/// are fetched whole and then printed line by line.
+fn stream_transcript(args: &TranscribeArgs, input: &str) -> Exit {
+    if let Err(code) = check_line_format(args, "--stream") {
+        return code;
+    }
+    let language = args.language.split(',').next().unwrap_or_default().trim();
+    let readable = |input: &str| {
+        is_subtitle_file(input) && matches!(InputFormat::from_path(Path::new(input)), Some(InputFormat::Vtt | InputFormat::Srt))
+    };
+    if input != STDIN_INPUT && !readable(input) {
+        let result = match process_transcript(args, input) {
+            Ok(result) => result,
+            Err(code) => return code,
+        };
+        let mut printer = SegmentPrinter::new(args, &result.video_id, &result.language);
+        for seg in &result.segments {
+            if let Some(code) = printer.print(seg) {
+                return code;
+            }
+        }
+        return Exit::Success;
+    }
+
+    let (name, reader): (String, Box<dyn BufRead>) = if input == STDIN_INPUT {
+        ("stdin".to_string(), Box::new(io::stdin().lock()))
+    } else {
+        match fs::File::open(input) {
+            Ok(file) => (
+                Path::new(input).file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+                Box::new(io::BufReader::new(file)),
+            ),
+            Err(e) => {
+                error!(read_failed: "Failed to read {} - {}", input, e);
+                return Exit::InvalidArgs;
+            }
+        }
+    };
+    let patterns = match args.strip_annotations.then(|| annotation_patterns(args)).transpose() {
+        Ok(patterns) => patterns,
+        Err(code) => return code,
+    };
+    let censor = match censor_filter(args) {
+        Ok(censor) => censor,
+        Err(code) => return code,
+    };
+
+    let mut printer = SegmentPrinter::new(args, &name, language);
+    let mut parser = VttReader::new();
+    let srt_lines = srt::SrtLines::new();
+    let mut is_srt = None;
+    let mut dedup = RollingDedup::default();
+    let mut auto_generated = false;
+    let mut printed = 0;
+    let mut lines = reader.lines();
+    let mut at_end = false;
+    while !at_end {
+        let parsed = match lines.next() {
+            Some(Ok(line)) => {
+                // Only speech recognition captions have per-word timing tags, and roll.
+                auto_generated |= line.contains("<c>");
+                if is_srt.is_none() && !line.trim().is_empty() {
+                    is_srt = Some(!srt::is_vtt(&line));
+                }
+                if is_srt == Some(true) {
+                    parser.push_line(&srt_lines.to_vtt(&line))
+                } else {
+                    parser.push_line(&line)
+                }
+            }
+            Some(Err(e)) => {
+                error!(read_failed: "Failed to read {} - {}", input, e);
+                return Exit::InvalidArgs;
+            }
+            None => {
+                at_end = true;
+                parser.finish()
+            }
+        };
+        let Some(mut seg) = parsed else {
+            continue;
+        };
+
+        if !args.raw_entities {
+            seg.text = decode_entities(&seg.text);
+        }
+        let mut segments = vec![seg];
+        if auto_generated && !args.no_dedup {
+            segments = segments.into_iter().filter_map(|seg| dedup.next(seg)).collect();
+        }
+        if let Some(patterns) = &patterns {
+            segments = strip_annotations(segments, patterns, args.drop_empty);
+        }
+        if args.from.is_some() || args.to.is_some() {
+            segments = clip_range(segments, args.from, args.to);
+        }
+        if args.offset.is_some() || args.stretch.is_some() {
+            segments = retime(segments, args.offset.unwrap_or(0.0), args.stretch.unwrap_or(1.0));
+        }
+        if let Some(censor) = &censor {
+            censor.apply_segments(&mut segments);
+        }
+        redact(&mut segments, &args.redact);
+        for mut seg in segments {
+            seg.index = printed;
+            printed += 1;
+            if let Some(code) = printer.print(&seg) {
+                return code;
+            }
+        }
+    }
+
+    if printed == 0 {
+        return report_error(&TranscriberError::EmptyTranscript);
+    }
+    Exit::Success
+}
+
+/// --stream and --live print formats made of independent lines.
+fn check_line_format(args: &TranscribeArgs, flag: &str) -> Result<(), Exit> {
+    if args.template.is_some() || !matches!(args.format, OutputFormat::Txt | OutputFormat::Jsonl) {
+        error!(invalid_argument: "{} prints the txt or jsonl format", flag);
+        return Err(Exit::InvalidArgs);
+    }
+    Ok(())
+}
+
+/// Follows a live broadcast (--live), fetching its captions every --live-interval and printing
+/// the new ones, until the broadcast has ended and its last captions are printed. The newest
+/// caption of a fetch is held back while the broadcast runs, as it may still grow. Failed
+/// fetches are retried with growing pauses up to --retries times in a row.
+fn follow_live(args: &TranscribeArgs, input: &str) -> Exit {
+    if let Err(code) = check_line_format(args, "--live") {
+        return code;
+    }
+    let Some(video_id) = extract_video_id(input) else {
+        error!(invalid_url: "--live needs a YouTube video URL or ID");
+        return Exit::InvalidUrl;
+    };
+    let language = args.language.split(',').next().unwrap_or_default().trim();
+    let options = fetch_options(args, language).cache(None).build();
+    let _video = LogContext::enter("video_id", &video_id);
+    let censor = match censor_filter(args) {
+        Ok(censor) => censor,
+        Err(code) => return code,
+    };
+
+    let mut printer = SegmentPrinter::new(args, &video_id, language);
+    let mut printed_until = f64::NEG_INFINITY;
+    let mut printed = 0;
+    let mut failures = 0;
+    loop {
+        let subtitles = match options.download(&video_id) {
+            Ok(subtitles) => subtitles,
+            Err(TranscriberError::NoCaptions { .. }) => {
+                notice!("No captions yet, checking again in {:.0}s", args.live_interval.as_secs_f64());
+                thread::sleep(args.live_interval);
+                continue;
+            }
+            Err(e) => {
+                let code = report_error(&e);
+                if failures >= options.retries() {
+                    return code;
+                }
+                let delay = backoff(failures);
+                failures += 1;
+                notice!("Reconnecting in {:.1}s", delay.as_secs_f64());
+                thread::sleep(delay);
+                continue;
+            }
+        };
+        failures = 0;
+        let live = subtitles.live;
+        let mut segments = match options.parse(video_id.clone(), subtitles) {
+            Ok(result) => result.segments,
+            Err(TranscriberError::EmptyTranscript) => Vec::new(),
+            Err(e) => return report_error(&e),
+        };
+        if live {
+            segments.pop();
+        }
+        if let Some(censor) = &censor {
+            censor.apply_segments(&mut segments);
+        }
+        redact(&mut segments, &args.redact);
+
+        let since = printed_until;
+        for mut seg in segments.into_iter().filter(|seg| seg.start_seconds > since) {
+            printed_until = seg.start_seconds;
+            seg.index = printed;
+            printed += 1;
+            if let Some(code) = printer.print(&seg) {
+                return code;
+            }
+        }
+        if !live {
+            notice!("The broadcast has ended");
+            return Exit::Success;
+        }
+        thread::sleep(args.live_interval);
+    }
+}
+
+/// Writes segments to stdout as they come, flushing each line.
+struct SegmentPrinter<'a> {
+    args: &'a TranscribeArgs,
+    video_id: &'a str,
+    language: &'a str,
+    out: io::StdoutLock<'static>,
+    first: bool,
+    color: bool,
+}
+
+impl<'a> SegmentPrinter<'a> {
+    fn new(args: &'a TranscribeArgs, video_id: &'a str, language: &'a str) -> Self {
+        let color = !matches!(args.format, OutputFormat::Jsonl) && use_color(args);
+        SegmentPrinter { args, video_id, language, out: io::stdout().lock(), first: true, color }
+    }
+
+    /// Prints one segment. Returns the exit code to stop with: success once the reader has gone
+    /// away (`| head`), or a write failure.
+    fn print(&mut self, seg: &TranscriptSegment) -> Option<Exit> {
+        let mut line = match self.args.format {
+            OutputFormat::Jsonl => jsonl_line(self.video_id, self.language, seg),
+            _ if self.args.link_timestamps && !self.args.no_timestamps => {
+                format!("{} {}", linked_timestamp(self.video_id, seg.start_seconds), seg.text)
+            }
+            _ => txt_line(seg, !self.args.no_timestamps),
+        };
+        if self.color {
+            line = colorize_txt(&line);
+        }
+        line.push('\n');
+        let bytes = encode_text(&line, self.args.encoding, std::mem::take(&mut self.first));
+        match self.out.write_all(&bytes).and_then(|_| self.out.flush()) {
+            Ok(()) => None,
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Some(Exit::Success),
+            Err(e) => {
+                error!(write_failed: "Failed to write output - {}", e);
+                Some(Exit::WriteFailed)
+            }
+        }
+    }
+}
+
+/// Transcribes several inputs given on the command line, each video to its own file as for a
+/// playlist. Playlists and channels among them are expanded into their videos.
+fn transcribe_inputs(cli: &Cli, archive: Option<&mut Archive>) -> Exit {
+    let args = &cli.options;
+    let target = match prepare_collection_output(args) {
+        Ok(target) => target,
+        Err(code) => return code,
+    };
+
+    let started = Instant::now();
+    let mut summary = BatchSummary::new(&cli.urls.join(" "));
+    let mut status = Exit::Success;
+    let mut succeeded = false;
+    let mut inputs = Vec::new();
+    for input in &cli.urls {
+        if !is_collection_url(input) {
+            inputs.push(input.clone());
+            continue;
+        }
+        match list_collection(input) {
+            Ok(ids) if ids.is_empty() => warning!("No videos found in {}", input),
+            Ok(ids) => inputs.extend(ids),
+            Err(code) => status = code,
+        }
+    }
+
+    transcribe_batch(args, inputs, &target, archive, cli.jobs, |outcome| {
+        if outcome.code == Exit::Success {
+            succeeded = true;
+        } else {
+            status = outcome.code;
+        }
+        add_to_summary(&mut summary, outcome);
+    });
+    if let Err(code) = finish_summary(cli, &mut summary, started) {
+        return code;
+    }
+    batch_status(status, succeeded)
+}
+
+/// What became of one video of a batch.
+struct VideoOutcome {
+    video_id: String,
+    code: Exit,
+    /// Skipped because the archive lists it
+    archived: bool,
+    /// Served from the subtitle cache
+    cached: bool,
+    /// Skipped because its output file exists (--skip-existing)
+    existing: bool,
+    /// Identifier and message of the failure
+    error: Option<(String, String)>,
+}
+
+/// Transcribes each input into its own file under `target` on `jobs` threads, skipping and
+/// recording videos in `archive`. `done` is called on this thread as each video finishes.
+fn transcribe_batch(
+    args: &TranscribeArgs,
+    inputs: Vec<String>,
+    target: &str,
+    archive: Option<&mut Archive>,
+    jobs: usize,
+    mut done: impl FnMut(VideoOutcome),
+) {
+    let progress = Progress::batch(inputs.len());
+    let jobs = jobs.clamp(1, inputs.len().max(1));
+    let queue = Mutex::new(inputs.into_iter());
+    let archive = Mutex::new(archive);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (queue, archive) = (&queue, &archive);
+            scope.spawn(move || {
+                let mut requested = false;
+                while let Some(input) = queue.lock().ok().and_then(|mut queue| queue.next()) {
+                    let youtube_id = extract_video_id(&input);
+                    let archived = youtube_id.as_ref().is_some_and(|id| {
+                        archive.lock().is_ok_and(|archive| archive.as_ref().is_some_and(|archive| archive.contains(id)))
+                    });
+                    if requested && !archived {
+                        pause(args.sleep.as_ref());
+                    }
+                    LAST_ERROR.with(|last| last.borrow_mut().take());
+                    CACHE_HIT.with(|hit| hit.set(false));
+                    OUTPUT_EXISTED.with(|existed| existed.set(false));
+
+                    let video_id = video_key(&input)
+                        .or_else(|| Path::new(&input).file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+                        .unwrap_or_else(|| input.clone());
+                    let mut code = Exit::Success;
+                    let mut fetched = false;
+                    if archived {
+                        notice!("Skipping {} - already in archive", video_id);
+                    } else {
+                        let output = collection_output(args, target, &video_id);
+                        if args.skip_existing && output_exists(&output) {
+                            OUTPUT_EXISTED.with(|existed| existed.set(true));
+                            notice!("Skipping {} - {} already exists", video_id, output);
+                        } else {
+                            code = transcribe_video(args, &input, Some(&output));
+                            fetched = true;
+                        }
+                    }
+                    if let (Exit::Success, false, Some(id)) = (code, archived, &youtube_id) {
+                        if let Ok(mut archive) = archive.lock() {
+                            if let Some(Err(e)) = archive.as_mut().map(|archive| archive.record(id)) {
+                                error!(write_failed: "Failed to update archive - {}", e);
+                                code = Exit::WriteFailed;
+                            }
+                        }
+                    }
+
+                    let cached = CACHE_HIT.with(Cell::get);
+                    let existing = OUTPUT_EXISTED.with(Cell::get);
+                    requested = fetched && !cached;
+                    let error = (code != Exit::Success).then(|| {
+                        LAST_ERROR
+                            .with(|last| last.borrow_mut().take())
+                            .unwrap_or_else(|| (code.name().to_string(), code.description().to_string()))
+                    });
+                    let _ = sender.send(VideoOutcome { video_id, code, archived, cached, existing, error });
+                }
+            });
+        }
+        drop(sender);
+        for outcome in receiver {
+            progress.advance();
+            done(outcome);
+        }
+    });
+}
+
+/// Counts a finished video in the run's summary.
+fn add_to_summary(summary: &mut BatchSummary, outcome: VideoOutcome) {
+    match outcome.error {
+        Some((error, message)) => summary.failed.push(FailedVideo {
+            video_id: outcome.video_id,
+            error,
+            message,
+        }),
+        None if outcome.archived => summary.archived += 1,
+        None if outcome.existing => summary.existing += 1,
+        None => {
+            summary.processed += 1;
+            summary.cached += outcome.cached as usize;
+        }
+    }
+}
+
+/// Reports the summary of a run and writes it to --summary.
+fn finish_summary(cli: &Cli, summary: &mut BatchSummary, started: Instant) -> Result<(), Exit> {
+    summary.duration_seconds = started.elapsed().as_secs_f64();
+    report_summary(summary);
+    if let Some(path) = &cli.summary {
+        if let Err(e) = summary.write(path) {
+            error!(write_failed: "Failed to write summary {} - {}", path.display(), e);
+            return Err(Exit::WriteFailed);
+        }
+    }
+    Ok(())
+}
+
+/// Prints the totals of a playlist or channel run and the reason each failed video failed.
+fn report_summary(summary: &BatchSummary) {
+    if JSON_LOGS.load(Ordering::Relaxed) {
+        if VERBOSITY.load(Ordering::Relaxed) >= 0 {
+            let fields = match serde_json::to_value(summary) {
+                Ok(serde_json::Value::Object(map)) => map,
+                _ => serde_json::Map::new(),
+            };
+            log_event("info", "Batch finished", fields);
+        }
+        return;
+    }
+    notice!(
+        "Done in {:.1}s: {} processed ({} from cache), {} skipped (archived), {} skipped (existing), {} failed",
+        summary.duration_seconds,
+        summary.processed,
+        summary.cached,
+        summary.archived,
+        summary.existing,
+        summary.failed.len()
+    );
+    for failed in &summary.failed {
+        notice!("  {}: {} - {}", failed.video_id, failed.error, failed.message);
+    }
+}
+
+/// A run where some videos failed and others succeeded exits with `PartialFailure`; when every
+/// video failed the last failure is reported as is.
+fn batch_status(last_failure: Exit, succeeded: bool) -> Exit {
+    if last_failure != Exit::Success && succeeded {
+        Exit::PartialFailure
+    } else {
+        last_failure
+    }
+}
+
+fn watch(args: WatchArgs) -> Exit {
+    if !is_collection_url(&args.url) {
+        error!(invalid_url: "watch needs a channel or playlist URL");
+        return Exit::InvalidUrl;
+    }
+
+    let options = &args.options;
+    if let Err(code) = check_format(options) {
+        return code;
+    }
+    let target = match prepare_collection_output(options) {
+        Ok(target) => target,
+        Err(code) => return code,
+    };
+    let archive_path = args.archive.clone().unwrap_or_else(|| match target.strip_prefix("sqlite:") {
+        Some(db) => PathBuf::from(format!("{}.archive.txt", db)),
+        None if options.append => PathBuf::from(format!("{}.archive.txt", target)),
+        None if is_s3_url(&target) || output_path::is_template(&target) => PathBuf::from(".yt-transcriber-archive.txt"),
+        None => Path::new(&target).join(".yt-transcriber-archive.txt"),
+    });
+    let mut archive = match Archive::open(&archive_path) {
+        Ok(archive) => archive,
+        Err(e) => {
+            error!(read_failed: "Failed to read archive - {}", e);
+            return Exit::WriteFailed;
+        }
+    };
+
+    let mut first_check = true;
+    loop {
+        let checked_at = chrono::Local::now().format("%Y-%m-%d %H:%M");
+        let ids = match list_collection(&args.url) {
+            Ok(ids) => ids,
+            Err(code) if args.once => return code,
+            Err(_) => Vec::new(),
+        };
+
+        // Listings are newest first; transcribe in upload order.
+        let new: Vec<String> = ids.into_iter().rev().filter(|id| !archive.contains(id)).collect();
+        notice!("[{}] {} new videos", checked_at, new.len());
+
+        let mut status = Exit::Success;
+        let mut succeeded = false;
+        let progress = Progress::batch(if first_check && args.new_only { 0 } else { new.len() });
+        let mut requested = false;
+        for video_id in &new {
+            if first_check && args.new_only {
+                if let Err(e) = archive.record(video_id) {
+                    error!(write_failed: "Failed to update archive - {}", e);
+                    return Exit::WriteFailed;
+                }
+                continue;
+            }
+            if requested {
+                pause(options.sleep.as_ref());
+            }
+            let output = collection_output(options, &target, video_id);
+            CACHE_HIT.with(|hit| hit.set(false));
+            let code = transcribe_archived(options, video_id, Some(&output), Some(&mut archive));
+            progress.advance();
+            requested = !CACHE_HIT.with(Cell::get);
+            if code == Exit::Success {
+                succeeded = true;
+            } else {
+                status = code;
+            }
+        }
+        drop(progress);
+
+        if args.once {
+            return batch_status(status, succeeded);
+        }
+        first_check = false;
+        std::thread::sleep(args.interval);
+    }
+}
+
+fn serve(args: ServeArgs) -> Exit {
+    if let Err(e) = ensure_yt_dlp() {
+        return report_error(&e);
+    }
+
+    let server = match tiny_http::Server::http((args.host.as_str(), args.port)) {
+        Ok(server) => server,
+        Err(e) => {
+            error!(listen_failed: "Failed to listen on {}:{} - {}", args.host, args.port, e);
+            return Exit::InvalidArgs;
+        }
+    };
+    notice!("Listening on http://{}:{}", args.host, args.port);
+
+    let (queue, pending) = mpsc::sync_channel::<tiny_http::Request>(args.queue);
+    let pending = Arc::new(Mutex::new(pending));
+    let options = Arc::new(args.options);
+    for _ in 0..args.workers.max(1) {
+        let pending = Arc::clone(&pending);
+        let options = Arc::clone(&options);
+        thread::spawn(move || loop {
+            let next = match pending.lock() {
+                Ok(pending) => pending.recv(),
+                Err(_) => return,
+            };
+            match next {
+                Ok(request) => handle_request(&options, request),
+                Err(_) => return,
+            }
+        });
+    }
+
+    for request in server.incoming_requests() {
+        if let Err(TrySendError::Full(request)) = queue.try_send(request) {
+            let response = tiny_http::Response::from_string(api_error("server busy, try again later"))
+                .with_status_code(503)
+                .with_header(header("Content-Type", "application/json"))
+                .with_header(header("Retry-After", "5"));
+            let _ = request.respond(response);
+        }
+    }
+    Exit::Success
+}
+
+/// HTTP status and message for a failed transcription, by exit code.
+fn failure_status(code: Exit) -> (u16, &'static str) {
+    match code {
+        Exit::Unavailable | Exit::NoCaptions => (404, "transcript unavailable"),
+        Exit::InvalidUrl => (400, "invalid video ID"),
+        Exit::Network => (502, "failed to reach YouTube"),
+        _ => (500, "transcript extraction failed"),
+    }
+}
+
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+fn api_error(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Answers one API request and logs it to stderr.
+fn handle_request(defaults: &TranscribeArgs, request: tiny_http::Request) {
+    let started = Instant::now();
+    let (status, content_type, body) = match api_response(defaults, &request) {
+        Ok((content_type, body)) => (200, content_type, body),
+        Err((status, message)) => (status, "application/json", api_error(&message).into_bytes()),
+    };
+    let summary = format!("{} {} {}", request.method(), request.url(), status);
+    if JSON_LOGS.load(Ordering::Relaxed) {
+        let fields = serde_json::json!({
+            "method": request.method().to_string(),
+            "path": request.url(),
+            "status": status,
+        });
+        log_stage("request", started, summary, fields);
+    } else {
+        notice!("{}", summary);
+    }
+
+    let response = tiny_http::Response::from_data(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", content_type));
+    if let Err(e) = request.respond(response) {
+        warning!("Failed to send response - {}", e);
+    }
+}
+
+/// Content type and body for `GET /transcript/<id>`, or an HTTP status and error message.
+fn api_response(
+    defaults: &TranscribeArgs,
+    request: &tiny_http::Request,
+) -> Result<(&'static str, Vec<u8>), (u16, String)> {
+    if *request.method() != tiny_http::Method::Get {
+        return Err((405, "only GET is supported".to_string()));
+    }
+    let url = url::Url::parse(&format!("http://localhost{}", request.url()))
+        .map_err(|_| (400, "invalid request URL".to_string()))?;
+    let segments: Vec<&str> = url.path_segments().map(|s| s.collect()).unwrap_or_default();
+    let video_id = match segments.as_slice() {
+        ["health"] => return Ok(("text/plain; charset=utf-8", b"ok".to_vec())),
+        ["transcript", id] => extract_video_id(id).ok_or((400, format!("invalid video ID '{}'", id)))?,
+        _ => return Err((404, "not found".to_string())),
+    };
+
+    let mut args = defaults.clone();
+    // Responses are declared as UTF-8.
+    args.encoding = TextEncoding::Utf8;
+    for (name, value) in url.query_pairs() {
+        match name.as_ref() {
+            "lang" => args.language = value.into_owned(),
+            "format" => {
+                args.format = OutputFormat::from_str(&value, true)
+                    .map_err(|_| (400, format!("unknown format '{}'", value)))?;
+            }
+            _ => {}
+        }
+    }
+
+    let failed = |code| {
+        let (status, message) = failure_status(code);
+        (status, message.to_string())
+    };
+    let _video = LogContext::enter("video_id", &video_id);
+    let result = process_transcript(&args, &video_id).map_err(failed)?;
+    let body = render_output(&args, &result).map_err(failed)?;
+    Ok((content_type(output_extension(&args)), body))
+}
+
+fn mcp(args: McpArgs) -> Exit {
+    if let Err(e) = ensure_yt_dlp() {
+        return report_error(&e);
+    }
+
+    let defaults = args.options;
+    let outcome = mcp::serve(io::stdin().lock(), io::stdout(), |request| {
+        let mut args = defaults.clone();
+        if let Some(language) = request.language {
+            args.language = language;
+        }
+        if let Some(format) = request.format {
+            if !TOOL_FORMATS.contains(&format.as_str()) {
+                return Err(format!("Unsupported format '{}'", format));
+            }
+            args.format = OutputFormat::from_str(&format, true)?;
+        }
+
+        let failed = |code| format!("Failed to get transcript for {}: {}", request.url, failure_status(code).1);
+        let _video = LogContext::enter("video_id", &extract_video_id(&request.url).unwrap_or_else(|| request.url.clone()));
+        let result = process_transcript(&args, &request.url).map_err(failed)?;
+        render_text(&args, &result).map_err(failed)
+    });
+
+    match outcome {
+        Ok(()) => Exit::Success,
+        Err(e) => {
+            error!(write_failed: "Failed to write output - {}", e);
+            Exit::WriteFailed
+        }
+    }
+}
+
+/// Transcribes one video unless the archive already lists it, and records it once it succeeds.
+fn transcribe_archived(
+    args: &TranscribeArgs,
+    input: &str,
+    output: Option<&str>,
+    archive: Option<&mut Archive>,
+) -> Exit {
+    let video_id = extract_video_id(input);
+    let archive = archive.zip(video_id.as_deref());
+    if let Some((archive, id)) = &archive {
+        if archive.contains(id) {
+            notice!("Skipping {} - already in archive", id);
+            return Exit::Success;
+        }
+    }
 
-    /// Output format
-    #[arg(short, long, default_value = "txt", value_enum)]
-    format: OutputFormat,
+    let code = transcribe_video(args, input, output);
+    if code == Exit::Success {
+        if let Some((archive, id)) = archive {
+            if let Err(e) = archive.record(id) {
+                error!(write_failed: "Failed to update archive - {}", e);
+                return Exit::WriteFailed;
+            }
+        }
+    }
+    code
+}
 
-    /// Output file path (default: stdout)
-    #[arg(short, long)]
-    output: Option<String>,
+/// Sends the webhook for a finished video. Failures are reported but do not fail the run.
+fn notify_webhook(args: &TranscribeArgs, url: &str, result: &TranscriptResult, output: Option<&str>) {
+    let body = match args.webhook_payload {
+        WebhookPayload::Full => format_json(result),
+        WebhookPayload::Summary => {
+            let format = args.format.to_possible_value().expect("no skipped formats");
+            let summary = WebhookSummary::new(result, format.get_name(), output);
+            serde_json::to_string(&summary).unwrap_or_default()
+        }
+    };
+    let started = Instant::now();
+    match post_json(url, &body) {
+        Ok(()) => log_stage("webhook", started, format!("Notified {}", url), serde_json::json!({ "url": url })),
+        Err(e) => warning!("Webhook for {} failed - {}", result.video_id, e),
+    }
+}
 
-    /// Language code for transcript
-    #[arg(short, long, default_value = "en")]
-    language: String,
+fn transcribe_video(args: &TranscribeArgs, input: &str, output: Option<&str>) -> Exit {
+    let _video = LogContext::enter("video_id", &video_key(input).unwrap_or_else(|| input.to_string()));
+    let result = match process_transcript(args, input) {
+        Ok(result) => result,
+        Err(code) => return code,
+    };
 
-    /// Exclude timestamps from TXT output
-    #[arg(long)]
-    no_timestamps: bool,
+    let output = match output.map(|output| resolve_output(args, &result, output)).transpose() {
+        Ok(output) => output,
+        Err(code) => return code,
+    };
+    let output = output.as_deref();
+
+    let started = Instant::now();
+    let code = write_transcript(args, &result, output);
+    if code == Exit::Success {
+        log_stage(
+            "write",
+            started,
+            format!("Wrote {}", output.unwrap_or("stdout")),
+            serde_json::json!({ "output": output }),
+        );
+        if let Some(url) = &args.webhook {
+            notify_webhook(args, url, &result, output);
+        }
+    }
+    code
 }
 
-#[derive(Clone, ValueEnum)]
-enum OutputFormat {
-    Txt,
-    Srt,
-    Json,
+/// Fetches a transcript and applies every post-processing option.
+fn process_transcript(args: &TranscribeArgs, input: &str) -> Result<TranscriptResult, Exit> {
+    let input = input.to_string();
+    let (input, url_start, url_end) = if is_clip_url(&input) {
+        resolve_clip(&input)?
+    } else {
+        let (start, end) = extract_time_window(&input);
+        (input, start, end)
+    };
+
+    // Each language in the chain is tried until one has subtitles.
+    let mut fetched = Err(Exit::NoCaptions);
+    for language in args.language.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+        fetched = fetch_transcript(&input, &fetch_options(args, language).build());
+        if !matches!(&fetched, Err(code) if *code == Exit::NoCaptions) {
+            break;
+        }
+    }
+    postprocess_transcript(args, fetched?, url_start, url_end)
 }
 
-#[derive(Serialize)]
-struct TranscriptSegment {
-    index: usize,
-    text: String,
-    start_seconds: f64,
-    end_seconds: f64,
-    duration_seconds: f64,
+/// The parsing options of `args`, for subtitles in `language`.
+fn fetch_options(args: &TranscribeArgs, language: &str) -> TranscriberBuilder {
+    fetch_defaults(language)
+        .dedup(!args.no_dedup)
+        .decode_entities(!args.raw_entities)
+        .cue_settings(args.cue_settings)
+        .word_timestamps(args.word_timestamps || matches!(args.format, OutputFormat::AssKaraoke | OutputFormat::Lrc))
 }
 
-#[derive(Serialize)]
-struct TranscriptResult {
-    video_id: String,
-    language: String,
-    segments: Vec<TranscriptSegment>,
-    metadata: Metadata,
+/// The --annotation-pattern regexes, or the default bracket patterns.
+fn annotation_patterns(args: &TranscribeArgs) -> Result<Vec<Regex>, Exit> {
+    let sources: Vec<&str> = if args.annotation_patterns.is_empty() {
+        DEFAULT_ANNOTATION_PATTERNS.to_vec()
+    } else {
+        args.annotation_patterns.iter().map(String::as_str).collect()
+    };
+    sources
+        .into_iter()
+        .map(|source| {
+            Regex::new(source).map_err(|e| {
+                error!(invalid_argument: "Invalid annotation pattern '{}' - {}", source, e);
+                Exit::InvalidArgs
+            })
+        })
+        .collect()
 }
 
-#[derive(Serialize)]
-struct Metadata {
-    total_segments: usize,
-    extracted_at: String,
+/// The --censor filter, from --censor-words or the built-in list.
+fn censor_filter(args: &TranscribeArgs) -> Result<Option<Censor>, Exit> {
+    if !args.censor {
+        return Ok(None);
+    }
+    let censor = match &args.censor_words {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(content) => Censor::new(&Censor::parse_wordlist(&content)),
+            Err(e) => {
+                error!(read_failed: "Failed to read {} - {}", path.display(), e);
+                return Err(Exit::InvalidArgs);
+            }
+        },
+        None => Censor::new(DEFAULT_WORDS),
+    };
+    censor.map(Some).map_err(|e| {
+        error!(invalid_argument: "Invalid --censor-words - {}", e);
+        Exit::InvalidArgs
+    })
 }
 
-fn extract_video_id(input: &str) -> Option<String> {
-    let trimmed = input.trim();
-    let id_regex = Regex::new(r"^[a-zA-Z0-9_-]{11}$").unwrap();
+/// Applies every post-processing option to a fetched or converted transcript. `url_start` and
+/// `url_end` are the time window of the input URL, if any.
+fn postprocess_transcript(
+    args: &TranscribeArgs,
+    mut result: TranscriptResult,
+    url_start: Option<f64>,
+    url_end: Option<f64>,
+) -> Result<TranscriptResult, Exit> {
+    let started = Instant::now();
+    let mode = match args.speakers {
+        SpeakerLabels::Keep => SpeakerMode::Keep,
+        SpeakerLabels::Strip => SpeakerMode::Strip,
+        SpeakerLabels::Group => SpeakerMode::Group,
+    };
+    let segments = label_speakers(std::mem::take(&mut result.segments), mode);
+    result.replace_segments(segments);
 
-    if id_regex.is_match(trimmed) {
-        return Some(trimmed.to_string());
+    if args.strip_annotations {
+        let patterns = annotation_patterns(args)?;
+        let segments = strip_annotations(std::mem::take(&mut result.segments), &patterns, args.drop_empty);
+        if segments.is_empty() {
+            error!(empty_transcript: "No transcript content left after stripping annotations");
+            return Err(Exit::NoCaptions);
+        }
+        result.replace_segments(segments);
     }
 
-    if let Ok(url) = url::Url::parse(trimmed) {
-        let host = url.host_str().unwrap_or("");
-        let clean_host = host
-            .trim_start_matches("www.")
-            .trim_start_matches("m.")
-            .trim_start_matches("music.");
+    let (from, to) = if args.ignore_url_time {
+        (args.from, args.to)
+    } else {
+        (args.from.or(url_start), args.to.or(url_end))
+    };
 
-        if clean_host == "youtu.be" {
-            let path = url.path().trim_start_matches('/');
-            let id = path.split('/').next().unwrap_or("");
-            if id_regex.is_match(id) {
-                return Some(id.to_string());
-            }
+    if from.is_some() || to.is_some() {
+        let segments = clip_range(std::mem::take(&mut result.segments), from, to);
+        if segments.is_empty() {
+            error!(empty_transcript: "No transcript content in the requested time range");
+            return Err(Exit::NoCaptions);
         }
+        result.replace_segments(segments);
+    }
 
-        if clean_host == "youtube.com" {
-            if let Some(v) = url.query_pairs().find(|(k, _)| k == "v") {
-                if id_regex.is_match(&v.1) {
-                    return Some(v.1.to_string());
-                }
-            }
+    if args.offset.is_some() || args.stretch.is_some() {
+        let segments = retime(
+            std::mem::take(&mut result.segments),
+            args.offset.unwrap_or(0.0),
+            args.stretch.unwrap_or(1.0),
+        );
+        if segments.is_empty() {
+            error!(invalid_argument: "--offset moves every segment before the start of the video");
+            return Err(Exit::InvalidArgs);
+        }
+        result.replace_segments(segments);
+    }
 
-            let segments: Vec<&str> = url.path().split('/').filter(|s| !s.is_empty()).collect();
-            let patterns = ["watch", "embed", "v", "shorts", "live", "clip"];
+    if args.sentences {
+        let segments = split_sentences(std::mem::take(&mut result.segments));
+        result.replace_segments(segments);
+    }
 
-            for i in 0..segments.len() {
-                if patterns.contains(&segments[i]) {
-                    if let Some(id) = segments.get(i + 1) {
-                        if id_regex.is_match(id) {
-                            return Some(id.to_string());
-                        }
-                    }
-                }
-            }
+    if args.paragraphs {
+        let segments = merge_paragraphs(std::mem::take(&mut result.segments), args.gap, args.max_paragraph);
+        result.replace_segments(segments);
+    }
+
+    if let Some(max_segment) = args.max_segment {
+        let segments = split_long_segments(std::mem::take(&mut result.segments), max_segment);
+        result.replace_segments(segments);
+    }
+
+    if let Some(target) = &args.mt_translate {
+        let translator = match args.mt_provider {
+            MtProvider::Deepl => Translator::deepl_from_env(),
+            MtProvider::Libretranslate => Ok(Translator::libretranslate_from_env()),
+        };
+        let source = Some(result.language.as_str()).filter(|language| !language.is_empty());
+        if let Err(e) = translator.and_then(|t| translate_segments(&t, &mut result.segments, source, target)) {
+            error!(translate_failed: "Failed to translate {} into {} - {}", result.video_id, target, e);
+            return Err(Exit::Network);
         }
+        result.language = target.to_lowercase();
     }
 
-    None
-}
+    if let Some(censor) = censor_filter(args)? {
+        censor.apply_segments(&mut result.segments);
+    }
 
-fn check_yt_dlp() -> bool {
-    Command::new("yt-dlp").arg("--version").output().is_ok()
-}
+    redact(&mut result.segments, &args.redact);
+
+    if let Some(max_line_length) = args.max_line_length {
+        let segments = wrap_cues(std::mem::take(&mut result.segments), max_line_length, args.max_lines);
+        result.replace_segments(segments);
+    }
+
+    if args.cps_report {
+        print_cps_report(&result.segments, args.max_cps);
+    }
+
+    if let Some(n) = args.keywords {
+        result.keywords = Some(extract_keywords(&result.segments, n));
+    }
+
+    if !args.mark.is_empty() {
+        result.marks = Some(find_marks(&result.video_id, &result.segments, &args.mark));
+    }
+
+    if let Some(every) = args.toc {
+        result.toc = Some(table_of_contents(&result.video_id, &result.segments, every));
+    }
 
-fn install_yt_dlp() -> bool {
-    eprintln!("yt-dlp not found. Attempting to install...");
+    let has_chapters = result
+        .video
+        .as_ref()
+        .is_some_and(|v| v.chapters.as_ref().is_some_and(|c| !c.is_empty()));
+    if args.auto_chapters && !has_chapters {
+        match propose_chapters(llm_client(), &result.segments) {
+            Ok(chapters) => {
+                let video = result.video.get_or_insert_with(VideoInfo::default);
+                video.chapters = Some(chapters);
+                video.chapters_generated = true;
+            }
+            Err(e) => {
+                error!(
+                    chapters_failed, hint: LLM_SETUP_HINT;
+                    "Failed to generate chapters for {} - {}", result.video_id, e
+                );
+                return Err(Exit::Network);
+            }
+        }
+    }
 
-    if Command::new("pip").arg("--version").output().is_ok() {
-        let status = Command::new("pip")
-            .args(["install", "--user", "yt-dlp"])
-            .status();
-        if status.map(|s| s.success()).unwrap_or(false) {
-            return true;
+    if args.count_tokens {
+        for seg in &mut result.segments {
+            seg.tokens = Some(count_tokens(&seg.text));
         }
+        result.metadata.total_tokens = Some(result.segments.iter().filter_map(|seg| seg.tokens).sum());
     }
 
-    if Command::new("pipx").arg("--version").output().is_ok() {
-        let status = Command::new("pipx").args(["install", "yt-dlp"]).status();
-        if status.map(|s| s.success()).unwrap_or(false) {
-            return true;
+    if let Some(spec) = &args.chunk {
+        let mut chunks = chunk(&result, spec);
+        if let Some(embedder) = args.embed {
+            if let Err(e) = embed_chunks(embedder, &mut chunks) {
+                log_error(
+                    "embed_failed",
+                    format!("Failed to embed {} - {}", result.video_id, e),
+                    matches!(embedder, Embedder::Api).then_some(LLM_SETUP_HINT),
+                );
+                return Err(Exit::Network);
+            }
         }
+        result.chunks = Some(chunks);
     }
 
-    if Command::new("brew").arg("--version").output().is_ok() {
-        let status = Command::new("brew").args(["install", "yt-dlp"]).status();
-        if status.map(|s| s.success()).unwrap_or(false) {
-            return true;
+    if args.summarize {
+        match summarize(llm_client(), &result.segments) {
+            Ok(summary) => result.summary = Some(summary),
+            Err(e) => {
+                error!(
+                    summarize_failed, hint: LLM_SETUP_HINT;
+                    "Failed to summarize {} - {}", result.video_id, e
+                );
+                return Err(Exit::Network);
+            }
         }
     }
+    log_stage(
+        "process",
+        started,
+        format!("Processed {} into {} segments", result.video_id, result.metadata.total_segments),
+        serde_json::json!({ "segments": result.metadata.total_segments }),
+    );
+    Ok(result)
+}
+
+/// Renders the transcript in the requested format or template.
+fn render_output(args: &TranscribeArgs, result: &TranscriptResult) -> Result<Vec<u8>, Exit> {
+    if args.template.is_none() && matches!(args.format, OutputFormat::Msgpack) {
+        return Ok(format_msgpack(result));
+    }
+    if args.template.is_none() && matches!(args.format, OutputFormat::Proto) {
+        return Ok(yt_transcriber::proto::encode(result));
+    }
+    if args.template.is_none() && matches!(args.format, OutputFormat::Parquet | OutputFormat::Arrow) {
+        let encoded = match args.format {
+            OutputFormat::Arrow => encode_arrow(result),
+            _ => encode_parquet(result),
+        };
+        return encoded.map_err(|e| {
+            error!(encode_failed: "Failed to encode output - {}", e);
+            Exit::InvalidArgs
+        });
+    }
+    Ok(encode_text(&render_text(args, result)?, args.encoding, true))
+}
 
-    false
+/// Whether the output is a binary format rather than text.
+fn binary_output(args: &TranscribeArgs) -> bool {
+    args.template.is_none()
+        && matches!(
+            args.format,
+            OutputFormat::Parquet | OutputFormat::Arrow | OutputFormat::Msgpack | OutputFormat::Proto
+        )
 }
 
-fn parse_vtt_timestamp(ts: &str) -> f64 {
-    let parts: Vec<&str> = ts.split(':').collect();
-    match parts.len() {
-        2 => {
-            let mins: f64 = parts[0].parse().unwrap_or(0.0);
-            let secs: f64 = parts[1].parse().unwrap_or(0.0);
-            mins * 60.0 + secs
+/// Renders a text format or template. Binary formats render as nothing.
+fn render_text(args: &TranscribeArgs, result: &TranscriptResult) -> Result<String, Exit> {
+    let rendered = if let Some(template) = &args.template {
+        match render_template(template, result) {
+            Ok(o) => o,
+            Err(e) => {
+                error!(template_failed: "Failed to render template - {}", e);
+                return Err(Exit::InvalidArgs);
+            }
+        }
+    } else {
+        let mut registry = FormatRegistry::with_builtins();
+        registry.register(
+            "txt",
+            TxtFormatter {
+                timestamps: !args.no_timestamps,
+                links: args.link_timestamps,
+                paragraphs: args.paragraphs,
+            },
+        );
+        registry.register(
+            "md-notes",
+            MdNotesFormatter {
+                paragraphs: args.paragraphs,
+                links: args.link_timestamps,
+            },
+        );
+        if let Some(chunks) = &result.chunks {
+            return Ok(format_chunks(chunks));
         }
-        3 => {
-            let hours: f64 = parts[0].parse().unwrap_or(0.0);
-            let mins: f64 = parts[1].parse().unwrap_or(0.0);
-            let secs: f64 = parts[2].parse().unwrap_or(0.0);
-            hours * 3600.0 + mins * 60.0 + secs
+        let name = args.format.to_possible_value().expect("no skipped formats");
+        registry.render(name.get_name(), result).unwrap_or_default()
+    };
+    Ok(rendered)
+}
+
+fn convert(args: ConvertArgs) -> Exit {
+    let options = &args.options;
+    if let Err(code) = check_format(options) {
+        return code;
+    }
+    let language = options.language.split(',').next().unwrap_or_default().trim();
+    let result = match read_local_transcript(&args.input, args.from, &fetch_options(options, language).build()) {
+        Ok(result) => result,
+        Err(code) => return code,
+    };
+    let result = match postprocess_transcript(options, result, None, None) {
+        Ok(result) => result,
+        Err(code) => return code,
+    };
+    let output = match options.output.as_deref().map(|output| resolve_output(options, &result, output)).transpose() {
+        Ok(output) => output,
+        Err(code) => return code,
+    };
+    write_transcript(options, &result, output.as_deref())
+}
+
+/// Fills in an output path template from the transcript's metadata and creates the directories
+/// it names. Other outputs are returned as they are.
+fn resolve_output(args: &TranscribeArgs, result: &TranscriptResult, output: &str) -> Result<String, Exit> {
+    if !output_path::is_template(output) {
+        return Ok(output.to_string());
+    }
+    let path = output_path::expand(output, result, output_extension(args));
+    if path.starts_with("sqlite:") || is_s3_url(&path) {
+        return Ok(path);
+    }
+    if let Some(parent) = Path::new(&path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        if let Err(e) = fs::create_dir_all(parent) {
+            error!(write_failed: "Failed to create output directory {} - {}", parent.display(), e);
+            return Err(Exit::WriteFailed);
         }
-        _ => 0.0,
     }
+    Ok(path)
 }
 
-fn parse_vtt(content: &str) -> Vec<TranscriptSegment> {
-    let mut segments = Vec::new();
-    let timestamp_re = Regex::new(r"(\d{1,2}:\d{2}:\d{2}\.\d{3}|\d{1,2}:\d{2}\.\d{3})\s*-->\s*(\d{1,2}:\d{2}:\d{2}\.\d{3}|\d{1,2}:\d{2}\.\d{3})").unwrap();
-    let tag_re = Regex::new(r"<[^>]+>").unwrap();
+fn write_transcript(args: &TranscribeArgs, result: &TranscriptResult, output: Option<&str>) -> Exit {
+    if args.clipboard {
+        if binary_output(args) {
+            error!(invalid_argument: "--clipboard needs a text format");
+            return Exit::InvalidArgs;
+        }
+        let text = match render_text(args, result) {
+            Ok(text) => text,
+            Err(code) => return code,
+        };
+        if let Err(e) = copy_to_clipboard(&text) {
+            error!(clipboard_failed: "Failed to copy to the clipboard - {}", e);
+            return Exit::WriteFailed;
+        }
+        notice!("Transcript copied to the clipboard");
+        if output.is_none() {
+            return Exit::Success;
+        }
+    }
 
-    let lines: Vec<&str> = content.lines().collect();
-    let mut i = 0;
+    if let Some(db) = output.and_then(|o| o.strip_prefix("sqlite:")) {
+        if let Err(e) = write_sqlite(Path::new(db), result) {
+            error!(write_failed: "Failed to write SQLite database - {}", e);
+            return Exit::WriteFailed;
+        }
+        notice!("Transcript saved to {}", db);
+        return Exit::Success;
+    }
 
-    while i < lines.len() {
-        let line = lines[i].trim();
+    if let Some(path) = output.filter(|_| args.append) {
+        return append_output(args, result, path);
+    }
+    if let Some(by) = &args.split_by {
+        let Some(path) = output else {
+            error!(invalid_argument: "--split-by writes files and needs --output or --output-dir");
+            return Exit::InvalidArgs;
+        };
+        return write_parts(args, result, path, by);
+    }
 
-        if let Some(caps) = timestamp_re.captures(line) {
-            let start = parse_vtt_timestamp(&caps[1]);
-            let end = parse_vtt_timestamp(&caps[2]);
+    let colored = output.is_none() && args.template.is_none() && matches!(args.format, OutputFormat::Txt);
+    let rendered = if colored && use_color(args) {
+        render_text(args, result).map(|text| encode_text(&colorize_txt(&text), args.encoding, true))
+    } else {
+        render_output(args, result)
+    };
+    let mut bytes = match rendered {
+        Ok(bytes) => bytes,
+        Err(code) => return code,
+    };
+    if let Some(path) = output {
+        return save_output(args, path, &bytes);
+    }
 
-            let mut text_lines = Vec::new();
-            i += 1;
+    if !binary_output(args) {
+        bytes.extend(encode_text("\n", args.encoding, false));
+    }
+    let written = if args.pager && io::stdout().is_terminal() {
+        page_output(&bytes)
+    } else {
+        io::stdout().write_all(&bytes)
+    };
+    if let Err(e) = written {
+        error!(write_failed: "Failed to write output - {}", e);
+        return Exit::WriteFailed;
+    }
+    Exit::Success
+}
 
-            while i < lines.len() && !lines[i].trim().is_empty() && !timestamp_re.is_match(lines[i]) {
-                let text_line = lines[i].trim();
-                if !text_line.starts_with("WEBVTT") && !text_line.starts_with("Kind:") && !text_line.starts_with("Language:") {
-                    let clean = tag_re.replace_all(text_line, "").to_string();
-                    if !clean.is_empty() {
-                        text_lines.push(clean);
-                    }
-                }
-                i += 1;
-            }
-
-            if !text_lines.is_empty() {
-                let text = text_lines.join(" ");
-                if !text.trim().is_empty() {
-                    segments.push(TranscriptSegment {
-                        index: segments.len(),
-                        text,
-                        start_seconds: start,
-                        end_seconds: end,
-                        duration_seconds: end - start,
-                    });
-                }
-            }
-        } else {
-            i += 1;
+/// Whether TXT output on standard output gets colors.
+fn use_color(args: &TranscribeArgs) -> bool {
+    match args.color {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => {
+            io::stdout().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                && std::env::var_os("TERM").is_none_or(|term| term != "dumb")
         }
     }
+}
 
-    segments
+/// Colors the timestamps of TXT output cyan and its `=== Section ===` headings bold.
+fn colorize_txt(text: &str) -> String {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(r"(?m)^(?:(\[[\d:.]+\](?:\([^)\s]*\))?)|(=== .+ ===)$)").expect("valid pattern")
+    });
+    pattern
+        .replace_all(text, |caps: &regex::Captures| match caps.get(1) {
+            Some(timestamp) => format!("\x1b[36m{}\x1b[0m", timestamp.as_str()),
+            None => format!("\x1b[1m{}\x1b[0m", &caps[2]),
+        })
+        .into_owned()
 }
 
-fn format_timestamp_bracket(seconds: f64) -> String {
-    let mins = (seconds / 60.0).floor() as u32;
-    let secs = (seconds % 60.0).floor() as u32;
-    format!("[{:02}:{:02}]", mins, secs)
+/// Shows output through $PAGER, or `less` with LESS=FRX like git, so short output is printed as
+/// is and colors survive. Without a pager the output goes straight to standard output.
+fn page_output(bytes: &[u8]) -> io::Result<()> {
+    let pager = std::env::var("PAGER").ok().filter(|pager| !pager.trim().is_empty());
+    let pager = pager.as_deref().unwrap_or("less");
+    let mut parts = pager.split_whitespace();
+    let mut command = Command::new(parts.next().unwrap_or("less"));
+    command.args(parts).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            debug!(1, "Not paging, {} failed to start - {}", pager, e);
+            return io::stdout().write_all(bytes);
+        }
+    };
+    let written = child.stdin.take().map_or(Ok(()), |mut stdin| stdin.write_all(bytes));
+    child.wait()?;
+    match written {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        written => written,
+    }
 }
 
-fn format_timestamp_srt(seconds: f64) -> String {
-    let hours = (seconds / 3600.0).floor() as u32;
-    let mins = ((seconds % 3600.0) / 60.0).floor() as u32;
-    let secs = (seconds % 60.0).floor() as u32;
-    let millis = ((seconds % 1.0) * 1000.0).floor() as u32;
-    format!("{:02}:{:02}:{:02},{:03}", hours, mins, secs, millis)
+/// Writes each part of a split transcript to its own file named after `path`.
+fn write_parts(args: &TranscribeArgs, result: &TranscriptResult, path: &str, by: &SplitBy) -> Exit {
+    let parts = split(result, by);
+    let width = parts.len().to_string().len().max(2);
+    for part in &parts {
+        let bytes = match render_output(args, &part.result) {
+            Ok(bytes) => bytes,
+            Err(code) => return code,
+        };
+        let code = save_output(args, &part_path(path, part, width), &bytes);
+        if code != Exit::Success {
+            return code;
+        }
+    }
+    Exit::Success
 }
 
-fn format_txt(result: &TranscriptResult, include_timestamps: bool) -> String {
-    result
-        .segments
-        .iter()
-        .map(|seg| {
-            if include_timestamps {
-                format!("{} {}", format_timestamp_bracket(seg.start_seconds), seg.text)
-            } else {
-                seg.text.clone()
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
-}
-
-fn format_srt(result: &TranscriptResult) -> String {
-    result
-        .segments
-        .iter()
-        .enumerate()
-        .map(|(i, seg)| {
-            format!(
-                "{}\n{} --> {}\n{}",
-                i + 1,
-                format_timestamp_srt(seg.start_seconds),
-                format_timestamp_srt(seg.end_seconds),
-                seg.text
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n\n")
+/// `talk.srt` becomes `talk-01.srt`, or `talk-01-Intro.srt` for a chapter. Everything after the
+/// first dot of the file name stays the extension, so `talk.json.gz` becomes `talk-01.json.gz`.
+fn part_path(path: &str, part: &Part, width: usize) -> String {
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    let split_at = path
+        .get(name_start + 1..)
+        .and_then(|name| name.find('.'))
+        .map_or(path.len(), |i| name_start + 1 + i);
+    let (stem, extension) = path.split_at(split_at);
+    let mut name = format!("{}-{:0width$}", stem, part.number, width = width);
+    if let Some(title) = &part.title {
+        name.push('-');
+        name.push_str(&output_path::sanitize(title));
+    }
+    name + extension
 }
 
-fn format_json(result: &TranscriptResult) -> String {
-    serde_json::to_string_pretty(result).unwrap_or_default()
+/// --append needs a format whose files can simply be concatenated, and --chunk writes jsonl,
+/// parquet or arrow.
+fn check_format(args: &TranscribeArgs) -> Result<(), Exit> {
+    if args.append && !matches!(args.format, OutputFormat::Txt | OutputFormat::Jsonl | OutputFormat::Csv) {
+        error!(invalid_argument: "--append works with the txt, jsonl and csv formats");
+        return Err(Exit::InvalidArgs);
+    }
+    if args.chunk.is_some()
+        && !matches!(args.format, OutputFormat::Jsonl | OutputFormat::Parquet | OutputFormat::Arrow)
+    {
+        error!(invalid_argument: "--chunk writes jsonl, parquet or arrow; leave out --format or use one of them");
+        return Err(Exit::InvalidArgs);
+    }
+    Ok(())
 }
 
-fn main() -> ExitCode {
-    let cli = Cli::parse();
+/// Adds a transcript to the end of a file: TXT transcripts each start with a line naming the
+/// video, JSONL and CSV rows carry the video ID, and the CSV header is only written to a new file.
+fn append_output(args: &TranscribeArgs, result: &TranscriptResult, path: &str) -> Exit {
+    if is_s3_url(path) {
+        error!(invalid_argument: "--append cannot add to an s3:// object");
+        return Exit::InvalidArgs;
+    }
 
-    let video_id = match extract_video_id(&cli.url) {
-        Some(id) => id,
-        None => {
-            eprintln!("Error: Invalid YouTube URL or video ID");
-            return ExitCode::from(1);
+    let _appending = APPENDING.lock();
+    let empty = fs::metadata(path).map_or(true, |metadata| metadata.len() == 0);
+    let mut text = match args.format {
+        OutputFormat::Jsonl => match &result.chunks {
+            Some(chunks) => format_chunks(chunks),
+            None => format_jsonl(result),
+        },
+        OutputFormat::Csv => format_csv(result, empty),
+        _ => {
+            let separator = if empty { "" } else { "\n" };
+            let transcript = format_txt(result, !args.no_timestamps, args.link_timestamps, args.paragraphs);
+            format!("{}{}\n{}", separator, txt_delimiter(result), transcript)
         }
     };
+    text.push('\n');
+    let text = encode_text(&text, args.encoding, empty);
+    let compressed = match compress_output(path, &text) {
+        Ok(compressed) => compressed,
+        Err(code) => return code,
+    };
+    let bytes = compressed.as_deref().unwrap_or(&text);
 
-    if !check_yt_dlp() {
-        if !install_yt_dlp() {
-            eprintln!("Error: yt-dlp is required but could not be installed");
-            eprintln!("Please install it manually: pip install yt-dlp");
-            return ExitCode::from(1);
-        }
-        if !check_yt_dlp() {
-            eprintln!("Error: yt-dlp installation succeeded but command not found in PATH");
-            eprintln!("Try restarting your terminal or adding ~/.local/bin to PATH");
-            return ExitCode::from(1);
-        }
+    let file = fs::OpenOptions::new().create(true).append(true).open(path);
+    if let Err(e) = file.and_then(|mut file| file.write_all(bytes)) {
+        error!(write_failed: "Failed to write file - {}", e);
+        return Exit::WriteFailed;
     }
+    notice!("Transcript appended to {}", path);
+    Exit::Success
+}
 
-    let temp_dir = match TempDir::new() {
-        Ok(d) => d,
-        Err(e) => {
-            eprintln!("Error: Failed to create temp directory - {}", e);
-            return ExitCode::from(4);
-        }
-    };
-
-    let url = format!("https://www.youtube.com/watch?v={}", video_id);
-    let output_template = temp_dir.path().join("%(id)s");
-
-    let output = Command::new("yt-dlp")
-        .args([
-            "--write-sub",
-            "--write-auto-sub",
-            "--sub-lang",
-            &cli.language,
-            "--sub-format",
-            "vtt",
-            "--skip-download",
-            "--no-warnings",
-            "-o",
-            output_template.to_str().unwrap_or("%(id)s"),
-            &url,
-        ])
-        .output();
+/// Environment variables read outside of clap; options with an `env` are listed with their flag.
+const ENVIRONMENT: &[(&str, &str)] = &[
+    ("YT_TRANSCRIBER_CONFIG", "Path of the config file."),
+    ("YT_TRANSCRIBER_CACHE_DIR", "Directory for cached subtitles."),
+    ("AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY", "Credentials for s3:// output."),
+    ("AWS_SESSION_TOKEN", "Session token for temporary S3 credentials."),
+    ("AWS_REGION, AWS_DEFAULT_REGION", "Region for s3:// output (default us-east-1)."),
+    ("AWS_ENDPOINT_URL", "S3-compatible endpoint when --s3-endpoint is not given."),
+    ("NO_COLOR", "Turns off colors with --color auto."),
+    ("PAGER", "Pager for --pager (default less, with LESS=FRX unless LESS is set)."),
+];
 
-    let output = match output {
-        Ok(o) => o,
+/// Writes the man page: clap's sections plus exit status, environment and files.
+fn man_page() -> Exit {
+    let man = clap_mangen::Man::new(Cli::command());
+    let mut page = Vec::new();
+    let rendered = man
+        .render_title(&mut page)
+        .and_then(|_| man.render_name_section(&mut page))
+        .and_then(|_| man.render_synopsis_section(&mut page))
+        .and_then(|_| man.render_description_section(&mut page))
+        .and_then(|_| man.render_options_section(&mut page))
+        .and_then(|_| man.render_subcommands_section(&mut page));
+    if let Err(e) = rendered {
+        error!(render_failed: "Failed to render man page - {}", e);
+        return Exit::InvalidArgs;
+    }
+
+    let mut extra = String::from(".SH \"EXIT STATUS\"\n");
+    for exit in Exit::ALL {
+        extra.push_str(&format!(".TP\n{} ({})\n{}\n", exit.code(), exit.name(), exit.description().replace('-', "\\-")));
+    }
+    extra.push_str(".SH ENVIRONMENT\n");
+    extra.push_str("Options shown with [env: ...] can also be set through that variable.\n");
+    for (name, meaning) in ENVIRONMENT {
+        extra.push_str(&format!(".TP\n\\fB{}\\fR\n{}\n", name, meaning.replace('-', "\\-")));
+    }
+    extra.push_str(".SH FILES\n");
+    extra.push_str(".TP\n\\fI~/.config/yt\\-transcriber/config.toml\\fR\nDefault settings and profiles.\n");
+    extra.push_str(".TP\n\\fI~/.cache/yt\\-transcriber/\\fR\nCached subtitles.\n");
+    page.extend_from_slice(extra.as_bytes());
+
+    if let Err(e) = man.render_version_section(&mut page) {
+        error!(render_failed: "Failed to render man page - {}", e);
+        return Exit::InvalidArgs;
+    }
+    if let Err(e) = io::stdout().write_all(&page) {
+        error!(write_failed: "Failed to write output - {}", e);
+        return Exit::WriteFailed;
+    }
+    Exit::Success
+}
+
+/// Fills in the transcription options not given on the command line from the config file.
+fn apply_config(cli: &mut Cli, matches: &ArgMatches) -> Result<(), Exit> {
+    let matches = matches.subcommand().map_or(matches, |(_, sub)| sub);
+    let options = match &mut cli.command {
+        None => Some(&mut cli.options),
+        Some(Commands::Watch(args)) => Some(&mut args.options),
+        Some(Commands::Serve(args)) => Some(&mut args.options),
+        Some(Commands::Mcp(args)) => Some(&mut args.options),
+        Some(Commands::Convert(args)) => Some(&mut args.options),
+        Some(_) => None,
+    };
+    let profile = options.as_ref().and_then(|options| options.profile.clone());
+
+    let path = Config::default_path();
+    let config = match path.as_deref().map(Config::load).transpose() {
+        Ok(config) => config.flatten(),
         Err(e) => {
-            eprintln!("Error: Failed to run yt-dlp - {}", e);
-            return ExitCode::from(3);
+            error!(invalid_config: "Failed to read config file {} - {}", path.unwrap_or_default().display(), e);
+            return Err(Exit::InvalidArgs);
+        }
+    };
+    let config = match (config, &profile) {
+        (Some(config), Some(name)) => {
+            let defined: Vec<String> = config.profile.keys().cloned().collect();
+            match config.with_profile(name) {
+                Some(config) => config,
+                None => {
+                    error!(invalid_config: "Unknown profile '{}' (defined: {})", name, defined.join(", "));
+                    return Err(Exit::InvalidArgs);
+                }
+            }
+        }
+        (None, Some(name)) => {
+            error!(invalid_config: "Unknown profile '{}' (no config file found)", name);
+            return Err(Exit::InvalidArgs);
         }
+        (Some(config), None) => config,
+        (None, None) => return Ok(()),
     };
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("unavailable") || stderr.contains("private") || stderr.contains("deleted") {
-            eprintln!("Error: Video is unavailable (private/deleted/restricted)");
-        } else {
-            eprintln!("Error: yt-dlp failed - {}", stderr.trim());
+    let invalid = |key: &str, value: &str| {
+        error!(invalid_config: "Invalid {} '{}' in config file", key, value);
+        Exit::InvalidArgs
+    };
+    let from_user = |id: &str| matches.value_source(id).is_some_and(|source| source != ValueSource::DefaultValue);
+    if cli.yt_dlp_path.is_none() {
+        cli.yt_dlp_path = config.yt_dlp_path;
+    }
+    if let Some(retries) = config.retries.filter(|_| !from_user("retries")) {
+        cli.retries = retries;
+    }
+    if let Some(jobs) = config.jobs.filter(|_| !from_user("jobs")) {
+        cli.jobs = jobs;
+    }
+    if let Some(timeout) = config.timeout.filter(|_| !from_user("timeout")) {
+        cli.timeout = duration_arg(&timeout).map_err(|_| invalid("timeout", &timeout))?;
+    }
+    if cli.yt_dlp_args.is_none() {
+        cli.yt_dlp_args = config.yt_dlp_args;
+    }
+    if cli.proxy.is_none() {
+        cli.proxy = config.proxy;
+    }
+    if cli.cookies.is_none() {
+        cli.cookies = config.cookies;
+    }
+    if cli.po_token.is_none() {
+        cli.po_token = config.po_token;
+    }
+    if cli.player_client.is_empty() {
+        cli.player_client = config.player_client.unwrap_or_default();
+    }
+    if let Some(country) = config.geo_bypass_country.filter(|_| cli.geo_bypass_country.is_none()) {
+        cli.geo_bypass_country = Some(country_arg(&country).map_err(|_| invalid("geo_bypass_country", &country))?);
+    }
+    if let Some(browser) = config.cookies_from_browser.filter(|_| cli.cookies_from_browser.is_none()) {
+        cli.cookies_from_browser = Some(browser_arg(&browser).map_err(|_| invalid("cookies_from_browser", &browser))?);
+    }
+    if cli.yt_dlp_candidates.is_empty() {
+        cli.yt_dlp_candidates = config.yt_dlp_candidates.unwrap_or_default();
+    }
+    if let Some(llm) = config.llm {
+        if let Some(temperature) = llm.temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+            return Err(invalid("llm.temperature", &temperature.to_string()));
         }
-        return ExitCode::from(2);
+        if llm.max_concurrency == Some(0) {
+            return Err(invalid("llm.max_concurrency", "0"));
+        }
+        let _ = LLM.set(LlmClient::from_config(&llm));
+    }
+    let Some(options) = options else {
+        return Ok(());
+    };
+    // --chunk picks jsonl unless a format is given on the command line.
+    if let Some(format) = config.format.filter(|_| !from_user("format") && options.chunk.is_none()) {
+        options.format = OutputFormat::from_str(&format, true).map_err(|_| invalid("format", &format))?;
+    }
+    if let Some(encoding) = config.encoding.filter(|_| !from_user("encoding")) {
+        options.encoding = TextEncoding::from_str(&encoding, true).map_err(|_| invalid("encoding", &encoding))?;
+    }
+    if let Some(template) = config.template.filter(|_| !from_user("template")) {
+        options.template = Some(template);
+    }
+    if let Some(language) = config.language.filter(|_| !from_user("language")) {
+        options.language = language.to_arg();
+    }
+    if options.output.is_none() && options.output_dir.is_none() {
+        options.output_dir = config.output_dir.map(|dir| dir.to_string_lossy().into_owned());
+    }
+    if options.censor_words.is_none() {
+        options.censor_words = config.censor_words;
+    }
+    if let Some(speakers) = config.speakers.filter(|_| !from_user("speakers")) {
+        options.speakers = SpeakerLabels::from_str(&speakers, true).map_err(|_| invalid("speakers", &speakers))?;
+    }
+    if let Some(color) = config.color.filter(|_| !from_user("color")) {
+        options.color = ColorMode::from_str(&color, true).map_err(|_| invalid("color", &color))?;
     }
 
-    let vtt_patterns = [
-        format!("{}.{}.vtt", video_id, cli.language),
-        format!("{}.{}-orig.vtt", video_id, cli.language),
+    let flags = [
+        ("no_timestamps", config.no_timestamps, &mut options.no_timestamps),
+        ("link_timestamps", config.link_timestamps, &mut options.link_timestamps),
+        ("pager", config.pager, &mut options.pager),
+        ("strip_annotations", config.strip_annotations, &mut options.strip_annotations),
+        ("drop_empty", config.drop_empty, &mut options.drop_empty),
+        ("sentences", config.sentences, &mut options.sentences),
+        ("paragraphs", config.paragraphs, &mut options.paragraphs),
+        ("summarize", config.summarize, &mut options.summarize),
+        ("censor", config.censor, &mut options.censor),
+        ("auto_chapters", config.auto_chapters, &mut options.auto_chapters),
     ];
+    for (id, value, flag) in flags {
+        if let Some(value) = value.filter(|_| !from_user(id)) {
+            *flag = value;
+        }
+    }
+    let limits = [
+        ("max_line_length", config.max_line_length, &mut options.max_line_length),
+        ("max_lines", config.max_lines, &mut options.max_lines),
+        ("keywords", config.keywords, &mut options.keywords),
+    ];
+    for (id, value, limit) in limits {
+        if value.is_some() && !from_user(id) {
+            *limit = value;
+        }
+    }
+    Ok(())
+}
 
-    let mut vtt_content = None;
+/// Whether a file can be created in `dir`, creating the directory if needed.
+fn check_writable(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    tempfile::tempfile_in(dir).map(|_| ()).map_err(|e| e.to_string())
+}
 
-    for pattern in &vtt_patterns {
-        let vtt_path = temp_dir.path().join(pattern);
-        if vtt_path.exists() {
-            if let Ok(content) = fs::read_to_string(&vtt_path) {
-                vtt_content = Some(content);
-                break;
+/// Runs every check, printing `ok`, `warn` or `FAIL` with a fix for each problem. Exits with the
+/// code of the first failed check.
+fn doctor(args: DoctorArgs) -> Exit {
+    let mut status = Exit::Success;
+    // A failure without an exit code is only a warning.
+    let mut report = |result: Result<String, (Option<Exit>, String, &str)>| match result {
+        Ok(detail) => println!("  ok    {}", detail),
+        Err((None, detail, fix)) => println!("  warn  {}\n        {}", detail, fix),
+        Err((Some(code), detail, fix)) => {
+            println!("  FAIL  {}\n        {}", detail, fix);
+            if status == Exit::Success {
+                status = code;
             }
         }
-    }
+    };
 
-    if vtt_content.is_none() {
-        if let Ok(entries) = fs::read_dir(temp_dir.path()) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().map(|e| e == "vtt").unwrap_or(false) {
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        vtt_content = Some(content);
-                        break;
-                    }
-                }
-            }
+    report(match yt_dlp_version() {
+        Some(version) => match yt_dlp::stale_days(&version) {
+            Some(days) => Err((
+                None,
+                format!("yt-dlp {} is {} days old", version, days),
+                "Run `yt-transcriber deps update`; old extractors often fail with no subtitles or HTTP 403",
+            )),
+            None => Ok(format!("yt-dlp {}", version)),
+        },
+        None => Err((
+            Some(Exit::DependencyMissing),
+            "yt-dlp not found in PATH".to_string(),
+            "Run any transcription to download the standalone binary, or install it with `pip install yt-dlp`",
+        )),
+    });
+
+    report(match http::agent().get("https://www.youtube.com/generate_204").timeout(Duration::from_secs(10)).call() {
+        Ok(_) => Ok("YouTube is reachable".to_string()),
+        Err(e) => Err((
+            Some(Exit::Network),
+            format!("Cannot reach YouTube - {}", e),
+            "Check your internet connection, firewall or proxy settings",
+        )),
+    });
+
+    let cache_dir = Cache::default_dir();
+    report(match check_writable(&cache_dir) {
+        Ok(()) => Ok(format!("Cache directory {} is writable", cache_dir.display())),
+        Err(e) => Err((
+            Some(Exit::WriteFailed),
+            format!("Cannot write to cache directory {} - {}", cache_dir.display(), e),
+            "Fix its permissions, point YT_TRANSCRIBER_CACHE_DIR elsewhere or use --no-cache",
+        )),
+    });
+
+    let output_dir = args.output.unwrap_or_else(|| PathBuf::from("."));
+    report(match check_writable(&output_dir) {
+        Ok(()) => Ok(format!("Output directory {} is writable", output_dir.display())),
+        Err(e) => Err((
+            Some(Exit::WriteFailed),
+            format!("Cannot write to output directory {} - {}", output_dir.display(), e),
+            "Fix its permissions or choose another directory with --output or --output-dir",
+        )),
+    });
+
+    report(match Command::new("ffmpeg").arg("-version").output() {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Ok(stdout.lines().next().unwrap_or("ffmpeg").to_string())
+        }
+        _ => Err((
+            None,
+            "ffmpeg not found (optional; yt-dlp uses it to extract and convert audio)".to_string(),
+            "Install it from https://ffmpeg.org or your package manager",
+        )),
+    });
+
+    status
+}
+
+/// GitHub repository whose releases self-update installs.
+const RELEASE_REPO: &str = "XMA-Faez/yt-transcriber";
+
+/// Name of the release asset built for this platform, such as `yt-transcriber-x86_64-linux`.
+fn release_asset_name() -> String {
+    format!(
+        "yt-transcriber-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+fn self_update(args: SelfUpdateArgs) -> Exit {
+    let current = env!("CARGO_PKG_VERSION");
+    let latest = Release::latest(RELEASE_REPO);
+    let release = match latest {
+        Ok(release) => release,
+        Err(e) => {
+            error!(network_error: "Failed to look up the latest release - {}", e);
+            return Exit::Network;
         }
+    };
+    if !is_newer(release.version(), current) {
+        notice!("yt-transcriber {} is up to date", current);
+        return Exit::Success;
+    }
+    if args.check {
+        println!("yt-transcriber {} is available (installed: {})", release.version(), current);
+        return Exit::Success;
     }
 
-    let vtt_content = match vtt_content {
-        Some(c) => c,
-        None => {
-            eprintln!("Error: No subtitles available for this video in '{}' language", cli.language);
-            return ExitCode::from(2);
+    let name = release_asset_name();
+    let (Some(binary), Some(sums)) = (release.asset(&name), release.asset("SHA256SUMS")) else {
+        error!(unsupported: "Release {} has no {} build with checksums", release.tag, name);
+        return Exit::Unavailable;
+    };
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            error!(read_failed: "Failed to locate the running executable - {}", e);
+            return Exit::WriteFailed;
         }
     };
 
-    let segments = parse_vtt(&vtt_content);
+    let progress = Progress::spinner(format!("Downloading {} {}", name, release.tag));
+    let downloaded = release::download(&binary.url).and_then(|bytes| Ok((bytes, release::download(&sums.url)?)));
+    drop(progress);
+    let (bytes, sums) = match downloaded {
+        Ok(downloaded) => downloaded,
+        Err(e) => {
+            error!(network_error: "Failed to download {} - {}", name, e);
+            return Exit::Network;
+        }
+    };
+    if let Err(e) = verify_checksum(&bytes, &String::from_utf8_lossy(&sums), &name) {
+        error!(checksum_mismatch: "Refusing to install {} - {}", name, e);
+        return Exit::ChecksumMismatch;
+    }
+    if let Err(e) = release::replace_executable(&exe, &bytes) {
+        error!(write_failed: "Failed to replace {} - {}", exe.display(), e);
+        return Exit::WriteFailed;
+    }
+    notice!("Updated yt-transcriber {} -> {}", current, release.version());
+    Exit::Success
+}
 
-    if segments.is_empty() {
-        eprintln!("Error: No transcript content found");
-        return ExitCode::from(2);
+fn deps(args: DepsArgs) -> Exit {
+    match args.action {
+        DepsAction::Status => {
+            let Some(version) = yt_dlp_version() else {
+                println!("yt-dlp: not installed (downloaded on first use)");
+                return Exit::DependencyMissing;
+            };
+            let source = if YT_DLP_PATH.get().is_some() {
+                "--yt-dlp-path"
+            } else if yt_dlp::managed_path().is_file() {
+                "standalone"
+            } else {
+                "PATH"
+            };
+            println!("yt-dlp {} ({}: {})", version, source, yt_dlp_program().join(" "));
+            if let Some(days) = yt_dlp::stale_days(&version) {
+                println!("{} days old; run `yt-transcriber deps update`", days);
+            }
+            Exit::Success
+        }
+        DepsAction::Update { version } => {
+            let version = version.or_else(|| PINNED_YT_DLP.get().cloned());
+            let previous = yt_dlp_version();
+            if let Err(e) = install_yt_dlp_version(version.as_deref()) {
+                return report_error(&e);
+            }
+            if let Some(previous) = previous {
+                notice!("Replaced yt-dlp {}", previous);
+            }
+            Exit::Success
+        }
     }
+}
 
-    let result = TranscriptResult {
-        video_id: video_id.clone(),
-        language: cli.language.clone(),
-        metadata: Metadata {
-            total_segments: segments.len(),
-            extracted_at: chrono::Utc::now().to_rfc3339(),
-        },
-        segments,
+/// The fetch settings of the global yt-dlp, network and cache options.
+fn cli_fetch_settings(cli: &Cli) -> Result<TranscriberBuilder, String> {
+    let extra_args = match &cli.yt_dlp_args {
+        Some(args) => split_args(args).map_err(|e| format!("Invalid --yt-dlp-args - {}", e))?,
+        None => Vec::new(),
     };
+    let cache = (cli.cache || !cli.no_cache).then(|| Cache::new(Cache::default_dir(), Some(cli.cache_ttl)));
+    Ok(Transcriber::builder()
+        .yt_dlp_resolver(|| ensure_yt_dlp().map(|()| yt_dlp_program()))
+        .extra_yt_dlp_args(extra_args)
+        .proxy(cli.proxy.clone())
+        .cookies(cli.cookies.clone())
+        .cookies_from_browser(cli.cookies_from_browser.clone())
+        .po_token(cli.po_token.clone())
+        .player_client(cli.player_client.clone())
+        .geo_bypass_country(cli.geo_bypass_country.clone())
+        .timeout(Some(cli.timeout).filter(|timeout| !timeout.is_zero()))
+        .retries(cli.retries)
+        .any_site(cli.any_site)
+        .cache(cache)
+        .logger(|level, message| match level {
+            LogLevel::Warning => warning!("{}", message),
+            LogLevel::Notice => notice!("{}", message),
+            LogLevel::Debug => debug!(2, "{}", message),
+        }))
+}
 
-    let output = match cli.format {
-        OutputFormat::Txt => format_txt(&result, !cli.no_timestamps),
-        OutputFormat::Srt => format_srt(&result),
-        OutputFormat::Json => format_json(&result),
-    };
+fn list_exit_codes() -> Exit {
+    for exit in Exit::ALL {
+        println!("{:>3}  {:<18}  {}", exit.code(), exit.name(), exit.description());
+    }
+    Exit::Success
+}
 
-    if let Some(path) = cli.output {
-        if let Err(e) = fs::write(&path, &output) {
-            eprintln!("Error: Failed to write file - {}", e);
-            return ExitCode::from(4);
+fn main() -> ExitCode {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let verbosity = if cli.quiet { -1 } else { cli.verbose.min(2) as i8 };
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+    JSON_LOGS.store(matches!(cli.log_format, LogFormat::Json), Ordering::Relaxed);
+    JSON_ERRORS.store(matches!(cli.error_format, ErrorFormat::Json), Ordering::Relaxed);
+    let interactive = matches!(
+        cli.command,
+        None | Some(Commands::Watch(_)) | Some(Commands::SelfUpdate(_)) | Some(Commands::Deps(_))
+    );
+    SHOW_PROGRESS.store(
+        interactive && !cli.no_progress && verbosity >= 0 && !matches!(cli.log_format, LogFormat::Json) && io::stderr().is_terminal(),
+        Ordering::Relaxed,
+    );
+    if let Some(version) = cli.yt_dlp_version.clone() {
+        let _ = PINNED_YT_DLP.set(version);
+    }
+    NO_INSTALL.store(cli.no_install, Ordering::Relaxed);
+    ANY_SITE.store(cli.any_site, Ordering::Relaxed);
+    ASSUME_YES.store(cli.yes, Ordering::Relaxed);
+    if cli.list_exit_codes {
+        return list_exit_codes().into();
+    }
+    if let Err(code) = apply_config(&mut cli, &matches) {
+        return code.into();
+    }
+    if let Some(path) = cli.yt_dlp_path.as_deref().filter(|path| !path.trim().is_empty()) {
+        // Anything but an existing file is a command line such as `python3 -m yt_dlp`.
+        let words = if Path::new(path).is_file() { Ok(vec![path.to_string()]) } else { split_args(path) };
+        match words {
+            Ok(words) if !words.is_empty() => {
+                let _ = YT_DLP_PATH.set(words);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!(invalid_argument: "Invalid --yt-dlp-path - {}", e);
+                return Exit::InvalidArgs.into();
+            }
+        }
+    }
+    let _ = YT_DLP_CANDIDATES.set(cli.yt_dlp_candidates.clone());
+    match cli_fetch_settings(&cli) {
+        Ok(settings) => {
+            let _ = FETCH_SETTINGS.set(settings);
+        }
+        Err(e) => {
+            error!(invalid_argument: "{}", e);
+            return Exit::InvalidArgs.into();
+        }
+    }
+    let timeout = Some(cli.timeout).filter(|timeout| !timeout.is_zero());
+    if let Err(e) = http::init(cli.proxy.as_deref(), timeout) {
+        error!(invalid_argument: "Invalid --proxy - {}", e);
+        return Exit::InvalidArgs.into();
+    }
+    if let Some(path) = &cli.cookies {
+        match cookies::load(path) {
+            Ok(loaded) => {
+                let _ = COOKIES.set(loaded);
+            }
+            Err(e) => {
+                error!(read_failed: "Failed to read cookies file {} - {}", path.display(), e);
+                return Exit::InvalidArgs.into();
+            }
         }
-        eprintln!("Transcript saved to {}", path);
-    } else {
-        println!("{}", output);
     }
 
-    ExitCode::SUCCESS
+    let exit = match cli.command {
+        Some(Commands::Search(args)) => search(args),
+        Some(Commands::Tui(args)) => tui(args),
+        Some(Commands::Index(args)) => index(args),
+        Some(Commands::Query(args)) => query(args),
+        Some(Commands::Stats(args)) => stats(args),
+        Some(Commands::Diff(args)) => diff(args),
+        Some(Commands::Audit(args)) => audit(args),
+        Some(Commands::Cache(args)) => cache(args),
+        Some(Commands::Watch(args)) => watch(*args),
+        Some(Commands::Serve(args)) => serve(*args),
+        Some(Commands::Mcp(args)) => mcp(*args),
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Cli::command(), "yt-transcriber", &mut io::stdout());
+            Exit::Success
+        }
+        Some(Commands::Man) => man_page(),
+        Some(Commands::Schema { proto: true }) => {
+            print!("{}", yt_transcriber::proto::PROTO);
+            Exit::Success
+        }
+        Some(Commands::Schema { proto: false }) => {
+            println!("{}", serde_json::to_string_pretty(&json_schema()).unwrap_or_default());
+            Exit::Success
+        }
+        Some(Commands::Doctor(args)) => doctor(args),
+        Some(Commands::SelfUpdate(args)) => self_update(args),
+        Some(Commands::Deps(args)) => deps(args),
+        Some(Commands::Convert(args)) => convert(*args),
+        None => transcribe(cli),
+    };
+    exit.into()
 }
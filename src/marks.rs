@@ -0,0 +1,59 @@
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::format::youtube_link;
+use crate::transcript::TranscriptSegment;
+
+/// A mention of a `--mark` keyword, with a link that opens the video where it is said.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Mark {
+    pub keyword: String,
+    pub seconds: f64,
+    pub url: String,
+    /// The segment the keyword appears in.
+    pub text: String,
+}
+
+/// Every segment mentioning one of `keywords`, matched as whole words and ignoring case, in
+/// order of time. A segment mentioning several keywords is listed once for each.
+pub fn find_marks(
+    video_id: &str,
+    segments: &[TranscriptSegment],
+    keywords: &[String],
+) -> Vec<Mark> {
+    let patterns: Vec<(&str, Regex)> = keywords
+        .iter()
+        .map(|keyword| keyword.trim())
+        .filter(|keyword| !keyword.is_empty())
+        .filter_map(|keyword| {
+            let words: Vec<String> = keyword.split_whitespace().map(regex::escape).collect();
+            let boundary = |c: Option<char>| match c {
+                Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+                _ => "",
+            };
+            let pattern = format!(
+                r"(?i){}{}{}",
+                boundary(keyword.chars().next()),
+                words.join(r"\s+"),
+                boundary(keyword.chars().last())
+            );
+            Regex::new(&pattern).ok().map(|re| (keyword, re))
+        })
+        .collect();
+
+    let mut marks = Vec::new();
+    for seg in segments {
+        for (keyword, re) in &patterns {
+            if re.is_match(&seg.text) {
+                marks.push(Mark {
+                    keyword: keyword.to_string(),
+                    seconds: seg.start_seconds,
+                    url: youtube_link(video_id, seg.start_seconds),
+                    text: seg.text.clone(),
+                });
+            }
+        }
+    }
+    marks
+}
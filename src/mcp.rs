@@ -0,0 +1,149 @@
+use std::io::{self, BufRead, Write};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// MCP protocol revision this server implements.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Arguments of the `get_transcript` tool.
+#[derive(Deserialize)]
+pub struct TranscriptRequest {
+    /// YouTube URL or video ID.
+    pub url: String,
+    pub language: Option<String>,
+    pub format: Option<String>,
+}
+
+/// Formats the tool can return; binary formats such as Parquet, MessagePack or protobuf do not
+/// fit in a text result.
+pub const TOOL_FORMATS: &[&str] = &["txt", "srt", "vtt", "ass", "lrc", "json", "jsonl", "csv", "md-notes"];
+
+fn tool_definition() -> Value {
+    json!({
+        "name": "get_transcript",
+        "description": "Fetch the transcript of a YouTube video with timestamps. The json format \
+            returns segments with start and end seconds plus video metadata.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "YouTube URL or 11-character video ID"
+                },
+                "language": {
+                    "type": "string",
+                    "description": "Language code of the captions, e.g. en or es (default: en)"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": TOOL_FORMATS,
+                    "description": "Output format (default: txt)"
+                }
+            },
+            "required": ["url"]
+        }
+    })
+}
+
+/// Runs a Model Context Protocol server over newline-delimited JSON-RPC until `input` ends.
+/// `get_transcript` answers tool calls with the rendered transcript or an error message.
+pub fn serve<R, W, F>(input: R, mut output: W, mut get_transcript: F) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(TranscriptRequest) -> Result<String, String>,
+{
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(&message, &mut get_transcript),
+            Err(e) => Some(error_response(
+                Value::Null,
+                -32700,
+                &format!("Parse error: {}", e),
+            )),
+        };
+        if let Some(response) = response {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to one message; notifications get none.
+fn handle_message<F>(message: &Value, get_transcript: &mut F) -> Option<Value>
+where
+    F: FnMut(TranscriptRequest) -> Result<String, String>,
+{
+    let id = message.get("id").cloned()?;
+    let method = message
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": [tool_definition()] }),
+        "tools/call" => match params.get("name").and_then(Value::as_str) {
+            Some("get_transcript") => call_get_transcript(&params, get_transcript),
+            Some(name) => {
+                return Some(error_response(
+                    id,
+                    -32602,
+                    &format!("Unknown tool: {}", name),
+                ))
+            }
+            None => return Some(error_response(id, -32602, "Missing tool name")),
+        },
+        _ => {
+            return Some(error_response(
+                id,
+                -32601,
+                &format!("Method not found: {}", method),
+            ))
+        }
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+/// Tool failures are reported in the result with `isError` so the model can see them.
+fn call_get_transcript<F>(params: &Value, get_transcript: &mut F) -> Value
+where
+    F: FnMut(TranscriptRequest) -> Result<String, String>,
+{
+    let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+    let outcome = serde_json::from_value::<TranscriptRequest>(arguments)
+        .map_err(|e| format!("Invalid arguments: {}", e))
+        .and_then(get_transcript);
+    let (text, is_error) = match outcome {
+        Ok(text) => (text, false),
+        Err(message) => (message, true),
+    };
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
@@ -0,0 +1,162 @@
+use serde_json::Value;
+
+use crate::http;
+use crate::transcript::TranscriptSegment;
+
+const DEEPL_URL: &str = "https://api.deepl.com/v2/translate";
+const DEEPL_FREE_URL: &str = "https://api-free.deepl.com/v2/translate";
+const LIBRETRANSLATE_URL: &str = "https://libretranslate.com";
+
+/// Texts sent per request; DeepL accepts at most 50.
+const BATCH: usize = 50;
+
+/// A machine translation service.
+pub enum Translator {
+    /// DeepL's API, with a key from `DEEPL_API_KEY`. Free keys end in `:fx` and use the free
+    /// endpoint.
+    Deepl { url: String, api_key: String },
+    /// A LibreTranslate server, `LIBRETRANSLATE_URL` or libretranslate.com, with an optional
+    /// `LIBRETRANSLATE_API_KEY`.
+    LibreTranslate {
+        url: String,
+        api_key: Option<String>,
+    },
+}
+
+impl Translator {
+    pub fn deepl_from_env() -> Result<Translator, String> {
+        let api_key = std::env::var("DEEPL_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| "DEEPL_API_KEY is not set".to_string())?;
+        let url = if api_key.ends_with(":fx") {
+            DEEPL_FREE_URL
+        } else {
+            DEEPL_URL
+        };
+        Ok(Translator::Deepl {
+            url: url.to_string(),
+            api_key,
+        })
+    }
+
+    pub fn libretranslate_from_env() -> Translator {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        Translator::LibreTranslate {
+            url: var("LIBRETRANSLATE_URL").unwrap_or_else(|| LIBRETRANSLATE_URL.to_string()),
+            api_key: var("LIBRETRANSLATE_API_KEY"),
+        }
+    }
+
+    /// Translates each text from `source` (detected when `None`) into `target`, keeping the
+    /// order.
+    pub fn translate(
+        &self,
+        texts: &[String],
+        source: Option<&str>,
+        target: &str,
+    ) -> Result<Vec<String>, String> {
+        let mut translated = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(BATCH) {
+            let (url, body, auth) = match self {
+                Translator::Deepl { url, api_key } => {
+                    let mut body = serde_json::json!({
+                        "text": batch,
+                        "target_lang": target.to_uppercase(),
+                        "preserve_formatting": true,
+                    });
+                    if let Some(source) = source {
+                        body["source_lang"] = primary_subtag(source).to_uppercase().into();
+                    }
+                    (
+                        url.clone(),
+                        body,
+                        Some(format!("DeepL-Auth-Key {}", api_key)),
+                    )
+                }
+                Translator::LibreTranslate { url, api_key } => {
+                    let source =
+                        source.map_or("auto".to_string(), |s| primary_subtag(s).to_lowercase());
+                    let mut body = serde_json::json!({
+                        "q": batch,
+                        "source": source,
+                        "target": target,
+                        "format": "text",
+                    });
+                    if let Some(key) = api_key {
+                        body["api_key"] = key.as_str().into();
+                    }
+                    let url = format!("{}/translate", url.trim_end_matches('/'));
+                    (url, body, None)
+                }
+            };
+            let response = post(&url, body, auth.as_deref())?;
+            let results: Vec<String> = match self {
+                Translator::Deepl { .. } => response["translations"]
+                    .as_array()
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|t| t["text"].as_str())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                Translator::LibreTranslate { .. } => {
+                    serde_json::from_value(response["translatedText"].clone()).unwrap_or_default()
+                }
+            };
+            if results.len() != batch.len() {
+                return Err(format!(
+                    "{} translations in the response from {} for {} texts",
+                    results.len(),
+                    url,
+                    batch.len()
+                ));
+            }
+            translated.extend(results);
+        }
+        Ok(translated)
+    }
+}
+
+/// Replaces the text of every segment with its translation. Timing and speakers are kept; word
+/// timings no longer match the text and are dropped.
+pub fn translate_segments(
+    translator: &Translator,
+    segments: &mut [TranscriptSegment],
+    source: Option<&str>,
+    target: &str,
+) -> Result<(), String> {
+    let texts: Vec<String> = segments.iter().map(|seg| seg.text.clone()).collect();
+    let translated = translator.translate(&texts, source, target)?;
+    for (seg, text) in segments.iter_mut().zip(translated) {
+        seg.text = text;
+        seg.words = None;
+    }
+    Ok(())
+}
+
+/// `en` of `en-GB`; YouTube also uses tracks like `en-orig`.
+fn primary_subtag(language: &str) -> &str {
+    language.split(['-', '_']).next().unwrap_or(language)
+}
+
+fn post(url: &str, body: Value, auth: Option<&str>) -> Result<Value, String> {
+    let mut request = http::agent()
+        .post(url)
+        .set("Content-Type", "application/json");
+    if let Some(auth) = auth {
+        request = request.set("Authorization", auth);
+    }
+    match request.send_json(body) {
+        Ok(response) => response
+            .into_json()
+            .map_err(|e| format!("invalid response from {} - {}", url, e)),
+        Err(ureq::Error::Status(code, response)) => {
+            let detail = response.into_string().unwrap_or_default();
+            Err(format!("HTTP {} from {} {}", code, url, detail.trim()))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
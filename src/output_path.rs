@@ -0,0 +1,199 @@
+use crate::transcript::TranscriptResult;
+
+/// Fields an output path template can use, as in `{channel}/{upload_date}-{title}.{ext}`.
+pub const FIELDS: [&str; 6] = ["id", "title", "channel", "upload_date", "language", "ext"];
+
+/// Substituted for metadata the video does not have, as yt-dlp does.
+const MISSING: &str = "NA";
+
+/// Longest file or directory name produced from one field, in bytes.
+const MAX_COMPONENT: usize = 200;
+
+/// Whether an `--output` value is a template rather than a plain path.
+pub fn is_template(output: &str) -> bool {
+    output.contains('{')
+}
+
+/// Checks that every `{field}` of a template is known and every brace is closed.
+pub fn check(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            return Err(format!("unclosed '{{' in output template '{}'", template));
+        };
+        let field = &rest[open + 1..open + close];
+        if !FIELDS.contains(&field) {
+            return Err(format!(
+                "unknown field '{{{}}}' in output template (expected one of {})",
+                field,
+                FIELDS.map(|f| format!("{{{}}}", f)).join(", ")
+            ));
+        }
+        rest = &rest[open + close + 1..];
+    }
+    Ok(())
+}
+
+/// Fills in a checked template from a transcript's metadata. Each value is sanitized into a
+/// single file name, so a title cannot add directories or escape the output directory; the
+/// template's own `/` still separates directories.
+pub fn expand(template: &str, result: &TranscriptResult, extension: &str) -> String {
+    let video = result.video.as_ref();
+    let mut path = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        path.push_str(&rest[..open]);
+        let value = match &rest[open + 1..open + close] {
+            "id" => Some(result.video_id.as_str()),
+            "title" => video.and_then(|v| v.title.as_deref()),
+            "channel" => video.and_then(|v| v.channel.as_deref()),
+            "upload_date" => video.and_then(|v| v.upload_date.as_deref()),
+            "language" => Some(result.language.as_str()),
+            "ext" => Some(extension),
+            _ => None,
+        };
+        path.push_str(&sanitize(value.unwrap_or(MISSING)));
+        rest = &rest[open + close + 1..];
+    }
+    path.push_str(rest);
+    path
+}
+
+/// Makes a metadata value safe as one file or directory name on every platform: path
+/// separators, reserved and control characters become `_`, surrounding dots and spaces are
+/// removed, and the name is cut to `MAX_COMPONENT` bytes.
+pub fn sanitize(value: &str) -> String {
+    let replaced: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let mut name = replaced
+        .trim_matches(|c: char| c == '.' || c.is_whitespace())
+        .to_string();
+    if name.len() > MAX_COMPONENT {
+        let mut end = MAX_COMPONENT;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+        name = name.trim_end().to_string();
+    }
+    if name.is_empty() {
+        MISSING.to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{Metadata, VideoInfo, SCHEMA_VERSION};
+
+    fn result(video: Option<VideoInfo>) -> TranscriptResult {
+        TranscriptResult {
+            schema_version: SCHEMA_VERSION,
+            video_id: "dQw4w9WgXcQ".to_string(),
+            language: "en".to_string(),
+            segments: Vec::new(),
+            metadata: Metadata {
+                total_segments: 0,
+                extracted_at: String::new(),
+                auto_generated: false,
+                total_tokens: None,
+            },
+            video,
+            keywords: None,
+            marks: None,
+            toc: None,
+            summary: None,
+            chunks: None,
+        }
+    }
+
+    fn video(title: &str, channel: &str) -> Option<VideoInfo> {
+        Some(VideoInfo {
+            title: Some(title.to_string()),
+            channel: Some(channel.to_string()),
+            upload_date: Some("20240101".to_string()),
+            ..VideoInfo::default()
+        })
+    }
+
+    #[test]
+    fn detects_templates() {
+        assert!(is_template("{id}.txt"));
+        assert!(!is_template("out/transcript.txt"));
+    }
+
+    #[test]
+    fn checks_fields() {
+        assert!(check("{channel}/{upload_date}-{title}.{ext}").is_ok());
+        assert!(check("plain.txt").is_ok());
+        assert!(check("{uploader}.txt")
+            .unwrap_err()
+            .contains("unknown field '{uploader}'"));
+        assert!(check("{title.txt").unwrap_err().contains("unclosed"));
+    }
+
+    #[test]
+    fn expands_metadata() {
+        let result = result(video("Talk", "Chan"));
+        assert_eq!(
+            expand("{channel}/{upload_date}-{title}.{ext}", &result, "srt"),
+            "Chan/20240101-Talk.srt"
+        );
+        assert_eq!(
+            expand("{id}.{language}.txt", &result, "txt"),
+            "dQw4w9WgXcQ.en.txt"
+        );
+    }
+
+    #[test]
+    fn keeps_separators_in_metadata_out_of_the_path() {
+        let result = result(video("../../etc/passwd", "AC/DC\\Live"));
+        assert_eq!(
+            expand("out/{channel}/{title}.{ext}", &result, "txt"),
+            "out/AC_DC_Live/_.._etc_passwd.txt"
+        );
+    }
+
+    #[test]
+    fn fills_missing_metadata() {
+        assert_eq!(
+            expand("{channel}/{title}.{ext}", &result(None), "txt"),
+            "NA/NA.txt"
+        );
+        assert_eq!(
+            expand("{title}.txt", &result(video("", "Chan")), "txt"),
+            "NA.txt"
+        );
+    }
+
+    #[test]
+    fn sanitizes_names() {
+        assert_eq!(
+            sanitize("What? A \"quote\" <b>|c*"),
+            "What_ A _quote_ _b__c_"
+        );
+        assert_eq!(sanitize("line\nbreak\ttab"), "line_break_tab");
+        assert_eq!(sanitize("  .hidden. "), "hidden");
+        assert_eq!(sanitize(".."), "NA");
+        assert_eq!(sanitize(""), "NA");
+    }
+
+    #[test]
+    fn truncates_long_names_on_char_boundaries() {
+        let name = sanitize(&"é".repeat(150));
+        assert_eq!(name.len(), MAX_COMPONENT);
+        assert!(name.chars().all(|c| c == 'é'));
+        assert_eq!(sanitize(&"x".repeat(500)).len(), MAX_COMPONENT);
+    }
+}
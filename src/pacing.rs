@@ -0,0 +1,55 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::timing::parse_duration;
+
+/// Pause before the first retry; it doubles with every further one.
+const FIRST_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Longest pause between retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A pause between requests, picked uniformly from `min..=max` each time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SleepRange {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl SleepRange {
+    /// Parses `2..5` (seconds) or a single duration such as `3`, `1.5s` or `1m` for a fixed pause.
+    pub fn parse(input: &str) -> Option<SleepRange> {
+        let (min, max) = match input.split_once("..") {
+            Some((min, max)) => (parse_duration(min)?, parse_duration(max)?),
+            None => {
+                let value = parse_duration(input)?;
+                (value, value)
+            }
+        };
+        (min <= max).then(|| SleepRange {
+            min: Duration::from_secs_f64(min),
+            max: Duration::from_secs_f64(max),
+        })
+    }
+
+    /// A random pause within the range.
+    pub fn pick(&self) -> Duration {
+        self.min + (self.max - self.min).mul_f64(random_fraction())
+    }
+}
+
+/// A random number in `0.0..1.0`, good enough for spreading out requests.
+pub fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Pause before retry number `attempt` (0 for the first): [`FIRST_BACKOFF`] doubling each time
+/// up to [`MAX_BACKOFF`], with the upper half randomized so that parallel runs spread out.
+pub fn backoff(attempt: u32) -> Duration {
+    let delay = FIRST_BACKOFF
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_BACKOFF);
+    delay / 2 + (delay / 2).mul_f64(random_fraction())
+}
@@ -0,0 +1,352 @@
+use prost::Message;
+
+use crate::{chunk, keywords, marks, summary, toc, transcript};
+
+/// The shipped `.proto` definition, printed by `yt-transcriber schema --proto`.
+pub const PROTO: &str = include_str!("../proto/transcript.proto");
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Transcript {
+    #[prost(uint32, tag = "1")]
+    pub schema_version: u32,
+    #[prost(string, tag = "2")]
+    pub video_id: String,
+    #[prost(string, tag = "3")]
+    pub language: String,
+    #[prost(message, repeated, tag = "4")]
+    pub segments: Vec<Segment>,
+    #[prost(message, optional, tag = "5")]
+    pub metadata: Option<Metadata>,
+    #[prost(message, optional, tag = "6")]
+    pub video: Option<VideoInfo>,
+    #[prost(message, repeated, tag = "7")]
+    pub keywords: Vec<Keyword>,
+    #[prost(message, optional, tag = "8")]
+    pub summary: Option<Summary>,
+    #[prost(message, repeated, tag = "9")]
+    pub chunks: Vec<Chunk>,
+    #[prost(message, repeated, tag = "10")]
+    pub marks: Vec<Mark>,
+    #[prost(message, repeated, tag = "11")]
+    pub toc: Vec<TocEntry>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Segment {
+    #[prost(uint64, tag = "1")]
+    pub index: u64,
+    #[prost(string, tag = "2")]
+    pub text: String,
+    #[prost(double, tag = "3")]
+    pub start_seconds: f64,
+    #[prost(double, tag = "4")]
+    pub end_seconds: f64,
+    #[prost(double, tag = "5")]
+    pub duration_seconds: f64,
+    #[prost(string, optional, tag = "6")]
+    pub speaker: Option<String>,
+    #[prost(message, optional, tag = "7")]
+    pub settings: Option<CueSettings>,
+    #[prost(message, repeated, tag = "8")]
+    pub words: Vec<Word>,
+    #[prost(uint64, optional, tag = "9")]
+    pub tokens: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Word {
+    #[prost(string, tag = "1")]
+    pub text: String,
+    #[prost(double, tag = "2")]
+    pub start: f64,
+    #[prost(double, tag = "3")]
+    pub end: f64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CueSettings {
+    #[prost(string, optional, tag = "1")]
+    pub vertical: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub line: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub position: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub size: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub align: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub region: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Metadata {
+    #[prost(uint64, tag = "1")]
+    pub total_segments: u64,
+    #[prost(string, tag = "2")]
+    pub extracted_at: String,
+    #[prost(bool, tag = "3")]
+    pub auto_generated: bool,
+    #[prost(uint64, optional, tag = "4")]
+    pub total_tokens: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct VideoInfo {
+    #[prost(string, optional, tag = "1")]
+    pub title: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub channel: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub upload_date: Option<String>,
+    #[prost(string, repeated, tag = "4")]
+    pub tags: Vec<String>,
+    #[prost(message, repeated, tag = "5")]
+    pub chapters: Vec<Chapter>,
+    #[prost(bool, tag = "6")]
+    pub chapters_generated: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Chapter {
+    #[prost(string, tag = "1")]
+    pub title: String,
+    #[prost(double, tag = "2")]
+    pub start_time: f64,
+    #[prost(double, tag = "3")]
+    pub end_time: f64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Keyword {
+    #[prost(string, tag = "1")]
+    pub phrase: String,
+    #[prost(double, tag = "2")]
+    pub score: f64,
+    #[prost(double, tag = "3")]
+    pub first_seconds: f64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Summary {
+    #[prost(string, tag = "1")]
+    pub text: String,
+    #[prost(message, repeated, tag = "2")]
+    pub key_points: Vec<KeyPoint>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct KeyPoint {
+    #[prost(double, tag = "1")]
+    pub seconds: f64,
+    #[prost(string, tag = "2")]
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Chunk {
+    #[prost(string, tag = "1")]
+    pub video_id: String,
+    #[prost(string, optional, tag = "2")]
+    pub title: Option<String>,
+    #[prost(uint64, tag = "3")]
+    pub chunk: u64,
+    #[prost(double, tag = "4")]
+    pub start_seconds: f64,
+    #[prost(double, tag = "5")]
+    pub end_seconds: f64,
+    #[prost(uint64, tag = "6")]
+    pub tokens: u64,
+    #[prost(string, tag = "7")]
+    pub text: String,
+    #[prost(string, tag = "8")]
+    pub url: String,
+    #[prost(float, repeated, tag = "9")]
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Mark {
+    #[prost(string, tag = "1")]
+    pub keyword: String,
+    #[prost(double, tag = "2")]
+    pub seconds: f64,
+    #[prost(string, tag = "3")]
+    pub url: String,
+    #[prost(string, tag = "4")]
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TocEntry {
+    #[prost(double, tag = "1")]
+    pub seconds: f64,
+    #[prost(string, tag = "2")]
+    pub url: String,
+    #[prost(string, tag = "3")]
+    pub text: String,
+}
+
+/// Encodes a transcript as one `Transcript` message.
+pub fn encode(result: &transcript::TranscriptResult) -> Vec<u8> {
+    Transcript::from(result).encode_to_vec()
+}
+
+impl From<&transcript::TranscriptResult> for Transcript {
+    fn from(result: &transcript::TranscriptResult) -> Self {
+        Transcript {
+            schema_version: result.schema_version,
+            video_id: result.video_id.clone(),
+            language: result.language.clone(),
+            segments: result.segments.iter().map(Segment::from).collect(),
+            metadata: Some(Metadata::from(&result.metadata)),
+            video: result.video.as_ref().map(VideoInfo::from),
+            keywords: list(&result.keywords),
+            summary: result.summary.as_ref().map(Summary::from),
+            chunks: list(&result.chunks),
+            marks: list(&result.marks),
+            toc: list(&result.toc),
+        }
+    }
+}
+
+/// Converts an optional list, which protobuf has no way to tell apart from an empty one.
+fn list<'a, T: 'a, M: From<&'a T>>(items: &'a Option<Vec<T>>) -> Vec<M> {
+    items.iter().flatten().map(M::from).collect()
+}
+
+impl From<&transcript::TranscriptSegment> for Segment {
+    fn from(seg: &transcript::TranscriptSegment) -> Self {
+        Segment {
+            index: seg.index as u64,
+            text: seg.text.clone(),
+            start_seconds: seg.start_seconds,
+            end_seconds: seg.end_seconds,
+            duration_seconds: seg.duration_seconds,
+            speaker: seg.speaker.clone(),
+            settings: seg.settings.as_ref().map(CueSettings::from),
+            words: list(&seg.words),
+            tokens: seg.tokens.map(|tokens| tokens as u64),
+        }
+    }
+}
+
+impl From<&transcript::Word> for Word {
+    fn from(word: &transcript::Word) -> Self {
+        Word {
+            text: word.text.clone(),
+            start: word.start,
+            end: word.end,
+        }
+    }
+}
+
+impl From<&transcript::CueSettings> for CueSettings {
+    fn from(settings: &transcript::CueSettings) -> Self {
+        CueSettings {
+            vertical: settings.vertical.clone(),
+            line: settings.line.clone(),
+            position: settings.position.clone(),
+            size: settings.size.clone(),
+            align: settings.align.clone(),
+            region: settings.region.clone(),
+        }
+    }
+}
+
+impl From<&transcript::Metadata> for Metadata {
+    fn from(metadata: &transcript::Metadata) -> Self {
+        Metadata {
+            total_segments: metadata.total_segments as u64,
+            extracted_at: metadata.extracted_at.clone(),
+            auto_generated: metadata.auto_generated,
+            total_tokens: metadata.total_tokens.map(|tokens| tokens as u64),
+        }
+    }
+}
+
+impl From<&transcript::VideoInfo> for VideoInfo {
+    fn from(video: &transcript::VideoInfo) -> Self {
+        VideoInfo {
+            title: video.title.clone(),
+            channel: video.channel.clone(),
+            upload_date: video.upload_date.clone(),
+            tags: video.tags.clone().unwrap_or_default(),
+            chapters: list(&video.chapters),
+            chapters_generated: video.chapters_generated,
+        }
+    }
+}
+
+impl From<&transcript::Chapter> for Chapter {
+    fn from(chapter: &transcript::Chapter) -> Self {
+        Chapter {
+            title: chapter.title.clone(),
+            start_time: chapter.start_time,
+            end_time: chapter.end_time,
+        }
+    }
+}
+
+impl From<&keywords::Keyword> for Keyword {
+    fn from(keyword: &keywords::Keyword) -> Self {
+        Keyword {
+            phrase: keyword.phrase.clone(),
+            score: keyword.score,
+            first_seconds: keyword.first_seconds,
+        }
+    }
+}
+
+impl From<&summary::Summary> for Summary {
+    fn from(summary: &summary::Summary) -> Self {
+        Summary {
+            text: summary.text.clone(),
+            key_points: summary
+                .key_points
+                .iter()
+                .map(|point| KeyPoint {
+                    seconds: point.seconds,
+                    text: point.text.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&chunk::Chunk> for Chunk {
+    fn from(chunk: &chunk::Chunk) -> Self {
+        Chunk {
+            video_id: chunk.video_id.clone(),
+            title: chunk.title.clone(),
+            chunk: chunk.chunk as u64,
+            start_seconds: chunk.start_seconds,
+            end_seconds: chunk.end_seconds,
+            tokens: chunk.tokens as u64,
+            text: chunk.text.clone(),
+            url: chunk.url.clone(),
+            embedding: chunk.embedding.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&marks::Mark> for Mark {
+    fn from(mark: &marks::Mark) -> Self {
+        Mark {
+            keyword: mark.keyword.clone(),
+            seconds: mark.seconds,
+            url: mark.url.clone(),
+            text: mark.text.clone(),
+        }
+    }
+}
+
+impl From<&toc::TocEntry> for TocEntry {
+    fn from(entry: &toc::TocEntry) -> Self {
+        TocEntry {
+            seconds: entry.seconds,
+            url: entry.url.clone(),
+            text: entry.text.clone(),
+        }
+    }
+}
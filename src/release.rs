@@ -0,0 +1,198 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::http;
+
+/// A GitHub release and its downloadable files.
+#[derive(Deserialize)]
+pub struct Release {
+    #[serde(rename = "tag_name")]
+    pub tag: String,
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+pub struct Asset {
+    pub name: String,
+    #[serde(rename = "browser_download_url")]
+    pub url: String,
+}
+
+impl Release {
+    /// The latest published release of `repo` (`owner/name`).
+    pub fn latest(repo: &str) -> Result<Release, String> {
+        Release::fetch(&format!(
+            "https://api.github.com/repos/{}/releases/latest",
+            repo
+        ))
+    }
+
+    /// The release of `repo` tagged `tag`.
+    pub fn tagged(repo: &str, tag: &str) -> Result<Release, String> {
+        Release::fetch(&format!(
+            "https://api.github.com/repos/{}/releases/tags/{}",
+            repo, tag
+        ))
+    }
+
+    fn fetch(url: &str) -> Result<Release, String> {
+        match http::agent()
+            .get(url)
+            .set("Accept", "application/vnd.github+json")
+            .call()
+        {
+            Ok(response) => response.into_json().map_err(|e| e.to_string()),
+            Err(ureq::Error::Status(code, _)) => Err(format!("HTTP {}", code)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
+    /// The tag without a leading `v`.
+    pub fn version(&self) -> &str {
+        self.tag.trim_start_matches('v')
+    }
+}
+
+/// Downloads `url` into memory.
+pub fn download(url: &str) -> Result<Vec<u8>, String> {
+    let response = match http::agent().get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(code, _)) => return Err(format!("HTTP {}", code)),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Checks `bytes` against the entry for `name` in a `sha256sum` style checksum file
+/// (`<hex digest>  <file name>` per line).
+pub fn verify_checksum(bytes: &[u8], sums: &str, name: &str) -> Result<(), String> {
+    let expected = sums
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, file)| file.trim().trim_start_matches('*') == name)
+        .map(|(digest, _)| digest.to_ascii_lowercase())
+        .ok_or_else(|| format!("no checksum listed for {}", name))?;
+    let actual = sha256_hex(bytes);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "checksum mismatch for {} (expected {}, got {})",
+            name, expected, actual
+        ))
+    }
+}
+
+/// Whether dotted version `a` is newer than `b`, comparing numeric components.
+pub fn is_newer(a: &str, b: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    };
+    parse(a) > parse(b)
+}
+
+/// Replaces the executable at `path` with `bytes`. The new file is written next to it and renamed
+/// over it, so the old binary stays intact if anything fails. On Windows, where a running
+/// executable cannot be overwritten, the old one is moved aside to `<name>.old` first.
+pub fn replace_executable(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut new = tempfile::NamedTempFile::new_in(dir)?;
+    io::Write::write_all(&mut new, bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(new.path(), fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(windows)]
+    if path.exists() {
+        let old = path.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(path, &old)?;
+    }
+    new.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: &str = "yt-transcriber-x86_64-linux";
+
+    fn sums(line: &str) -> String {
+        format!(
+            "{}  yt-transcriber-aarch64-macos\n{}\n",
+            sha256_hex(b"other"),
+            line
+        )
+    }
+
+    #[test]
+    fn accepts_a_matching_digest() {
+        let digest = sha256_hex(b"binary");
+        assert_eq!(
+            verify_checksum(b"binary", &sums(&format!("{}  {}", digest, NAME)), NAME),
+            Ok(())
+        );
+        let upper = digest.to_ascii_uppercase();
+        assert_eq!(
+            verify_checksum(b"binary", &sums(&format!("{}  {}", upper, NAME)), NAME),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn accepts_binary_mode_lines() {
+        let line = format!("{} *{}", sha256_hex(b"binary"), NAME);
+        assert_eq!(verify_checksum(b"binary", &sums(&line), NAME), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_mismatched_digest() {
+        let line = format!("{}  {}", sha256_hex(b"binary"), NAME);
+        let err = verify_checksum(b"tampered", &sums(&line), NAME).unwrap_err();
+        assert!(err.starts_with("checksum mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_a_missing_entry() {
+        let line = format!("{}  {}.sig", sha256_hex(b"binary"), NAME);
+        assert_eq!(
+            verify_checksum(b"binary", &sums(&line), NAME),
+            Err(format!("no checksum listed for {}", NAME))
+        );
+        assert!(verify_checksum(b"binary", "", NAME).is_err());
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer("1.10.0", "1.9.9"));
+        assert!(is_newer("v2.0.0", "1.99.99"));
+        assert!(is_newer("1.0.1", "1.0"));
+        assert!(!is_newer("1.9.9", "1.10.0"));
+        assert!(!is_newer("1.0.0", "v1.0.0"));
+    }
+}
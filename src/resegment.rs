@@ -0,0 +1,453 @@
+use crate::transcript::{TranscriptSegment, Word};
+
+/// Merges consecutive segments into paragraphs while the silence between them is under
+/// `max_gap` seconds and the speaker stays the same. A paragraph is closed once it spans
+/// `max_duration` seconds so continuous speech does not collapse into a single block.
+pub fn merge_paragraphs(
+    segments: Vec<TranscriptSegment>,
+    max_gap: f64,
+    max_duration: f64,
+) -> Vec<TranscriptSegment> {
+    let mut paragraphs: Vec<TranscriptSegment> = Vec::new();
+
+    for seg in segments {
+        if let Some(last) = paragraphs.last_mut() {
+            let gap = seg.start_seconds - last.end_seconds;
+            if gap < max_gap
+                && seg.end_seconds - last.start_seconds <= max_duration
+                && seg.speaker == last.speaker
+            {
+                last.text.push(' ');
+                last.text.push_str(&seg.text);
+                last.end_seconds = last.end_seconds.max(seg.end_seconds);
+                last.duration_seconds = last.end_seconds - last.start_seconds;
+                if let Some(words) = seg.words {
+                    last.words.get_or_insert_with(Vec::new).extend(words);
+                }
+                continue;
+            }
+        }
+        paragraphs.push(seg);
+    }
+
+    paragraphs
+}
+
+struct TimedWord<'a> {
+    text: String,
+    start: f64,
+    end: f64,
+    speaker: Option<&'a str>,
+    /// Timing comes from the caption's own word timestamps rather than interpolation.
+    exact: bool,
+}
+
+/// Uses each segment's word timestamps when they cover its whole text, and otherwise spreads the
+/// segment's duration over its words in proportion to their length.
+fn timed_words(segments: &[TranscriptSegment]) -> Vec<TimedWord<'_>> {
+    let mut words = Vec::new();
+
+    for seg in segments {
+        let parts: Vec<&str> = seg.text.split_whitespace().collect();
+        if let Some(exact) = seg.words.as_ref().filter(|w| w.len() == parts.len()) {
+            words.extend(parts.iter().zip(exact).map(|(part, word)| TimedWord {
+                text: part.to_string(),
+                start: word.start,
+                end: word.end,
+                speaker: seg.speaker.as_deref(),
+                exact: true,
+            }));
+            continue;
+        }
+
+        let total: usize = parts.iter().map(|w| w.chars().count()).sum();
+        if total == 0 {
+            continue;
+        }
+
+        let mut consumed = 0;
+        for part in parts {
+            let len = part.chars().count();
+            let start = seg.start_seconds + seg.duration_seconds * consumed as f64 / total as f64;
+            consumed += len;
+            let end = seg.start_seconds + seg.duration_seconds * consumed as f64 / total as f64;
+            words.push(TimedWord {
+                text: part.to_string(),
+                start,
+                end,
+                speaker: seg.speaker.as_deref(),
+                exact: false,
+            });
+        }
+    }
+
+    words
+}
+
+/// The words' own timings, when every one of them came from the captions.
+fn exact_words(words: &[&TimedWord]) -> Option<Vec<Word>> {
+    words.iter().all(|w| w.exact).then(|| {
+        words
+            .iter()
+            .map(|w| Word {
+                text: w.text.clone(),
+                start: w.start,
+                end: w.end,
+            })
+            .collect()
+    })
+}
+
+fn ends_sentence(word: &str) -> bool {
+    word.trim_end_matches(['"', '\'', ')', ']', '»', '”', '’'])
+        .ends_with(['.', '!', '?', '…'])
+}
+
+fn starts_sentence(word: &str) -> bool {
+    word.trim_start_matches(['"', '\'', '(', '[', '«', '“', '‘', '-'])
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_uppercase() || c.is_numeric())
+}
+
+/// Re-segments the transcript into sentences: a sentence ends at `.`, `!`, `?` or `…` when the
+/// next word starts with a capital letter or digit. Timestamps come from word timings when the
+/// captions have them and are otherwise interpolated from the original cues. Transcripts without
+/// any sentence punctuation are returned unchanged.
+pub fn split_sentences(segments: Vec<TranscriptSegment>) -> Vec<TranscriptSegment> {
+    let words = timed_words(&segments);
+    if !words.iter().any(|w| ends_sentence(&w.text)) {
+        return segments;
+    }
+
+    let mut sentences = Vec::new();
+    let mut current: Vec<&TimedWord> = Vec::new();
+
+    for (i, word) in words.iter().enumerate() {
+        current.push(word);
+        let boundary = match words.get(i + 1) {
+            Some(next) => ends_sentence(&word.text) && starts_sentence(&next.text),
+            None => true,
+        };
+
+        if boundary {
+            let start = current[0].start;
+            let end = word.end;
+            sentences.push(TranscriptSegment {
+                index: sentences.len(),
+                text: current
+                    .iter()
+                    .map(|w| w.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                start_seconds: start,
+                end_seconds: end,
+                duration_seconds: end - start,
+                speaker: current[0].speaker.map(str::to_string),
+                settings: None,
+                words: exact_words(&current),
+                tokens: None,
+            });
+            current.clear();
+        }
+    }
+
+    sentences
+}
+
+/// Greedily wraps words into lines of at most `max_line_length` characters (a longer word gets a
+/// line of its own).
+fn wrap_lines<'w, 'a>(
+    words: &'w [TimedWord<'a>],
+    max_line_length: usize,
+) -> Vec<Vec<&'w TimedWord<'a>>> {
+    let mut lines: Vec<Vec<&TimedWord>> = Vec::new();
+    let mut width = 0;
+
+    for word in words {
+        let len = word.text.chars().count();
+        match lines.last_mut() {
+            Some(line) if width + 1 + len <= max_line_length => {
+                line.push(word);
+                width += 1 + len;
+            }
+            _ => {
+                lines.push(vec![word]);
+                width = len;
+            }
+        }
+    }
+
+    lines
+}
+
+/// Builds one piece of `seg` from a run of its words. The first and last pieces keep the
+/// segment's own start and end times.
+fn split_piece(
+    seg: &TranscriptSegment,
+    words: &[&TimedWord],
+    text: String,
+    first: bool,
+    last: bool,
+) -> TranscriptSegment {
+    let start = if first {
+        seg.start_seconds
+    } else {
+        words[0].start
+    };
+    let end = if last {
+        seg.end_seconds
+    } else {
+        words[words.len() - 1].end
+    };
+    TranscriptSegment {
+        index: 0,
+        text,
+        start_seconds: start,
+        end_seconds: end,
+        duration_seconds: end - start,
+        speaker: seg.speaker.clone(),
+        settings: seg.settings.clone(),
+        words: exact_words(words),
+        tokens: None,
+    }
+}
+
+fn join_lines(lines: &[Vec<&TimedWord>]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|w| w.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Re-wraps each cue into lines of at most `max_line_length` characters and, when it needs more
+/// than `max_lines` lines, splits it into consecutive cues at line boundaries. Split cues take
+/// their timing from word timestamps or, without them, from interpolation by word length.
+pub fn wrap_cues(
+    segments: Vec<TranscriptSegment>,
+    max_line_length: usize,
+    max_lines: Option<usize>,
+) -> Vec<TranscriptSegment> {
+    let max_lines = max_lines.unwrap_or(usize::MAX).max(1);
+    let mut output = Vec::with_capacity(segments.len());
+
+    for seg in segments {
+        let words = timed_words(std::slice::from_ref(&seg));
+        let lines = wrap_lines(&words, max_line_length);
+        let chunks: Vec<&[Vec<&TimedWord>]> = lines.chunks(max_lines).collect();
+        if chunks.len() <= 1 {
+            let text = join_lines(&lines);
+            output.push(TranscriptSegment { text, ..seg });
+            continue;
+        }
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let chunk_words: Vec<&TimedWord> = chunk.iter().flatten().copied().collect();
+            output.push(split_piece(
+                &seg,
+                &chunk_words,
+                join_lines(chunk),
+                i == 0,
+                i == chunks.len() - 1,
+            ));
+        }
+    }
+
+    output
+}
+
+/// Splits cues longer than `max_duration` seconds at word boundaries into pieces of at most that
+/// length, timed from word timestamps or by interpolation. A single word is never split.
+pub fn split_long_segments(
+    segments: Vec<TranscriptSegment>,
+    max_duration: f64,
+) -> Vec<TranscriptSegment> {
+    let mut output = Vec::with_capacity(segments.len());
+
+    for seg in segments {
+        if seg.duration_seconds <= max_duration {
+            output.push(seg);
+            continue;
+        }
+
+        let words = timed_words(std::slice::from_ref(&seg));
+        let mut pieces: Vec<Vec<&TimedWord>> = Vec::new();
+        for word in &words {
+            match pieces.last_mut() {
+                Some(piece) if word.end - piece[0].start <= max_duration => piece.push(word),
+                _ => pieces.push(vec![word]),
+            }
+        }
+        if pieces.len() <= 1 {
+            output.push(seg);
+            continue;
+        }
+
+        for (i, piece) in pieces.iter().enumerate() {
+            let text = piece
+                .iter()
+                .map(|w| w.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            output.push(split_piece(
+                &seg,
+                piece,
+                text,
+                i == 0,
+                i == pieces.len() - 1,
+            ));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A segment whose words each last one second, starting at `start`.
+    fn timed_segment(start: f64, text: &str) -> TranscriptSegment {
+        let words: Vec<Word> = text
+            .split_whitespace()
+            .enumerate()
+            .map(|(i, word)| Word {
+                text: word.to_string(),
+                start: start + i as f64,
+                end: start + i as f64 + 1.0,
+            })
+            .collect();
+        let mut seg = TranscriptSegment::new(start, start + words.len() as f64, text);
+        seg.words = Some(words);
+        seg
+    }
+
+    fn cues(segments: &[TranscriptSegment]) -> Vec<(&str, f64, f64)> {
+        segments
+            .iter()
+            .map(|seg| (seg.text.as_str(), seg.start_seconds, seg.end_seconds))
+            .collect()
+    }
+
+    #[test]
+    fn wraps_lines_within_a_cue() {
+        let wrapped = wrap_cues(
+            vec![TranscriptSegment::new(0.0, 3.0, "one two three")],
+            7,
+            None,
+        );
+        assert_eq!(cues(&wrapped), [("one two\nthree", 0.0, 3.0)]);
+    }
+
+    #[test]
+    fn gives_long_words_their_own_line() {
+        let wrapped = wrap_cues(
+            vec![TranscriptSegment::new(0.0, 2.0, "a extraordinary b")],
+            5,
+            None,
+        );
+        assert_eq!(wrapped[0].text, "a\nextraordinary\nb");
+    }
+
+    #[test]
+    fn splits_cues_over_max_lines() {
+        let wrapped = wrap_cues(vec![timed_segment(0.0, "one two three four")], 9, Some(1));
+        assert_eq!(
+            cues(&wrapped),
+            [
+                ("one two", 0.0, 2.0),
+                ("three", 2.0, 3.0),
+                ("four", 3.0, 4.0)
+            ]
+        );
+        assert_eq!(wrapped[1].words.as_ref().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn split_cues_keep_the_original_edges() {
+        let wrapped = wrap_cues(vec![TranscriptSegment::new(1.0, 5.0, "aa bb")], 2, Some(1));
+        assert_eq!(wrapped.len(), 2);
+        assert_eq!(wrapped[0].start_seconds, 1.0);
+        assert_eq!(wrapped[0].end_seconds, 3.0);
+        assert_eq!(wrapped[1].start_seconds, 3.0);
+        assert_eq!(wrapped[1].end_seconds, 5.0);
+        assert!(wrapped[0].words.is_none());
+    }
+
+    #[test]
+    fn wrap_keeps_empty_cues() {
+        let wrapped = wrap_cues(vec![TranscriptSegment::new(0.0, 1.0, "")], 10, Some(0));
+        assert_eq!(cues(&wrapped), [("", 0.0, 1.0)]);
+    }
+
+    #[test]
+    fn splits_long_segments_at_word_boundaries() {
+        let split = split_long_segments(vec![timed_segment(0.0, "a b c d e")], 2.0);
+        assert_eq!(
+            cues(&split),
+            [("a b", 0.0, 2.0), ("c d", 2.0, 4.0), ("e", 4.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn leaves_short_and_unsplittable_segments() {
+        let segments = vec![
+            TranscriptSegment::new(0.0, 2.0, "short one"),
+            TranscriptSegment::new(2.0, 12.0, "loooong"),
+            TranscriptSegment::new(12.0, 30.0, ""),
+        ];
+        let split = split_long_segments(segments, 5.0);
+        assert_eq!(
+            cues(&split),
+            [
+                ("short one", 0.0, 2.0),
+                ("loooong", 2.0, 12.0),
+                ("", 12.0, 30.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_sentences_across_cues() {
+        let segments = vec![
+            timed_segment(0.0, "Hello there. How"),
+            timed_segment(3.0, "are you? fine."),
+        ];
+        let sentences = split_sentences(segments);
+        assert_eq!(
+            cues(&sentences),
+            [("Hello there.", 0.0, 2.0), ("How are you? fine.", 2.0, 6.0)]
+        );
+        assert_eq!(sentences[1].index, 1);
+        assert_eq!(sentences[1].words.as_ref().map(Vec::len), Some(4));
+    }
+
+    #[test]
+    fn interpolates_sentence_times_without_word_timings() {
+        let sentences = split_sentences(vec![TranscriptSegment::new(0.0, 10.0, "Aaaa. Bbbb.")]);
+        assert_eq!(
+            cues(&sentences),
+            [("Aaaa.", 0.0, 5.0), ("Bbbb.", 5.0, 10.0)]
+        );
+        assert!(sentences[0].words.is_none());
+    }
+
+    #[test]
+    fn leaves_unpunctuated_transcripts_alone() {
+        let segments = vec![
+            TranscriptSegment::new(0.0, 1.0, "no punctuation"),
+            TranscriptSegment::new(1.0, 2.0, ""),
+        ];
+        assert_eq!(
+            cues(&split_sentences(segments)),
+            [("no punctuation", 0.0, 1.0), ("", 1.0, 2.0)]
+        );
+        assert!(split_sentences(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use regex::Regex;
+
+use crate::transcript::TranscriptSegment;
+
+pub enum Query {
+    /// Case-insensitive substring match.
+    Text(String),
+    Regex(Regex),
+    /// Approximate match allowing up to `max_distance` character edits.
+    Fuzzy { needle: Vec<char>, max_distance: usize },
+}
+
+impl Query {
+    pub fn text(query: &str) -> Self {
+        Query::Text(query.to_lowercase())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Query::Regex)
+    }
+
+    /// Builds a fuzzy query. Without an explicit limit, one edit is allowed per four
+    /// characters of the query.
+    pub fn fuzzy(query: &str, max_distance: Option<usize>) -> Self {
+        let needle: Vec<char> = normalize(query).chars().collect();
+        let max_distance = max_distance.unwrap_or((needle.len() / 4).max(1));
+        Query::Fuzzy { needle, max_distance }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Query::Text(q) => text.to_lowercase().contains(q),
+            Query::Regex(re) => re.is_match(text),
+            Query::Fuzzy { needle, max_distance } => {
+                let haystack: Vec<char> = normalize(text).chars().collect();
+                substring_distance(needle, &haystack) <= *max_distance
+            }
+        }
+    }
+}
+
+/// Lowercases and drops punctuation so quotes match regardless of caption styling.
+fn normalize(text: &str) -> String {
+    let cleaned: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Smallest edit distance between `needle` and any substring of `haystack` (Sellers' algorithm).
+fn substring_distance(needle: &[char], haystack: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=needle.len()).collect();
+    let mut best = needle.len();
+
+    for &h in haystack {
+        let mut cur = vec![0; needle.len() + 1];
+        for j in 1..=needle.len() {
+            let cost = if needle[j - 1] == h { 0 } else { 1 };
+            cur[j] = (prev[j - 1] + cost).min(prev[j] + 1).min(cur[j - 1] + 1);
+        }
+        best = best.min(cur[needle.len()]);
+        prev = cur;
+    }
+
+    best
+}
+
+/// A run of segments `start..end` holding one or more matches plus their context.
+pub struct MatchGroup {
+    pub start: usize,
+    pub end: usize,
+    pub matches: Vec<usize>,
+}
+
+/// Finds matching segments and pads each with `context` segments on either side,
+/// merging groups whose context overlaps.
+pub fn find_matches(segments: &[TranscriptSegment], query: &Query, context: usize) -> Vec<MatchGroup> {
+    let mut groups: Vec<MatchGroup> = Vec::new();
+
+    for (i, seg) in segments.iter().enumerate() {
+        if !query.is_match(&seg.text) {
+            continue;
+        }
+
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(segments.len());
+
+        match groups.last_mut() {
+            Some(last) if start <= last.end => {
+                last.end = end;
+                last.matches.push(i);
+            }
+            _ => groups.push(MatchGroup {
+                start,
+                end,
+                matches: vec![i],
+            }),
+        }
+    }
+
+    groups
+}
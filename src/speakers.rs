@@ -0,0 +1,151 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::transcript::TranscriptSegment;
+
+/// What to do with speaker labels once they have been recorded in `TranscriptSegment::speaker`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SpeakerMode {
+    /// Leave the cue text untouched.
+    Keep,
+    /// Remove `>>` and `NAME:` prefixes from the text.
+    Strip,
+    /// Merge each speaker turn into one segment prefixed with `NAME:`.
+    Group,
+}
+
+enum Turn<'a> {
+    Named(&'a str, &'a str),
+    Anonymous(&'a str),
+}
+
+fn parse_turn(text: &str) -> Option<Turn<'_>> {
+    static NAMED: OnceLock<Regex> = OnceLock::new();
+    let named_re = NAMED.get_or_init(|| Regex::new(r"^([A-Z][A-Z0-9.'\- ]{0,30}[A-Z0-9.]):(?:\s+|$)").unwrap());
+    let (anonymous, rest) = match text.strip_prefix(">>") {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, text),
+    };
+
+    if let Some(caps) = named_re.captures(rest) {
+        let name = caps.get(1).map_or("", |m| m.as_str()).trim();
+        // `AT 10: ...` is a time of day rather than a speaker
+        let last_word = name.rsplit(' ').next().unwrap_or(name);
+        let ends_in_number = last_word.bytes().all(|b| b.is_ascii_digit());
+        if !ends_in_number {
+            let body = &rest[caps.get(0).map_or(0, |m| m.end())..];
+            return Some(Turn::Named(name, body));
+        }
+    }
+
+    anonymous.then_some(Turn::Anonymous(rest))
+}
+
+/// Detects speaker changes marked by `>>` (unnamed) or an upper-case `NAME:` prefix and sets
+/// `speaker` on every segment until the next change. Segments after an unnamed change have
+/// no speaker.
+pub fn label_speakers(
+    segments: Vec<TranscriptSegment>,
+    mode: SpeakerMode,
+) -> Vec<TranscriptSegment> {
+    let mut output: Vec<TranscriptSegment> = Vec::with_capacity(segments.len());
+    let mut current: Option<String> = None;
+
+    for mut seg in segments {
+        let turn = parse_turn(&seg.text);
+        let new_turn = turn.is_some();
+        let body = match turn {
+            Some(Turn::Named(name, body)) => {
+                current = Some(name.to_string());
+                body.to_string()
+            }
+            Some(Turn::Anonymous(body)) => {
+                current = None;
+                body.to_string()
+            }
+            None => seg.text.clone(),
+        };
+        seg.speaker = current.clone();
+
+        match mode {
+            SpeakerMode::Keep => output.push(seg),
+            SpeakerMode::Strip => {
+                seg.text = body;
+                output.push(seg);
+            }
+            SpeakerMode::Group => match output.last_mut() {
+                Some(last) if !new_turn => {
+                    last.text.push(' ');
+                    last.text.push_str(&body);
+                    last.end_seconds = last.end_seconds.max(seg.end_seconds);
+                    last.duration_seconds = last.end_seconds - last.start_seconds;
+                    if let Some(words) = seg.words {
+                        last.words.get_or_insert_with(Vec::new).extend(words);
+                    }
+                }
+                _ => {
+                    seg.text = match &seg.speaker {
+                        Some(name) => format!("{}: {}", name, body),
+                        None => body,
+                    };
+                    output.push(seg);
+                }
+            },
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labelled(texts: &[&str], mode: SpeakerMode) -> Vec<(Option<String>, String)> {
+        let segments = texts.iter().map(|text| TranscriptSegment::new(0.0, 1.0, text)).collect();
+        label_speakers(segments, mode).into_iter().map(|seg| (seg.speaker, seg.text)).collect()
+    }
+
+    fn speakers(texts: &[&str]) -> Vec<Option<String>> {
+        labelled(texts, SpeakerMode::Keep).into_iter().map(|(speaker, _)| speaker).collect()
+    }
+
+    #[test]
+    fn names_speakers_until_the_next_turn() {
+        assert_eq!(
+            speakers(&["JOHN: Hello.", "How are you?", "MARY: Fine."]),
+            [Some("JOHN".to_string()), Some("JOHN".to_string()), Some("MARY".to_string())]
+        );
+    }
+
+    #[test]
+    fn ignores_times_of_day() {
+        assert_eq!(speakers(&["AT 10:30 WE START", "THE 9:00 SHOW"]), [None, None]);
+        assert_eq!(speakers(&["AT 10: WE START"]), [None]);
+        assert_eq!(speakers(&["R2D2: Beep."]), [Some("R2D2".to_string())]);
+    }
+
+    #[test]
+    fn arrows_mark_unnamed_turns() {
+        assert_eq!(
+            labelled(&["JOHN: Hi.", ">> Who is this?", ">> MARY: Me."], SpeakerMode::Strip),
+            [
+                (Some("JOHN".to_string()), "Hi.".to_string()),
+                (None, "Who is this?".to_string()),
+                (Some("MARY".to_string()), "Me.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_turns_into_one_segment() {
+        assert_eq!(
+            labelled(&[">> JOHN: Hello", "there.", ">> Hi."], SpeakerMode::Group),
+            [
+                (Some("JOHN".to_string()), "JOHN: Hello there.".to_string()),
+                (None, "Hi.".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::timing::parse_duration;
+use crate::transcript::{Metadata, TranscriptResult, TranscriptSegment, SCHEMA_VERSION};
+
+/// How `--split-by` divides a transcript into parts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SplitBy {
+    /// One part per chapter of the video.
+    Chapter,
+    /// Parts of this many seconds.
+    Duration(f64),
+}
+
+impl SplitBy {
+    /// Parses `chapter` or `duration=<duration>`, e.g. `duration=10m`.
+    pub fn parse(value: &str) -> Result<SplitBy, String> {
+        if value == "chapter" {
+            return Ok(SplitBy::Chapter);
+        }
+        match value.strip_prefix("duration=").map(parse_duration) {
+            Some(Some(seconds)) if seconds > 0.0 => Ok(SplitBy::Duration(seconds)),
+            Some(_) => Err(format!(
+                "invalid duration in '{}', expected e.g. duration=10m",
+                value
+            )),
+            None => Err(format!(
+                "invalid split '{}', expected chapter or duration=<duration>",
+                value
+            )),
+        }
+    }
+}
+
+/// One part of a split transcript.
+pub struct Part {
+    /// Position among the parts, from 1.
+    pub number: usize,
+    /// Chapter title, when split by chapter.
+    pub title: Option<String>,
+    pub result: TranscriptResult,
+}
+
+/// Splits a transcript into parts, each segment going to the part it starts in. Segments before
+/// the first chapter belong to it; parts without segments are left out. A video without
+/// chapters is one part.
+pub fn split(result: &TranscriptResult, by: &SplitBy) -> Vec<Part> {
+    let chapters = result
+        .video
+        .as_ref()
+        .and_then(|v| v.chapters.clone())
+        .unwrap_or_default();
+    let bounds: Vec<(f64, Option<String>)> = match by {
+        SplitBy::Chapter if chapters.is_empty() => vec![(0.0, None)],
+        SplitBy::Chapter => chapters
+            .into_iter()
+            .map(|chapter| (chapter.start_time, Some(chapter.title)))
+            .collect(),
+        SplitBy::Duration(seconds) => {
+            let end = result
+                .segments
+                .iter()
+                .map(|seg| seg.start_seconds)
+                .fold(0.0, f64::max);
+            let count = (end / seconds).floor() as usize + 1;
+            (0..count).map(|n| (n as f64 * seconds, None)).collect()
+        }
+    };
+
+    let mut buckets: Vec<Vec<TranscriptSegment>> = bounds.iter().map(|_| Vec::new()).collect();
+    for seg in &result.segments {
+        let n = bounds
+            .iter()
+            .rposition(|(start, _)| *start <= seg.start_seconds)
+            .unwrap_or(0);
+        buckets[n].push(seg.clone());
+    }
+
+    bounds
+        .into_iter()
+        .zip(buckets)
+        .filter(|(_, segments)| !segments.is_empty())
+        .enumerate()
+        .map(|(i, ((_, title), segments))| {
+            let total_tokens = result
+                .metadata
+                .total_tokens
+                .map(|_| segments.iter().filter_map(|seg| seg.tokens).sum());
+            let marks = result.marks.as_ref().map(|marks| {
+                marks
+                    .iter()
+                    .filter(|mark| segments.iter().any(|seg| seg.start_seconds == mark.seconds))
+                    .cloned()
+                    .collect()
+            });
+            let toc = result.toc.as_ref().map(|toc| {
+                toc.iter()
+                    .filter(|entry| segments.iter().any(|seg| seg.start_seconds == entry.seconds))
+                    .cloned()
+                    .collect()
+            });
+            let mut part = TranscriptResult {
+                schema_version: SCHEMA_VERSION,
+                video_id: result.video_id.clone(),
+                language: result.language.clone(),
+                segments: Vec::new(),
+                metadata: Metadata {
+                    total_segments: 0,
+                    extracted_at: result.metadata.extracted_at.clone(),
+                    auto_generated: result.metadata.auto_generated,
+                    total_tokens,
+                },
+                video: result.video.clone(),
+                keywords: None,
+                marks,
+                toc,
+                summary: None,
+                chunks: None,
+            };
+            part.replace_segments(segments);
+            Part {
+                number: i + 1,
+                title,
+                result: part,
+            }
+        })
+        .collect()
+}
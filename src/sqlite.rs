@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::transcript::TranscriptResult;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS videos (
+    video_id TEXT NOT NULL,
+    language TEXT NOT NULL,
+    title TEXT,
+    channel TEXT,
+    upload_date TEXT,
+    total_segments INTEGER NOT NULL,
+    extracted_at TEXT NOT NULL,
+    PRIMARY KEY (video_id, language)
+);
+CREATE TABLE IF NOT EXISTS segments (
+    video_id TEXT NOT NULL,
+    language TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    start_seconds REAL NOT NULL,
+    end_seconds REAL NOT NULL,
+    PRIMARY KEY (video_id, language, idx)
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS segments_fts USING fts5(
+    text,
+    video_id UNINDEXED,
+    language UNINDEXED,
+    start_seconds UNINDEXED
+);
+";
+
+/// Upserts the video and its segments into a SQLite database, replacing any earlier
+/// extraction of the same video and language.
+pub fn write_sqlite(path: &Path, result: &TranscriptResult) -> rusqlite::Result<()> {
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+
+    let info = result.video.as_ref();
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        "INSERT INTO videos (video_id, language, title, channel, upload_date, total_segments, extracted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (video_id, language) DO UPDATE SET
+             title = excluded.title,
+             channel = excluded.channel,
+             upload_date = excluded.upload_date,
+             total_segments = excluded.total_segments,
+             extracted_at = excluded.extracted_at",
+        params![
+            result.video_id,
+            result.language,
+            info.and_then(|v| v.title.clone()),
+            info.and_then(|v| v.channel.clone()),
+            info.and_then(|v| v.upload_date.clone()),
+            result.metadata.total_segments as i64,
+            result.metadata.extracted_at,
+        ],
+    )?;
+
+    tx.execute(
+        "DELETE FROM segments WHERE video_id = ?1 AND language = ?2",
+        params![result.video_id, result.language],
+    )?;
+    tx.execute(
+        "DELETE FROM segments_fts WHERE video_id = ?1 AND language = ?2",
+        params![result.video_id, result.language],
+    )?;
+
+    {
+        let mut insert_segment = tx.prepare(
+            "INSERT INTO segments (video_id, language, idx, text, start_seconds, end_seconds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let mut insert_fts = tx.prepare(
+            "INSERT INTO segments_fts (text, video_id, language, start_seconds) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+
+        for seg in &result.segments {
+            insert_segment.execute(params![
+                result.video_id,
+                result.language,
+                seg.index as i64,
+                seg.text,
+                seg.start_seconds,
+                seg.end_seconds,
+            ])?;
+            insert_fts.execute(params![seg.text, result.video_id, result.language, seg.start_seconds])?;
+        }
+    }
+
+    tx.commit()
+}
@@ -0,0 +1,61 @@
+use std::borrow::Cow;
+
+use regex::Regex;
+
+use crate::transcript::TranscriptSegment;
+use crate::vtt::parse_vtt;
+
+/// Rewrites SubRip subtitles as WebVTT. The formats differ only in the `WEBVTT` header and the
+/// decimal comma of SRT timestamps; the numeric cue counters become cue identifiers.
+pub fn srt_to_vtt(content: &str) -> String {
+    let lines = SrtLines::new();
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut vtt = String::from("WEBVTT\n\n");
+    for line in content.lines() {
+        vtt.push_str(&lines.to_vtt(line));
+        vtt.push('\n');
+    }
+    vtt
+}
+
+/// [`srt_to_vtt`] a line at a time, for subtitles read from a stream.
+pub struct SrtLines {
+    timing_re: Regex,
+}
+
+impl Default for SrtLines {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SrtLines {
+    pub fn new() -> Self {
+        SrtLines {
+            timing_re: Regex::new(
+                r"^(\s*(?:\d+:)?\d{1,2}:\d{2}),(\d{3}\s*-->\s*(?:\d+:)?\d{1,2}:\d{2}),(\d{3})",
+            )
+            .unwrap(),
+        }
+    }
+
+    /// The line as WebVTT: timing lines get decimal points.
+    pub fn to_vtt<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let line = line.trim_end_matches('\r');
+        self.timing_re.replace(line, "$1.$2.$3")
+    }
+}
+
+/// Parses SubRip (`.srt`) subtitles into segments.
+pub fn parse_srt(content: &str) -> Vec<TranscriptSegment> {
+    parse_vtt(&srt_to_vtt(content))
+}
+
+/// Whether `content` is WebVTT rather than SubRip, judging by its header.
+pub fn is_vtt(content: &str) -> bool {
+    content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(content)
+        .trim_start()
+        .starts_with("WEBVTT")
+}
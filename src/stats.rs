@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::transcript::TranscriptSegment;
+
+#[derive(Serialize)]
+pub struct TranscriptStats {
+    pub segment_count: usize,
+    pub word_count: usize,
+    pub unique_words: usize,
+    /// Time covered by at least one segment, with overlapping cues counted once.
+    pub speaking_seconds: f64,
+    pub total_seconds: f64,
+    pub words_per_minute: f64,
+    pub wpm_over_time: Vec<WpmBucket>,
+    pub longest_gaps: Vec<Gap>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readability: Option<Readability>,
+}
+
+/// Readability and vocabulary metrics. Sentence-based scores are only meaningful for
+/// punctuated captions; auto-generated tracks usually have little punctuation.
+#[derive(Serialize)]
+pub struct Readability {
+    pub sentence_count: usize,
+    pub average_sentence_length: f64,
+    pub average_syllables_per_word: f64,
+    pub flesch_reading_ease: f64,
+    pub flesch_kincaid_grade: f64,
+    pub type_token_ratio: f64,
+    /// Type-token ratio averaged over sliding windows, comparable across transcript lengths.
+    pub moving_average_ttr: f64,
+}
+
+#[derive(Serialize)]
+pub struct WpmBucket {
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub words: usize,
+    pub words_per_minute: f64,
+}
+
+#[derive(Serialize)]
+pub struct Gap {
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub duration_seconds: f64,
+}
+
+/// A cue whose reading speed exceeds the limit.
+#[derive(Serialize)]
+pub struct CpsWarning {
+    pub index: usize,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub characters: usize,
+    pub characters_per_second: f64,
+    pub text: String,
+}
+
+/// Reading speed of a cue in characters per second, counting spaces but not line breaks.
+/// Cues without duration have infinite speed.
+pub fn characters_per_second(seg: &TranscriptSegment) -> f64 {
+    let characters = seg.text.chars().filter(|&c| c != '\n').count();
+    if seg.duration_seconds > 0.0 {
+        characters as f64 / seg.duration_seconds
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Cues read faster than `max_cps` characters per second, in transcript order.
+pub fn cps_warnings(segments: &[TranscriptSegment], max_cps: f64) -> Vec<CpsWarning> {
+    segments
+        .iter()
+        .filter_map(|seg| {
+            let cps = characters_per_second(seg);
+            (cps > max_cps).then(|| CpsWarning {
+                index: seg.index,
+                start_seconds: seg.start_seconds,
+                end_seconds: seg.end_seconds,
+                characters: seg.text.chars().filter(|&c| c != '\n').count(),
+                characters_per_second: cps,
+                text: seg.text.replace('\n', " "),
+            })
+        })
+        .collect()
+}
+
+pub fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Computes word and timing statistics. Words are counted in the `bucket_seconds` window in
+/// which their segment starts; the `top_gaps` longest silences are reported longest first.
+pub fn compute_stats(
+    segments: &[TranscriptSegment],
+    bucket_seconds: f64,
+    top_gaps: usize,
+) -> TranscriptStats {
+    let mut word_count = 0;
+    let mut unique = HashSet::new();
+    for seg in segments {
+        for word in seg.text.split_whitespace() {
+            word_count += 1;
+            let normalized = normalize_word(word);
+            if !normalized.is_empty() {
+                unique.insert(normalized);
+            }
+        }
+    }
+
+    let mut speaking_seconds = 0.0;
+    let mut gaps = Vec::new();
+    let mut covered_until: Option<f64> = None;
+    for seg in segments {
+        match covered_until {
+            Some(until) if seg.start_seconds > until => {
+                gaps.push(Gap {
+                    start_seconds: until,
+                    end_seconds: seg.start_seconds,
+                    duration_seconds: seg.start_seconds - until,
+                });
+                speaking_seconds += seg.end_seconds - seg.start_seconds;
+            }
+            Some(until) => speaking_seconds += (seg.end_seconds - until).max(0.0),
+            None => speaking_seconds += seg.end_seconds - seg.start_seconds,
+        }
+        covered_until = Some(covered_until.map_or(seg.end_seconds, |u| u.max(seg.end_seconds)));
+    }
+    gaps.sort_by(|a, b| b.duration_seconds.total_cmp(&a.duration_seconds));
+    gaps.truncate(top_gaps);
+
+    let first_start = segments.first().map_or(0.0, |s| s.start_seconds);
+    let last_end = covered_until.unwrap_or(0.0);
+    let total_seconds = last_end - first_start;
+
+    let mut wpm_over_time: Vec<WpmBucket> = Vec::new();
+    if bucket_seconds > 0.0 {
+        for seg in segments {
+            let bucket = (seg.start_seconds / bucket_seconds).floor() as usize;
+            while wpm_over_time.len() <= bucket {
+                let start = wpm_over_time.len() as f64 * bucket_seconds;
+                wpm_over_time.push(WpmBucket {
+                    start_seconds: start,
+                    end_seconds: (start + bucket_seconds).min(last_end),
+                    words: 0,
+                    words_per_minute: 0.0,
+                });
+            }
+            wpm_over_time[bucket].words += seg.text.split_whitespace().count();
+        }
+        for bucket in &mut wpm_over_time {
+            let minutes = (bucket.end_seconds - bucket.start_seconds) / 60.0;
+            if minutes > 0.0 {
+                bucket.words_per_minute = bucket.words as f64 / minutes;
+            }
+        }
+    }
+
+    TranscriptStats {
+        segment_count: segments.len(),
+        word_count,
+        unique_words: unique.len(),
+        speaking_seconds,
+        total_seconds,
+        words_per_minute: if speaking_seconds > 0.0 {
+            word_count as f64 / (speaking_seconds / 60.0)
+        } else {
+            0.0
+        },
+        wpm_over_time,
+        longest_gaps: gaps,
+        readability: None,
+    }
+}
+
+const MATTR_WINDOW: usize = 50;
+
+fn count_syllables(word: &str) -> usize {
+    let letters: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if letters.is_empty() {
+        return 0;
+    }
+
+    let mut count = 0;
+    let mut prev_vowel = false;
+    for &c in &letters {
+        let vowel = matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+        if vowel && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = vowel;
+    }
+
+    let silent_e =
+        letters.len() > 2 && letters.ends_with(&['e']) && !letters.ends_with(&['l', 'e']);
+    if silent_e && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+pub fn compute_readability(segments: &[TranscriptSegment]) -> Readability {
+    let text = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(normalize_word)
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let sentence_count = text
+        .split(['.', '!', '?'])
+        .filter(|s| s.chars().any(char::is_alphanumeric))
+        .count()
+        .max(1);
+    let syllables: usize = words.iter().map(|w| count_syllables(w)).sum();
+
+    let word_count = words.len().max(1) as f64;
+    let average_sentence_length = words.len() as f64 / sentence_count as f64;
+    let average_syllables_per_word = syllables as f64 / word_count;
+
+    let unique = words.iter().collect::<HashSet<_>>().len();
+    let moving_average_ttr = if words.len() <= MATTR_WINDOW {
+        unique as f64 / word_count
+    } else {
+        let windows = words.windows(MATTR_WINDOW);
+        let count = windows.len() as f64;
+        windows
+            .map(|w| w.iter().collect::<HashSet<_>>().len() as f64 / MATTR_WINDOW as f64)
+            .sum::<f64>()
+            / count
+    };
+
+    Readability {
+        sentence_count,
+        average_sentence_length,
+        average_syllables_per_word,
+        flesch_reading_ease: 206.835
+            - 1.015 * average_sentence_length
+            - 84.6 * average_syllables_per_word,
+        flesch_kincaid_grade: 0.39 * average_sentence_length + 11.8 * average_syllables_per_word
+            - 15.59,
+        type_token_ratio: unique as f64 / word_count,
+        moving_average_ttr,
+    }
+}
@@ -0,0 +1,82 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::llm::{reply_json, transcript_chunks, LlmClient};
+use crate::timing::parse_time;
+use crate::transcript::TranscriptSegment;
+
+/// Summary of a transcript with its key points.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Summary {
+    pub text: String,
+    pub key_points: Vec<KeyPoint>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct KeyPoint {
+    /// Where in the video the point is made.
+    pub seconds: f64,
+    pub text: String,
+}
+
+/// Transcript characters sent per request, well within the context of small models.
+const CHUNK_CHARS: usize = 12_000;
+
+const CHUNK_PROMPT: &str = "You summarize video transcripts. Each line of the transcript starts \
+with its [mm:ss] timestamp. Reply with only a JSON object of the form {\"summary\": \"...\", \
+\"key_points\": [{\"time\": \"mm:ss\", \"point\": \"...\"}]}: a summary of a few sentences and \
+the most important points in order, each with the timestamp of the line where it is made. Write \
+in the language of the transcript.";
+
+const COMBINE_PROMPT: &str = "You are given summaries of consecutive parts of one video \
+transcript. Combine them into a single summary of a few sentences covering the whole video. \
+Reply with only a JSON object of the form {\"summary\": \"...\"}. Write in the language of the \
+summaries.";
+
+/// Summarizes a transcript: each chunk is summarized with its key points, in parallel, and when
+/// there are several chunks their summaries are combined in a final request.
+pub fn summarize(client: &LlmClient, segments: &[TranscriptSegment]) -> Result<Summary, String> {
+    let mut summaries = Vec::new();
+    let mut key_points = Vec::new();
+    let chunks = transcript_chunks(segments, CHUNK_CHARS);
+    let replies = client.map(&chunks, |chunk| {
+        reply_json(&client.chat(CHUNK_PROMPT, chunk)?)
+    })?;
+    for reply in replies {
+        summaries.push(summary_text(&reply)?);
+        let points = reply["key_points"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        key_points.extend(points.iter().filter_map(key_point));
+    }
+
+    let text = if summaries.len() > 1 {
+        summary_text(&reply_json(
+            &client.chat(COMBINE_PROMPT, &summaries.join("\n\n"))?,
+        )?)?
+    } else {
+        summaries.pop().unwrap_or_default()
+    };
+    key_points.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+    Ok(Summary { text, key_points })
+}
+
+fn summary_text(reply: &Value) -> Result<String, String> {
+    reply["summary"]
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| "the reply has no \"summary\"".to_string())
+}
+
+/// A key point from a reply; points without a readable time or text are dropped.
+fn key_point(point: &Value) -> Option<KeyPoint> {
+    let time = point["time"].as_str()?.trim_matches(['[', ']']);
+    let text = point["point"].as_str()?.trim();
+    Some(KeyPoint {
+        seconds: parse_time(time)?,
+        text: text.to_string(),
+    })
+    .filter(|point| !point.text.is_empty())
+}
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+use std::error::Error as _;
+use std::fs;
+
+use tera::{Tera, Value};
+
+use crate::format::{format_timestamp_bracket, format_timestamp_srt};
+use crate::transcript::TranscriptResult;
+
+fn seconds_arg(value: &Value) -> tera::Result<f64> {
+    value
+        .as_f64()
+        .ok_or_else(|| tera::Error::msg("timestamp filters expect a number of seconds"))
+}
+
+/// Renders `source` as a Tera template over the transcript fields.
+pub fn render_tera(name: &str, source: &str, result: &TranscriptResult) -> tera::Result<String> {
+    let mut tera = Tera::default();
+    tera.register_filter("timestamp", |value: &Value, _: &HashMap<String, Value>| {
+        let ts = format_timestamp_bracket(seconds_arg(value)?);
+        Ok(Value::String(ts.trim_matches(['[', ']']).to_string()))
+    });
+    tera.register_filter("srt_time", |value: &Value, _: &HashMap<String, Value>| {
+        Ok(Value::String(format_timestamp_srt(seconds_arg(value)?)))
+    });
+    tera.add_raw_template(name, source)?;
+    tera.render(name, &tera::Context::from_serialize(result)?)
+}
+
+pub fn render_template(path: &str, result: &TranscriptResult) -> Result<String, String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("cannot read '{}' - {}", path, e))?;
+
+    render_tera(path, &source, result).map_err(|e| {
+        let mut message = e.to_string();
+        let mut cause = e.source();
+        while let Some(c) = cause {
+            message.push_str(&format!(": {}", c));
+            cause = c.source();
+        }
+        message
+    })
+}
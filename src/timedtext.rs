@@ -0,0 +1,215 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::transcript::{TranscriptSegment, Word};
+use crate::vtt::parse_vtt;
+
+/// Caption formats requested from yt-dlp, most preferred first. YouTube's own `json3` and `srv3`
+/// carry word timing without the rolling repetition of its WebVTT auto-captions.
+pub const PREFERRED_FORMATS: [SubtitleFormat; 3] = [
+    SubtitleFormat::Json3,
+    SubtitleFormat::Srv3,
+    SubtitleFormat::Vtt,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    #[default]
+    Vtt,
+    Json3,
+    Srv3,
+}
+
+impl SubtitleFormat {
+    /// File extension, which is also yt-dlp's name for the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            SubtitleFormat::Vtt => "vtt",
+            SubtitleFormat::Json3 => "json3",
+            SubtitleFormat::Srv3 => "srv3",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<SubtitleFormat> {
+        PREFERRED_FORMATS
+            .into_iter()
+            .find(|format| extension.eq_ignore_ascii_case(format.extension()))
+    }
+
+    /// Parses captions in this format into segments.
+    pub fn parse(self, content: &str) -> Vec<TranscriptSegment> {
+        match self {
+            SubtitleFormat::Vtt => parse_vtt(content),
+            SubtitleFormat::Json3 => parse_json3(content),
+            SubtitleFormat::Srv3 => parse_srv3(content),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Json3 {
+    #[serde(default)]
+    events: Vec<Json3Event>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Event {
+    #[serde(default)]
+    t_start_ms: u64,
+    #[serde(default)]
+    d_duration_ms: u64,
+    #[serde(default)]
+    segs: Vec<Json3Seg>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Seg {
+    #[serde(default)]
+    utf8: String,
+    t_offset_ms: Option<u64>,
+}
+
+/// A caption before it becomes a segment: its timing and its text in pieces, each with the time
+/// it starts being spoken when the format says so.
+struct Caption {
+    start: f64,
+    end: f64,
+    pieces: Vec<(Option<f64>, String)>,
+}
+
+/// Parses YouTube's `json3` timedtext. Events without text (window definitions and the line
+/// breaks of auto-captions) are skipped; `tOffsetMs` on the pieces of an event gives word timing.
+pub fn parse_json3(content: &str) -> Vec<TranscriptSegment> {
+    let Ok(json3) = serde_json::from_str::<Json3>(content) else {
+        return Vec::new();
+    };
+    let captions = json3
+        .events
+        .into_iter()
+        .map(|event| {
+            let start = event.t_start_ms as f64 / 1000.0;
+            Caption {
+                start,
+                end: start + event.d_duration_ms as f64 / 1000.0,
+                pieces: event
+                    .segs
+                    .into_iter()
+                    .map(|seg| {
+                        let offset = seg.t_offset_ms.map(|ms| start + ms as f64 / 1000.0);
+                        (offset, seg.utf8)
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    into_segments(captions)
+}
+
+/// Parses YouTube's `srv3` timedtext XML: `<p t="..." d="...">` paragraphs, optionally split
+/// into `<s t="...">` word spans timed relative to their paragraph.
+pub fn parse_srv3(content: &str) -> Vec<TranscriptSegment> {
+    let p_re = Regex::new(r"(?s)<p\b([^>]*?)(?:/>|>(.*?)</p>)").unwrap();
+    let s_re = Regex::new(r"(?s)<s\b([^>]*?)(?:/>|>(.*?)</s>)").unwrap();
+    let t_re = Regex::new(r#"\bt="(\d+)""#).unwrap();
+    let d_re = Regex::new(r#"\bd="(\d+)""#).unwrap();
+    let br_re = Regex::new(r"<br\s*/?>").unwrap();
+    let tag_re = Regex::new(r"<[^>]+>").unwrap();
+    let millis = |re: &Regex, attrs: &str| {
+        re.captures(attrs)
+            .and_then(|caps| caps[1].parse::<u64>().ok())
+            .map(|ms| ms as f64 / 1000.0)
+    };
+
+    let captions = p_re
+        .captures_iter(content)
+        .map(|p| {
+            let start = millis(&t_re, &p[1]).unwrap_or(0.0);
+            let inner = p.get(2).map_or("", |m| m.as_str());
+            let spans: Vec<(Option<f64>, String)> = s_re
+                .captures_iter(inner)
+                .map(|s| {
+                    let offset = millis(&t_re, &s[1]).map(|offset| start + offset);
+                    (offset, s.get(2).map_or("", |m| m.as_str()).to_string())
+                })
+                .collect();
+            let pieces = if spans.is_empty() {
+                vec![(None, inner.to_string())]
+            } else {
+                spans
+            };
+            Caption {
+                start,
+                end: start + millis(&d_re, &p[1]).unwrap_or(0.0),
+                pieces: pieces
+                    .into_iter()
+                    .map(|(offset, text)| {
+                        let text = br_re.replace_all(&text, " ");
+                        (offset, tag_re.replace_all(&text, "").into_owned())
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    into_segments(captions)
+}
+
+/// Turns captions into segments, dropping those without text. Auto-captions stay on screen
+/// while the next line is spoken, so a caption ends where the next one starts at the latest.
+/// Captions with timed pieces get words.
+fn into_segments(captions: Vec<Caption>) -> Vec<TranscriptSegment> {
+    let captions: Vec<Caption> = captions
+        .into_iter()
+        .filter(|caption| {
+            caption
+                .pieces
+                .iter()
+                .any(|(_, text)| !text.trim().is_empty())
+        })
+        .collect();
+
+    let mut segments = Vec::with_capacity(captions.len());
+    for (i, caption) in captions.iter().enumerate() {
+        let next_start = captions.get(i + 1).map_or(f64::INFINITY, |next| next.start);
+        let end = caption.end.min(next_start).max(caption.start);
+        let text: String = caption
+            .pieces
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let timed = caption.pieces.iter().any(|(at, _)| at.is_some());
+        let mut words = Vec::new();
+        if timed {
+            for (n, (at, piece)) in caption.pieces.iter().enumerate() {
+                let piece_start = at.unwrap_or(caption.start).clamp(caption.start, end);
+                let piece_end = caption.pieces[n + 1..]
+                    .iter()
+                    .find_map(|(at, _)| *at)
+                    .unwrap_or(end)
+                    .clamp(piece_start, end);
+                words.extend(piece.split_whitespace().map(|word| Word {
+                    text: word.to_string(),
+                    start: piece_start,
+                    end: piece_end,
+                }));
+            }
+        }
+
+        segments.push(TranscriptSegment {
+            index: segments.len(),
+            text,
+            start_seconds: caption.start,
+            end_seconds: end,
+            duration_seconds: end - caption.start,
+            speaker: None,
+            settings: None,
+            words: (!words.is_empty()).then_some(words),
+            tokens: None,
+        });
+    }
+    segments
+}
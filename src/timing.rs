@@ -0,0 +1,225 @@
+use crate::transcript::TranscriptSegment;
+
+/// Parses `[[hh:]mm:]ss[.fff]` or plain seconds with an optional `s` suffix.
+pub fn parse_time(input: &str) -> Option<f64> {
+    let trimmed = input.trim();
+    let trimmed = trimmed.strip_suffix('s').unwrap_or(trimmed);
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let mut seconds = 0.0;
+    for part in trimmed.split(':') {
+        let value: f64 = part.parse().ok()?;
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + value;
+    }
+
+    (trimmed.split(':').count() <= 3).then_some(seconds)
+}
+
+/// Parses a duration such as `90`, `45s`, `30m`, `1h`, `7d` or `1:30:00` into seconds.
+pub fn parse_duration(input: &str) -> Option<f64> {
+    let trimmed = input.trim();
+    let units = [('m', 60.0), ('h', 3600.0), ('d', 86400.0)];
+    for (suffix, scale) in units {
+        if let Some(value) = trimmed.strip_suffix(suffix) {
+            let value: f64 = value.parse().ok()?;
+            return (value.is_finite() && value >= 0.0).then_some(value * scale);
+        }
+    }
+    parse_time(trimmed)
+}
+
+/// Keeps the segments overlapping `from..to`, clipping the ones that straddle either edge.
+pub fn clip_range(
+    segments: Vec<TranscriptSegment>,
+    from: Option<f64>,
+    to: Option<f64>,
+) -> Vec<TranscriptSegment> {
+    let from = from.unwrap_or(0.0);
+    let to = to.unwrap_or(f64::INFINITY);
+
+    segments
+        .into_iter()
+        .filter(|seg| seg.end_seconds > from && seg.start_seconds < to)
+        .map(|mut seg| {
+            seg.start_seconds = seg.start_seconds.max(from);
+            seg.end_seconds = seg.end_seconds.min(to);
+            seg.duration_seconds = seg.end_seconds - seg.start_seconds;
+            if let Some(words) = seg.words.as_mut() {
+                words.retain(|w| w.end > from && w.start < to);
+                for word in words.iter_mut() {
+                    word.start = word.start.max(from);
+                    word.end = word.end.min(to);
+                }
+            }
+            seg
+        })
+        .collect()
+}
+
+/// Parses a signed offset such as `+3.5s`, `-2` or `-1:30`.
+pub fn parse_offset(input: &str) -> Option<f64> {
+    let trimmed = input.trim();
+    if let Some(rest) = trimmed.strip_prefix('-') {
+        parse_time(rest).map(|t| -t)
+    } else {
+        parse_time(trimmed.strip_prefix('+').unwrap_or(trimmed))
+    }
+}
+
+/// Maps every cue time `t` to `t * stretch + offset`. Cues pushed entirely before zero are
+/// dropped and the rest are clamped to start at zero.
+pub fn retime(
+    segments: Vec<TranscriptSegment>,
+    offset: f64,
+    stretch: f64,
+) -> Vec<TranscriptSegment> {
+    segments
+        .into_iter()
+        .filter_map(|mut seg| {
+            let start = seg.start_seconds * stretch + offset;
+            let end = seg.end_seconds * stretch + offset;
+            if end <= 0.0 {
+                return None;
+            }
+            seg.start_seconds = start.max(0.0);
+            seg.end_seconds = end;
+            seg.duration_seconds = seg.end_seconds - seg.start_seconds;
+            for word in seg.words.iter_mut().flatten() {
+                word.start = (word.start * stretch + offset).max(0.0);
+                word.end = (word.end * stretch + offset).max(0.0);
+            }
+            Some(seg)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Word;
+
+    fn word(text: &str, start: f64, end: f64) -> Word {
+        Word {
+            text: text.to_string(),
+            start,
+            end,
+        }
+    }
+
+    fn times(segments: &[TranscriptSegment]) -> Vec<(f64, f64)> {
+        segments
+            .iter()
+            .map(|seg| (seg.start_seconds, seg.end_seconds))
+            .collect()
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("90"), Some(90.0));
+        assert_eq!(parse_time("45s"), Some(45.0));
+        assert_eq!(parse_time("1:30"), Some(90.0));
+        assert_eq!(parse_time(" 1:02:03.5 "), Some(3723.5));
+        assert_eq!(parse_time("0"), Some(0.0));
+    }
+
+    #[test]
+    fn rejects_bad_times() {
+        for input in [
+            "", "s", "-5", "1:-5", "abc", "inf", "NaN", "1:2:3:4", "1::2",
+        ] {
+            assert_eq!(parse_time(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30m"), Some(1800.0));
+        assert_eq!(parse_duration("1h"), Some(3600.0));
+        assert_eq!(parse_duration("7d"), Some(604800.0));
+        assert_eq!(parse_duration("1.5h"), Some(5400.0));
+        assert_eq!(parse_duration("1:30:00"), Some(5400.0));
+        assert_eq!(parse_duration("0"), Some(0.0));
+        for input in ["", "m", "-1h", "infh", "12x"] {
+            assert_eq!(parse_duration(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_offsets() {
+        assert_eq!(parse_offset("+3.5s"), Some(3.5));
+        assert_eq!(parse_offset("-1:30"), Some(-90.0));
+        assert_eq!(parse_offset("2"), Some(2.0));
+        assert_eq!(parse_offset("-"), None);
+        assert_eq!(parse_offset("--2"), None);
+    }
+
+    #[test]
+    fn clips_segments_to_range() {
+        let segments = vec![
+            TranscriptSegment::new(0.0, 2.0, "one"),
+            TranscriptSegment::new(2.0, 4.0, "two"),
+            TranscriptSegment::new(4.0, 6.0, "three"),
+        ];
+        let clipped = clip_range(segments, Some(1.0), Some(4.0));
+        assert_eq!(times(&clipped), [(1.0, 2.0), (2.0, 4.0)]);
+        assert_eq!(clipped[0].duration_seconds, 1.0);
+    }
+
+    #[test]
+    fn clip_without_range_keeps_everything() {
+        let segments = vec![
+            TranscriptSegment::new(0.0, 2.0, ""),
+            TranscriptSegment::new(2.0, 4.0, "two"),
+        ];
+        assert_eq!(
+            times(&clip_range(segments, None, None)),
+            [(0.0, 2.0), (2.0, 4.0)]
+        );
+        assert!(clip_range(Vec::new(), Some(1.0), Some(2.0)).is_empty());
+    }
+
+    #[test]
+    fn clips_words_crossing_range_edges() {
+        let mut seg = TranscriptSegment::new(0.0, 3.0, "a b c");
+        seg.words = Some(vec![
+            word("a", 0.0, 1.0),
+            word("b", 1.0, 2.0),
+            word("c", 2.0, 3.0),
+        ]);
+        let clipped = clip_range(vec![seg], Some(1.5), Some(2.5));
+        let words = clipped[0].words.as_ref().unwrap();
+        let kept: Vec<_> = words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start, w.end))
+            .collect();
+        assert_eq!(kept, [("b", 1.5, 2.0), ("c", 2.0, 2.5)]);
+    }
+
+    #[test]
+    fn retimes_with_stretch_and_offset() {
+        let segments = vec![
+            TranscriptSegment::new(1.0, 2.0, "one"),
+            TranscriptSegment::new(2.0, 4.0, "two"),
+        ];
+        let retimed = retime(segments, 1.0, 2.0);
+        assert_eq!(times(&retimed), [(3.0, 5.0), (5.0, 9.0)]);
+        assert_eq!(retimed[1].duration_seconds, 4.0);
+    }
+
+    #[test]
+    fn retime_drops_cues_moved_before_zero() {
+        let mut seg = TranscriptSegment::new(2.0, 4.0, "two three");
+        seg.words = Some(vec![word("two", 2.0, 3.0), word("three", 3.0, 4.0)]);
+        let segments = vec![TranscriptSegment::new(0.0, 2.0, "one"), seg];
+        let retimed = retime(segments, -3.0, 1.0);
+        assert_eq!(times(&retimed), [(0.0, 1.0)]);
+        let words = retimed[0].words.as_ref().unwrap();
+        assert_eq!((words[0].start, words[0].end), (0.0, 0.0));
+        assert_eq!((words[1].start, words[1].end), (0.0, 1.0));
+    }
+}
@@ -0,0 +1,93 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::format::youtube_link;
+use crate::timing::parse_duration;
+use crate::transcript::TranscriptSegment;
+
+/// Longest entry text, in words; captions without punctuation are cut here.
+const MAX_WORDS: usize = 20;
+
+/// The start of one `--toc` interval: where speech resumes, with a link and its first sentence.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TocEntry {
+    pub seconds: f64,
+    pub url: String,
+    pub text: String,
+}
+
+/// Parses the interval of `--toc`, `every=<duration>` (e.g. `every=5m`), into seconds.
+pub fn parse_interval(value: &str) -> Result<f64, String> {
+    match value.strip_prefix("every=").map(parse_duration) {
+        Some(Some(seconds)) if seconds > 0.0 => Ok(seconds),
+        Some(_) => Err(format!(
+            "invalid duration in '{}', expected e.g. every=5m",
+            value
+        )),
+        None => Err(format!(
+            "invalid table of contents '{}', expected every=<duration>",
+            value
+        )),
+    }
+}
+
+/// One entry per `every` seconds of the video that has speech, starting at its first segment.
+pub fn table_of_contents(
+    video_id: &str,
+    segments: &[TranscriptSegment],
+    every: f64,
+) -> Vec<TocEntry> {
+    let mut entries = Vec::new();
+    let mut last_interval = None;
+    for (i, seg) in segments.iter().enumerate() {
+        let interval = (seg.start_seconds / every).floor() as u64;
+        if last_interval == Some(interval) {
+            continue;
+        }
+        last_interval = Some(interval);
+
+        let mut text = String::new();
+        for next in &segments[i..] {
+            if (next.start_seconds / every).floor() as u64 != interval {
+                break;
+            }
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(next.text.trim());
+            if sentence_end(&text).is_some() || text.split_whitespace().count() >= MAX_WORDS {
+                break;
+            }
+        }
+        entries.push(TocEntry {
+            seconds: seg.start_seconds,
+            url: youtube_link(video_id, seg.start_seconds),
+            text: first_sentence(&text),
+        });
+    }
+    entries
+}
+
+/// Byte offset just past the first `.`, `!`, `?` or `…` that ends a sentence.
+fn sentence_end(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if matches!(c, '.' | '!' | '?' | '…')
+            && chars.peek().is_none_or(|(_, next)| next.is_whitespace())
+        {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// The first sentence of `text` on one line, shortened to `MAX_WORDS` words.
+fn first_sentence(text: &str) -> String {
+    let sentence = &text[..sentence_end(text).unwrap_or(text.len())];
+    let words: Vec<&str> = sentence.split_whitespace().collect();
+    if words.len() > MAX_WORDS {
+        format!("{}…", words[..MAX_WORDS].join(" "))
+    } else {
+        words.join(" ")
+    }
+}
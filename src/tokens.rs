@@ -0,0 +1,20 @@
+/// Counts the tokens GPT-4o and later OpenAI models see in `text`, with their `o200k_base`
+/// encoding.
+#[cfg(feature = "tokenizer")]
+pub fn count_tokens(text: &str) -> usize {
+    use std::sync::OnceLock;
+
+    static ENCODING: OnceLock<tiktoken_rs::CoreBPE> = OnceLock::new();
+    ENCODING
+        .get_or_init(|| tiktoken_rs::o200k_base().expect("o200k_base is bundled"))
+        .encode_with_special_tokens(text)
+        .len()
+}
+
+/// Estimates how many tokens a language model sees in `text`, at about four characters per
+/// token as for English with the GPT tokenizers. Build with the `tokenizer` feature for exact
+/// counts.
+#[cfg(not(feature = "tokenizer"))]
+pub fn count_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
@@ -0,0 +1,1033 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::Value;
+use tempfile::TempDir;
+
+use crate::cache::{Cache, CachedSubtitles};
+use crate::cleanup::dedup_rolling;
+use crate::cookies::{self, Cookie};
+use crate::error::TranscriberError;
+use crate::pacing::backoff;
+use crate::timedtext::{SubtitleFormat, PREFERRED_FORMATS};
+use crate::transcript::{Metadata, TranscriptResult, VideoInfo, SCHEMA_VERSION};
+use crate::video_id::{extract_video_id, is_web_url, url_key};
+use crate::vtt::decode_entities;
+use crate::{http, yt_dlp};
+
+/// Which caption track of a video to fetch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Track {
+    /// Manual subtitles when available, otherwise auto-generated captions
+    #[default]
+    Any,
+    Manual,
+    Auto,
+}
+
+impl Track {
+    /// Name of the track in cache keys.
+    pub fn name(self) -> &'static str {
+        match self {
+            Track::Any => "any",
+            Track::Manual => "manual",
+            Track::Auto => "auto",
+        }
+    }
+}
+
+/// How subtitles are downloaded from YouTube.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// yt-dlp (the managed binary once installed, otherwise `yt-dlp` on PATH), which keeps up
+    /// with YouTube's changes and gets the video's metadata
+    #[default]
+    YtDlp,
+    /// YouTube's watch page and caption endpoint directly, without any external program
+    Native,
+}
+
+/// How much a message from a [`Transcriber`] matters, for [`TranscriberBuilder::logger`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    /// Something failed without stopping the fetch, such as writing the cache
+    Warning,
+    /// Worth telling the user, such as a retry after a temporary failure
+    Notice,
+    /// Details for troubleshooting, such as the yt-dlp command line
+    Debug,
+}
+
+/// Subtitles from [`Transcriber::fetch_subtitles`], not yet parsed.
+pub struct FetchedSubtitles {
+    /// The YouTube video ID, or a key derived from the URL of another site with
+    /// [`TranscriberBuilder::any_site`]
+    pub video_id: String,
+    pub subtitles: CachedSubtitles,
+    /// Whether the subtitles came from the cache rather than a download
+    pub cached: bool,
+}
+
+type YtDlpResolver = Arc<dyn Fn() -> Result<Vec<String>, TranscriberError> + Send + Sync>;
+
+type Logger = Arc<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+/// Tried once with a region-restricted video when yt-dlp does not say where it is available.
+const DEFAULT_GEO_BYPASS_COUNTRY: &str = "US";
+
+/// Fetches transcripts and parses them into [`TranscriptResult`]s. Built with
+/// [`Transcriber::builder`]:
+///
+/// ```no_run
+/// use yt_transcriber::transcriber::{Backend, Transcriber};
+///
+/// let transcriber = Transcriber::builder()
+///     .language("en")
+///     .prefer_manual(true)
+///     .backend(Backend::Native)
+///     .build();
+/// let result = transcriber.fetch("https://youtu.be/dQw4w9WgXcQ")?;
+/// # Ok::<(), yt_transcriber::TranscriberError>(())
+/// ```
+#[derive(Clone)]
+pub struct Transcriber {
+    language: String,
+    track: Track,
+    backend: Backend,
+    dedup: bool,
+    decode_entities: bool,
+    cue_settings: bool,
+    word_timestamps: bool,
+    cache: Option<Cache>,
+    yt_dlp: YtDlpResolver,
+    extra_yt_dlp_args: Vec<String>,
+    proxy: Option<String>,
+    cookies: Option<PathBuf>,
+    cookies_from_browser: Option<String>,
+    po_token: Option<String>,
+    player_client: Vec<String>,
+    geo_bypass_country: Option<String>,
+    timeout: Option<Duration>,
+    retries: u32,
+    any_site: bool,
+    logger: Option<Logger>,
+}
+
+/// Options of a [`Transcriber`]. Without any, it fetches English subtitles with yt-dlp,
+/// preferring manual ones, retries temporary failures three times and caches nothing.
+#[derive(Clone)]
+pub struct TranscriberBuilder {
+    transcriber: Transcriber,
+}
+
+impl TranscriberBuilder {
+    /// Language code of the subtitles, such as `en` or `pt-BR`.
+    pub fn language(mut self, language: &str) -> Self {
+        self.transcriber.language = language.to_string();
+        self
+    }
+
+    /// Whether manual subtitles are taken when the video has them (the default), falling back
+    /// to auto-generated captions; `false` takes the auto-generated captions only.
+    pub fn prefer_manual(self, prefer_manual: bool) -> Self {
+        self.track(if prefer_manual {
+            Track::Any
+        } else {
+            Track::Auto
+        })
+    }
+
+    pub fn track(mut self, track: Track) -> Self {
+        self.transcriber.track = track;
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.transcriber.backend = backend;
+        self
+    }
+
+    /// Whether the repeated lines of rolling auto-captions are stripped (the default).
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.transcriber.dedup = dedup;
+        self
+    }
+
+    /// Whether HTML entities in cue text are decoded (the default).
+    pub fn decode_entities(mut self, decode_entities: bool) -> Self {
+        self.transcriber.decode_entities = decode_entities;
+        self
+    }
+
+    /// Whether WebVTT cue settings are kept on the segments.
+    pub fn cue_settings(mut self, cue_settings: bool) -> Self {
+        self.transcriber.cue_settings = cue_settings;
+        self
+    }
+
+    /// Whether per-word timings are kept on the segments.
+    pub fn word_timestamps(mut self, word_timestamps: bool) -> Self {
+        self.transcriber.word_timestamps = word_timestamps;
+        self
+    }
+
+    /// Where downloaded subtitles are reused from and saved to, if anywhere.
+    pub fn cache(mut self, cache: Option<Cache>) -> Self {
+        self.transcriber.cache = cache;
+        self
+    }
+
+    /// The yt-dlp command line to run: an executable, or a program and its leading arguments
+    /// such as `["python3", "-m", "yt_dlp"]`. By default the managed binary once installed,
+    /// otherwise `yt-dlp` on PATH.
+    pub fn yt_dlp_path(self, command: Vec<String>) -> Self {
+        self.yt_dlp_resolver(move || Ok(command.clone()))
+    }
+
+    /// Finds the yt-dlp command line each time yt-dlp is about to run, for callers that install
+    /// it on demand. Replaces [`TranscriberBuilder::yt_dlp_path`].
+    pub fn yt_dlp_resolver(
+        mut self,
+        resolve: impl Fn() -> Result<Vec<String>, TranscriberError> + Send + Sync + 'static,
+    ) -> Self {
+        self.transcriber.yt_dlp = Arc::new(resolve);
+        self
+    }
+
+    /// Arguments appended to every yt-dlp run, after those of the network options.
+    pub fn extra_yt_dlp_args(mut self, args: Vec<String>) -> Self {
+        self.transcriber.extra_yt_dlp_args = args;
+        self
+    }
+
+    /// Proxy for yt-dlp and the native backend, such as `socks5://127.0.0.1:9050`. Without one,
+    /// `HTTPS_PROXY`, `HTTP_PROXY` and `ALL_PROXY` are honored.
+    pub fn proxy(mut self, proxy: Option<String>) -> Self {
+        self.transcriber.proxy = proxy;
+        self
+    }
+
+    /// Netscape cookies.txt passed to yt-dlp and sent with the native backend's requests.
+    pub fn cookies(mut self, path: Option<PathBuf>) -> Self {
+        self.transcriber.cookies = path;
+        self
+    }
+
+    /// Browser yt-dlp reads cookies from, as `BROWSER[+KEYRING][:PROFILE][::CONTAINER]`.
+    pub fn cookies_from_browser(mut self, browser: Option<String>) -> Self {
+        self.transcriber.cookies_from_browser = browser;
+        self
+    }
+
+    /// Proof-of-origin token(s) yt-dlp passes YouTube's bot check with, as
+    /// `CLIENT.CONTEXT+TOKEN`, comma-separated.
+    pub fn po_token(mut self, token: Option<String>) -> Self {
+        self.transcriber.po_token = token;
+        self
+    }
+
+    /// YouTube player clients for yt-dlp to use, in order, such as `mweb`.
+    pub fn player_client(mut self, clients: Vec<String>) -> Self {
+        self.transcriber.player_client = clients;
+        self
+    }
+
+    /// Country (two-letter code) yt-dlp pretends to be in. Without one, a region-restricted
+    /// video is retried once with a country it is available in.
+    pub fn geo_bypass_country(mut self, country: Option<String>) -> Self {
+        self.transcriber.geo_bypass_country = country;
+        self
+    }
+
+    /// Longest a yt-dlp run or native request may take; `None` (the default) for no limit.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.transcriber.timeout = timeout;
+        self
+    }
+
+    /// How often a temporary yt-dlp failure (network errors, HTTP 429, server errors, timeouts)
+    /// is retried, waiting longer each time.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.transcriber.retries = retries;
+        self
+    }
+
+    /// Whether URLs of other sites yt-dlp supports are accepted, keyed by
+    /// [`url_key`](crate::video_id::url_key).
+    pub fn any_site(mut self, any_site: bool) -> Self {
+        self.transcriber.any_site = any_site;
+        self
+    }
+
+    /// Receives warnings, retry notices and debugging details while fetching.
+    pub fn logger(mut self, log: impl Fn(LogLevel, &str) + Send + Sync + 'static) -> Self {
+        self.transcriber.logger = Some(Arc::new(log));
+        self
+    }
+
+    pub fn build(self) -> Transcriber {
+        self.transcriber
+    }
+}
+
+impl Transcriber {
+    pub fn builder() -> TranscriberBuilder {
+        TranscriberBuilder {
+            transcriber: Transcriber {
+                language: "en".to_string(),
+                track: Track::Any,
+                backend: Backend::YtDlp,
+                dedup: true,
+                decode_entities: true,
+                cue_settings: false,
+                word_timestamps: false,
+                cache: None,
+                yt_dlp: Arc::new(|| Ok(vec![default_yt_dlp()])),
+                extra_yt_dlp_args: Vec::new(),
+                proxy: None,
+                cookies: None,
+                cookies_from_browser: None,
+                po_token: None,
+                player_client: Vec::new(),
+                geo_bypass_country: None,
+                timeout: None,
+                retries: 3,
+                any_site: false,
+                logger: None,
+            },
+        }
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn track(&self) -> Track {
+        self.track
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Fetches the transcript of a YouTube URL or video ID, from the cache when it has the
+    /// subtitles.
+    pub fn fetch(&self, input: &str) -> Result<TranscriptResult, TranscriberError> {
+        let fetched = self.fetch_subtitles(input)?;
+        self.parse(fetched.video_id, fetched.subtitles)
+    }
+
+    /// Fetches the subtitles of a YouTube URL or video ID without parsing them: from the cache
+    /// when it has them, otherwise downloaded and cached unless the video is live.
+    pub fn fetch_subtitles(&self, input: &str) -> Result<FetchedSubtitles, TranscriberError> {
+        let (video_id, url) = self.resolve_input(input)?;
+        let key = Cache::key(&video_id, &self.language, self.track.name());
+        if let Some(subtitles) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            return Ok(FetchedSubtitles {
+                video_id,
+                subtitles,
+                cached: true,
+            });
+        }
+        let subtitles = self.download_url(&video_id, &url)?;
+        // The captions of a running broadcast are incomplete.
+        if let Some(cache) = self.cache.as_ref().filter(|_| !subtitles.live) {
+            if let Err(e) = cache.put(&key, &subtitles) {
+                self.log(LogLevel::Warning, &format!("Failed to write cache - {}", e));
+            }
+        }
+        Ok(FetchedSubtitles {
+            video_id,
+            subtitles,
+            cached: false,
+        })
+    }
+
+    /// Downloads the subtitles of a YouTube video with the configured backend, bypassing the
+    /// cache.
+    pub fn download(&self, video_id: &str) -> Result<CachedSubtitles, TranscriberError> {
+        self.download_url(
+            video_id,
+            &format!("https://www.youtube.com/watch?v={}", video_id),
+        )
+    }
+
+    /// Runs yt-dlp with `args` followed by the network options and
+    /// [`TranscriberBuilder::extra_yt_dlp_args`]. Temporary failures are retried with growing
+    /// pauses, and a region-restricted video once with a country it is available in unless a
+    /// geo-bypass country was given. The output is returned whether or not yt-dlp succeeded; an
+    /// error means it could not be found, started or finished in time.
+    pub fn run_yt_dlp(&self, args: &[&str]) -> Result<Output, TranscriberError> {
+        let program = (self.yt_dlp)()?;
+        let mut extra = self.network_args();
+        let mut attempt = 0;
+        loop {
+            let output = match self.run_yt_dlp_with(&program, args, &extra) {
+                Err(e) if e.kind() == io::ErrorKind::TimedOut && attempt < self.retries => {
+                    let delay = backoff(attempt);
+                    attempt += 1;
+                    self.log(
+                        LogLevel::Notice,
+                        &format!(
+                            "{}, retrying in {:.1}s ({}/{})",
+                            e,
+                            delay.as_secs_f64(),
+                            attempt,
+                            self.retries
+                        ),
+                    );
+                    thread::sleep(delay);
+                    continue;
+                }
+                output => output.map_err(|e| TranscriberError::from_run_failure(&e))?,
+            };
+            if output.status.success() {
+                return Ok(output);
+            }
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if yt_dlp::is_geo_restricted(&stderr)
+                && !extra
+                    .iter()
+                    .any(|a| a == "--geo-bypass-country" || a == "--xff")
+            {
+                let country = yt_dlp::available_country(&stderr)
+                    .unwrap_or_else(|| DEFAULT_GEO_BYPASS_COUNTRY.to_string());
+                self.log(
+                    LogLevel::Notice,
+                    &format!(
+                        "Video is region-restricted, retrying with --geo-bypass-country {}",
+                        country
+                    ),
+                );
+                extra.extend(["--geo-bypass-country".to_string(), country]);
+            } else if yt_dlp::is_temporary_failure(&stderr) && attempt < self.retries {
+                let delay = backoff(attempt);
+                attempt += 1;
+                self.log(
+                    LogLevel::Notice,
+                    &format!(
+                        "yt-dlp failed temporarily, retrying in {:.1}s ({}/{})",
+                        delay.as_secs_f64(),
+                        attempt,
+                        self.retries
+                    ),
+                );
+                thread::sleep(delay);
+            } else {
+                return Ok(output);
+            }
+        }
+    }
+
+    /// Parses fetched or local subtitles into a transcript, applying the parsing options.
+    pub fn parse(
+        &self,
+        video_id: String,
+        subtitles: CachedSubtitles,
+    ) -> Result<TranscriptResult, TranscriberError> {
+        let mut segments = subtitles.format.parse(&subtitles.content);
+        for seg in &mut segments {
+            if self.decode_entities {
+                seg.text = decode_entities(&seg.text);
+                for word in seg.words.iter_mut().flatten() {
+                    word.text = decode_entities(&word.text);
+                }
+            }
+            if !self.cue_settings {
+                seg.settings = None;
+            }
+            if !self.word_timestamps {
+                seg.words = None;
+            }
+        }
+        // Only YouTube's WebVTT auto-captions repeat the previous line.
+        if subtitles.auto_generated && self.dedup && subtitles.format == SubtitleFormat::Vtt {
+            segments = dedup_rolling(segments);
+        }
+        if segments.is_empty() {
+            return Err(TranscriberError::EmptyTranscript);
+        }
+
+        let mut result = TranscriptResult {
+            schema_version: SCHEMA_VERSION,
+            video_id,
+            language: self.language.clone(),
+            metadata: Metadata {
+                total_segments: 0,
+                extracted_at: chrono::Utc::now().to_rfc3339(),
+                auto_generated: subtitles.auto_generated,
+                total_tokens: None,
+            },
+            segments: Vec::new(),
+            video: subtitles.video,
+            keywords: None,
+            marks: None,
+            toc: None,
+            summary: None,
+            chunks: None,
+        };
+        result.replace_segments(segments);
+        Ok(result)
+    }
+
+    /// Arguments asking yt-dlp for the subtitles and info JSON of `url`, written next to
+    /// `output_template`.
+    fn yt_dlp_args(&self, url: &str, output_template: &str) -> Vec<String> {
+        let track_args: &[&str] = match self.track {
+            Track::Any => &["--write-sub", "--write-auto-sub"],
+            Track::Manual => &["--write-sub"],
+            Track::Auto => &["--write-auto-sub"],
+        };
+        let sub_format: Vec<&str> = PREFERRED_FORMATS
+            .iter()
+            .map(|format| format.extension())
+            .collect();
+        let mut args: Vec<String> = track_args.iter().map(|a| a.to_string()).collect();
+        args.extend([
+            "--sub-lang".to_string(),
+            self.language.clone(),
+            "--sub-format".to_string(),
+            sub_format.join("/"),
+            "--skip-download".to_string(),
+            "--write-info-json".to_string(),
+            "--no-warnings".to_string(),
+            "-o".to_string(),
+            output_template.to_string(),
+            url.to_string(),
+        ]);
+        args
+    }
+
+    /// Picks up the subtitles and metadata a run with [`Transcriber::yt_dlp_args`] left in
+    /// `dir`, in the most preferred format.
+    fn read_yt_dlp_output(
+        &self,
+        dir: &Path,
+        video_id: &str,
+    ) -> Result<CachedSubtitles, TranscriberError> {
+        let language = self.language.as_str();
+        let info_json = fs::read_to_string(dir.join(format!("{}.info.json", video_id)))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+
+        let named = PREFERRED_FORMATS.into_iter().find_map(|format| {
+            [
+                format!("{}.{}.{}", video_id, language, format.extension()),
+                format!("{}.{}-orig.{}", video_id, language, format.extension()),
+            ]
+            .into_iter()
+            .find_map(|name| fs::read_to_string(dir.join(name)).ok())
+            .map(|content| (content, format))
+        });
+        let (content, format) = named
+            .or_else(|| {
+                fs::read_dir(dir).ok()?.flatten().find_map(|entry| {
+                    let path = entry.path();
+                    let format =
+                        SubtitleFormat::from_extension(&path.extension()?.to_string_lossy())?;
+                    Some((fs::read_to_string(&path).ok()?, format))
+                })
+            })
+            .ok_or_else(|| {
+                let languages = info_json
+                    .as_ref()
+                    .map(|info| yt_dlp_languages(info, self.track))
+                    .unwrap_or_default();
+                self.no_captions(languages)
+            })?;
+
+        let video = info_json
+            .as_ref()
+            .and_then(|info| VideoInfo::deserialize(info).ok());
+        let has_manual = info_json
+            .as_ref()
+            .is_some_and(|info| info["subtitles"].get(language).is_some());
+        let auto_generated = match self.track {
+            Track::Manual => false,
+            Track::Auto => true,
+            Track::Any => info_json.is_some() && !has_manual,
+        };
+        let live = info_json
+            .as_ref()
+            .is_some_and(|info| info["live_status"] == "is_live" || info["is_live"] == true);
+
+        Ok(CachedSubtitles {
+            content,
+            format,
+            video,
+            auto_generated,
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            live,
+        })
+    }
+
+    /// The key and URL of `input`: the video ID and its watch page for YouTube, or with
+    /// [`TranscriberBuilder::any_site`] a key derived from the URL of another site.
+    fn resolve_input(&self, input: &str) -> Result<(String, String), TranscriberError> {
+        if let Some(video_id) = extract_video_id(input) {
+            let url = format!("https://www.youtube.com/watch?v={}", video_id);
+            return Ok((video_id, url));
+        }
+        if self.any_site && is_web_url(input) {
+            return Ok((url_key(input), input.trim().to_string()));
+        }
+        Err(TranscriberError::InvalidUrl(input.to_string()))
+    }
+
+    fn download_url(&self, key: &str, url: &str) -> Result<CachedSubtitles, TranscriberError> {
+        match self.backend {
+            Backend::YtDlp => self.download_yt_dlp(key, url),
+            Backend::Native => match extract_video_id(url) {
+                Some(video_id) => self.download_native(&video_id),
+                None => Err(TranscriberError::BackendFailure {
+                    backend: Backend::Native,
+                    message: "The native backend only fetches from YouTube".to_string(),
+                }),
+            },
+        }
+    }
+
+    fn download_yt_dlp(&self, key: &str, url: &str) -> Result<CachedSubtitles, TranscriberError> {
+        let dir = TempDir::new()?;
+        // Files are named by the key rather than the site's ID, which only YouTube URLs carry.
+        let template = dir.path().join(key);
+        let args = self.yt_dlp_args(url, &template.to_string_lossy());
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.run_yt_dlp(&args)?;
+        if !output.status.success() {
+            return Err(TranscriberError::from_yt_dlp(&String::from_utf8_lossy(
+                &output.stderr,
+            )));
+        }
+        self.read_yt_dlp_output(dir.path(), key)
+    }
+
+    /// yt-dlp arguments for the network options, followed by the extra arguments.
+    fn network_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(proxy) = &self.proxy {
+            args.extend(["--proxy".to_string(), proxy.clone()]);
+        }
+        if let Some(cookies) = &self.cookies {
+            args.extend([
+                "--cookies".to_string(),
+                cookies.to_string_lossy().into_owned(),
+            ]);
+        }
+        if let Some(browser) = &self.cookies_from_browser {
+            args.extend(["--cookies-from-browser".to_string(), browser.clone()]);
+        }
+        if let Some(country) = &self.geo_bypass_country {
+            args.extend(["--geo-bypass-country".to_string(), country.clone()]);
+        }
+        let mut youtube_args = Vec::new();
+        if !self.player_client.is_empty() {
+            youtube_args.push(format!("player_client={}", self.player_client.join(",")));
+        }
+        if let Some(token) = &self.po_token {
+            youtube_args.push(format!("po_token={}", token));
+        }
+        if !youtube_args.is_empty() {
+            args.extend([
+                "--extractor-args".to_string(),
+                format!("youtube:{}", youtube_args.join(";")),
+            ]);
+        }
+        args.extend(self.extra_yt_dlp_args.iter().cloned());
+        args
+    }
+
+    /// Runs `program` once with `args` and `extra`, logging its command line and exit status.
+    fn run_yt_dlp_with(
+        &self,
+        program: &[String],
+        args: &[&str],
+        extra: &[String],
+    ) -> io::Result<Output> {
+        if self.logger.is_some() {
+            let quoted: Vec<String> = args
+                .iter()
+                .copied()
+                .chain(extra.iter().map(String::as_str))
+                .map(|a| {
+                    if a.is_empty() || a.contains([' ', '%']) {
+                        format!("'{}'", a)
+                    } else {
+                        a.to_string()
+                    }
+                })
+                .collect();
+            self.log(
+                LogLevel::Debug,
+                &format!("Running: {} {}", program.join(" "), quoted.join(" ")),
+            );
+        }
+
+        let Some((executable, leading)) = program.split_first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "empty yt-dlp command",
+            ));
+        };
+        let started = Instant::now();
+        let mut command = Command::new(executable);
+        command.args(leading).args(args).args(extra);
+        let output = output_within(&mut command, self.timeout);
+        if let Ok(o) = &output {
+            self.log(
+                LogLevel::Debug,
+                &format!(
+                    "yt-dlp exited with {} after {:.2}s",
+                    o.status,
+                    started.elapsed().as_secs_f64()
+                ),
+            );
+        }
+        output
+    }
+
+    /// The agent for native requests: the shared one, or one of its own for the configured
+    /// proxy and timeout.
+    fn agent(&self) -> Result<ureq::Agent, TranscriberError> {
+        if self.proxy.is_none() && self.timeout.is_none() {
+            return Ok(http::agent().clone());
+        }
+        http::build_agent(self.proxy.as_deref(), self.timeout).map_err(|e| {
+            TranscriberError::BackendFailure {
+                backend: Backend::Native,
+                message: format!("Invalid proxy - {}", e),
+            }
+        })
+    }
+
+    fn log(&self, level: LogLevel, message: &str) {
+        if let Some(logger) = &self.logger {
+            logger(level, message);
+        }
+    }
+
+    /// Reads the caption tracks from the player response embedded in the watch page and
+    /// downloads the chosen one as `json3`.
+    fn download_native(&self, video_id: &str) -> Result<CachedSubtitles, TranscriberError> {
+        let agent = self.agent()?;
+        let cookies = match &self.cookies {
+            Some(path) => cookies::load(path)?,
+            None => Vec::new(),
+        };
+        let download = |url: &str| get(&agent, url, &cookies);
+        let page = download(&format!(
+            "https://www.youtube.com/watch?v={}&hl=en",
+            video_id
+        ))?;
+        let player = player_response(&String::from_utf8_lossy(&page)).ok_or_else(|| {
+            TranscriberError::BackendFailure {
+                backend: Backend::Native,
+                message: "No player response on the watch page".to_string(),
+            }
+        })?;
+        if player["playabilityStatus"]["status"] != "OK" {
+            let reason = player["playabilityStatus"]["reason"]
+                .as_str()
+                .unwrap_or("unplayable")
+                .to_string();
+            return Err(if yt_dlp::is_bot_check(&reason) {
+                TranscriberError::BotCheck(reason)
+            } else if yt_dlp::is_geo_restricted(&reason) {
+                TranscriberError::GeoRestricted(reason)
+            } else {
+                TranscriberError::VideoUnavailable(reason)
+            });
+        }
+
+        let tracks = player["captions"]["playerCaptionsTracklistRenderer"]["captionTracks"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        // Speech recognition tracks are of kind `asr`; manual ones have no kind.
+        let is_auto = |track: &Value| track["kind"] == "asr";
+        let wanted = |track: &&Value| match self.track {
+            Track::Any => true,
+            Track::Manual => !is_auto(track),
+            Track::Auto => is_auto(track),
+        };
+        let find = |auto: bool| {
+            tracks.iter().filter(wanted).find(|track| {
+                track["languageCode"] == self.language.as_str() && is_auto(track) == auto
+            })
+        };
+        let Some(track) = find(false).or_else(|| find(true)) else {
+            let mut languages: Vec<String> = tracks
+                .iter()
+                .filter(wanted)
+                .filter_map(|track| track["languageCode"].as_str().map(str::to_string))
+                .collect();
+            languages.sort();
+            languages.dedup();
+            return Err(self.no_captions(languages));
+        };
+        let base_url =
+            track["baseUrl"]
+                .as_str()
+                .ok_or_else(|| TranscriberError::BackendFailure {
+                    backend: Backend::Native,
+                    message: "Caption track has no URL".to_string(),
+                })?;
+        let content = download(&format!("{}&fmt=json3", base_url))?;
+
+        let details = &player["videoDetails"];
+        let text = |value: &Value| value.as_str().map(str::to_string);
+        let video = VideoInfo {
+            title: text(&details["title"]),
+            channel: text(&details["author"]),
+            // yt-dlp's YYYYMMDD rather than the page's YYYY-MM-DD.
+            upload_date: text(&player["microformat"]["playerMicroformatRenderer"]["uploadDate"])
+                .map(|date| date.chars().take(10).filter(|c| *c != '-').collect()),
+            tags: details["keywords"]
+                .as_array()
+                .map(|tags| tags.iter().filter_map(text).collect()),
+            ..VideoInfo::default()
+        };
+
+        Ok(CachedSubtitles {
+            content: String::from_utf8_lossy(&content).into_owned(),
+            format: SubtitleFormat::Json3,
+            video: Some(video),
+            auto_generated: is_auto(track),
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            live: details["isLive"] == true,
+        })
+    }
+
+    fn no_captions(&self, langs_available: Vec<String>) -> TranscriberError {
+        TranscriberError::NoCaptions {
+            language: self.language.clone(),
+            langs_available,
+        }
+    }
+}
+
+/// Languages of `track` that yt-dlp's info JSON lists subtitles in. Of the auto-generated
+/// captions only the spoken language counts, not YouTube's machine translations of it.
+fn yt_dlp_languages(info: &Value, track: Track) -> Vec<String> {
+    let keys = |field: &str| {
+        info[field]
+            .as_object()
+            .into_iter()
+            .flat_map(|languages| languages.keys().cloned())
+            .collect::<Vec<_>>()
+    };
+    let mut languages = Vec::new();
+    if track != Track::Auto {
+        languages.extend(keys("subtitles").into_iter().filter(|l| l != "live_chat"));
+    }
+    if track != Track::Manual {
+        languages.extend(
+            keys("automatic_captions")
+                .into_iter()
+                .filter_map(|l| l.strip_suffix("-orig").map(str::to_string)),
+        );
+    }
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
+/// The managed yt-dlp binary once installed, otherwise `yt-dlp` on PATH.
+fn default_yt_dlp() -> String {
+    let managed = yt_dlp::managed_path();
+    if managed.is_file() {
+        managed.to_string_lossy().into_owned()
+    } else {
+        "yt-dlp".to_string()
+    }
+}
+
+/// Like [`Command::output`], but kills the process once `timeout` has passed and returns a
+/// `TimedOut` error.
+fn output_within(command: &mut Command, timeout: Option<Duration>) -> io::Result<Output> {
+    let Some(timeout) = timeout else {
+        return command.output();
+    };
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Drain both pipes while waiting so a chatty process cannot block on a full pipe.
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "yt-dlp did not finish within {}s and was stopped",
+                    timeout.as_secs_f64()
+                ),
+            ));
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// Downloads `url` with `cookies` that match it, telling rate limiting apart from other
+/// failures.
+fn get(agent: &ureq::Agent, url: &str, cookies: &[Cookie]) -> Result<Vec<u8>, TranscriberError> {
+    let mut request = agent.get(url);
+    if let Some(cookie) = cookies::header_for(cookies, url) {
+        request = request.set("Cookie", &cookie);
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(429, _)) => {
+            return Err(TranscriberError::RateLimited("HTTP 429".to_string()))
+        }
+        Err(ureq::Error::Status(code, _)) => {
+            return Err(TranscriberError::Network(format!("HTTP {}", code)))
+        }
+        Err(e) => return Err(TranscriberError::Network(e.to_string())),
+    };
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(|e| TranscriberError::Network(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// The `ytInitialPlayerResponse` object assigned in a watch page's scripts.
+fn player_response(page: &str) -> Option<Value> {
+    let start = page.find("ytInitialPlayerResponse = ")? + "ytInitialPlayerResponse = ".len();
+    serde_json::Deserializer::from_str(&page[start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_defaults() {
+        let transcriber = Transcriber::builder().build();
+        assert_eq!(transcriber.language(), "en");
+        assert_eq!(transcriber.track(), Track::Any);
+        assert_eq!(transcriber.backend(), Backend::YtDlp);
+        assert_eq!(transcriber.retries(), 3);
+        assert_eq!(transcriber.timeout(), None);
+        assert!(transcriber.cache().is_none());
+        assert!(transcriber.network_args().is_empty());
+    }
+
+    #[test]
+    fn builder_overrides_defaults() {
+        let transcriber = Transcriber::builder()
+            .language("de")
+            .prefer_manual(false)
+            .backend(Backend::Native)
+            .retries(0)
+            .timeout(Some(Duration::from_secs(30)))
+            .build();
+        assert_eq!(transcriber.language(), "de");
+        assert_eq!(transcriber.track(), Track::Auto);
+        assert_eq!(transcriber.backend(), Backend::Native);
+        assert_eq!(transcriber.retries(), 0);
+        assert_eq!(transcriber.timeout(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn network_settings_become_yt_dlp_args() {
+        let transcriber = Transcriber::builder()
+            .proxy(Some("socks5://127.0.0.1:9050".to_string()))
+            .cookies(Some(PathBuf::from("cookies.txt")))
+            .cookies_from_browser(Some("firefox".to_string()))
+            .geo_bypass_country(Some("DE".to_string()))
+            .player_client(vec!["mweb".to_string(), "web".to_string()])
+            .po_token(Some("web.gvs+TOKEN".to_string()))
+            .extra_yt_dlp_args(vec!["--no-check-certificates".to_string()])
+            .build();
+        assert_eq!(
+            transcriber.network_args(),
+            [
+                "--proxy",
+                "socks5://127.0.0.1:9050",
+                "--cookies",
+                "cookies.txt",
+                "--cookies-from-browser",
+                "firefox",
+                "--geo-bypass-country",
+                "DE",
+                "--extractor-args",
+                "youtube:player_client=mweb,web;po_token=web.gvs+TOKEN",
+                "--no-check-certificates",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_youtube_input_to_watch_url() {
+        let (key, url) = Transcriber::builder()
+            .build()
+            .resolve_input("https://youtu.be/dQw4w9WgXcQ")
+            .unwrap();
+        assert_eq!(key, "dQw4w9WgXcQ");
+        assert_eq!(url, "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    }
+
+    #[test]
+    fn other_sites_need_any_site() {
+        let url = "https://vimeo.com/76979871";
+        assert!(matches!(
+            Transcriber::builder().build().resolve_input(url),
+            Err(TranscriberError::InvalidUrl(_))
+        ));
+        let (key, resolved) = Transcriber::builder()
+            .any_site(true)
+            .build()
+            .resolve_input(url)
+            .unwrap();
+        assert_eq!(key, url_key(url));
+        assert_eq!(resolved, url);
+    }
+}
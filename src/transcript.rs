@@ -0,0 +1,153 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::Chunk;
+use crate::keywords::Keyword;
+use crate::marks::Mark;
+use crate::summary::Summary;
+use crate::toc::TocEntry;
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TranscriptSegment {
+    pub index: usize,
+    pub text: String,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub duration_seconds: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<CueSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<Word>>,
+    /// Token count of `text`, with `--count-tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<usize>,
+}
+
+#[cfg(test)]
+impl TranscriptSegment {
+    /// A plain cue from `start` to `end`, for tests.
+    pub(crate) fn new(start: f64, end: f64, text: &str) -> Self {
+        TranscriptSegment {
+            index: 0,
+            text: text.to_string(),
+            start_seconds: start,
+            end_seconds: end,
+            duration_seconds: end - start,
+            speaker: None,
+            settings: None,
+            words: None,
+            tokens: None,
+        }
+    }
+}
+
+/// A word with its own timing, from the inline `<00:00:01.234>` tags of auto-generated captions.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Word {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// WebVTT cue settings (`line:0 position:10% align:start`), kept so caption positioning
+/// survives conversion to other subtitle formats.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CueSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertical: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub align: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+/// Version of the JSON structure of [`TranscriptResult`]. Adding an optional field keeps it;
+/// removing, renaming or retyping a field raises it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct TranscriptResult {
+    /// Structure version, [`SCHEMA_VERSION`] when written by this release.
+    #[serde(default = "current_schema_version")]
+    pub schema_version: u32,
+    pub video_id: String,
+    pub language: String,
+    pub segments: Vec<TranscriptSegment>,
+    pub metadata: Metadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video: Option<VideoInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<Keyword>>,
+    /// Mentions of the `--mark` keywords.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marks: Option<Vec<Mark>>,
+    /// Table of contents, with `--toc`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toc: Option<Vec<TocEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<Summary>,
+    /// Retrieval chunks, with `--chunk`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<Vec<Chunk>>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct VideoInfo {
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    pub upload_date: Option<String>,
+    pub tags: Option<Vec<String>>,
+    /// Chapters from the video description, as listed by yt-dlp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chapters: Option<Vec<Chapter>>,
+    /// Whether `chapters` were proposed by `--auto-chapters` rather than set by the creator.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub chapters_generated: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Chapter {
+    pub title: String,
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Metadata {
+    pub total_segments: usize,
+    pub extracted_at: String,
+    /// Whether the captions are YouTube's automatic speech recognition track.
+    #[serde(default)]
+    pub auto_generated: bool,
+    /// Sum of the segments' token counts, with `--count-tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_tokens: Option<usize>,
+}
+
+fn current_schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+/// JSON Schema of JSON output, as printed by `yt-transcriber schema`.
+pub fn json_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(TranscriptResult)).expect("schema serializes")
+}
+
+impl TranscriptResult {
+    /// Replaces the segments after a transformation, renumbering them and updating metadata.
+    pub fn replace_segments(&mut self, mut segments: Vec<TranscriptSegment>) {
+        for (i, seg) in segments.iter_mut().enumerate() {
+            seg.index = i;
+        }
+        self.metadata.total_segments = segments.len();
+        self.segments = segments;
+    }
+}
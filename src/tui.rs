@@ -0,0 +1,287 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::format::{format_timestamp_bracket, youtube_link};
+use crate::search::Query;
+use crate::transcript::{TranscriptResult, TranscriptSegment};
+
+const HELP: &str =
+    "↑/↓ move  / search  n/N next/previous match  y copy quote  o open video  q quit";
+
+/// Browses a transcript in the terminal until the user quits. `copy` puts a quote on the
+/// clipboard, so embedders decide how (or whether) that works.
+pub fn browse(
+    result: &TranscriptResult,
+    copy: &dyn Fn(&str) -> Result<(), String>,
+) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let outcome = Browser::new(result).run(&mut terminal, copy);
+    ratatui::restore();
+    outcome
+}
+
+/// A quote of a segment with its timestamp and a link to that moment, for pasting into notes.
+pub fn quote(result: &TranscriptResult, seg: &TranscriptSegment) -> String {
+    let title = result.video.as_ref().and_then(|v| v.title.as_deref());
+    format!(
+        "\"{}\"{} {} {}",
+        seg.text.replace('\n', " "),
+        title.map(|t| format!(" — {}", t)).unwrap_or_default(),
+        format_timestamp_bracket(seg.start_seconds),
+        youtube_link(&result.video_id, seg.start_seconds)
+    )
+}
+
+/// Opens a URL in the default browser.
+pub fn open_url(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+struct Browser<'a> {
+    result: &'a TranscriptResult,
+    list: ListState,
+    /// The search being typed, with the selection to go back to on Esc.
+    input: Option<(String, Option<usize>)>,
+    /// Indices of the segments matching the last search, in order.
+    matches: Vec<usize>,
+    status: String,
+    /// Rows of the transcript on screen, for Page Up and Page Down.
+    page: usize,
+}
+
+impl<'a> Browser<'a> {
+    fn new(result: &'a TranscriptResult) -> Self {
+        Browser {
+            result,
+            list: ListState::default().with_selected(Some(0)),
+            input: None,
+            matches: Vec::new(),
+            status: String::new(),
+            page: 1,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        copy: &dyn Fn(&str) -> Result<(), String>,
+    ) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                return Ok(());
+            }
+            if self.input.is_some() {
+                self.edit_search(key.code);
+                continue;
+            }
+
+            self.status.clear();
+            let last = self.result.segments.len().saturating_sub(1);
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+                KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+                KeyCode::PageDown | KeyCode::Char(' ') => self.move_by(self.page as isize),
+                KeyCode::PageUp => self.move_by(-(self.page as isize)),
+                KeyCode::Home | KeyCode::Char('g') => self.list.select(Some(0)),
+                KeyCode::End | KeyCode::Char('G') => self.list.select(Some(last)),
+                KeyCode::Char('/') => self.input = Some((String::new(), self.list.selected())),
+                KeyCode::Char('n') => self.next_match(true),
+                KeyCode::Char('N') => self.next_match(false),
+                KeyCode::Char('y') => {
+                    if let Some(seg) = self.selected() {
+                        self.status = match copy(&quote(self.result, seg)) {
+                            Ok(()) => "Copied the quote".to_string(),
+                            Err(e) => format!("Failed to copy - {}", e),
+                        };
+                    }
+                }
+                KeyCode::Char('o') => {
+                    if let Some(seg) = self.selected() {
+                        let url = youtube_link(&self.result.video_id, seg.start_seconds);
+                        self.status = match open_url(&url) {
+                            Ok(()) => format!("Opened {}", url),
+                            Err(e) => format!("Failed to open {} - {}", url, e),
+                        };
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<&'a TranscriptSegment> {
+        self.list
+            .selected()
+            .and_then(|i| self.result.segments.get(i))
+    }
+
+    fn move_by(&mut self, rows: isize) {
+        let last = self.result.segments.len().saturating_sub(1);
+        let current = self.list.selected().unwrap_or(0);
+        self.list
+            .select(Some(current.saturating_add_signed(rows).min(last)));
+    }
+
+    /// Handles a key while a search is typed, jumping to the first match as the query changes.
+    fn edit_search(&mut self, code: KeyCode) {
+        let Some((text, origin)) = &mut self.input else {
+            return;
+        };
+        let origin = *origin;
+        match code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Enter => {
+                self.input = None;
+                self.status = match self.matches.len() {
+                    0 => "No matches".to_string(),
+                    1 => "1 match".to_string(),
+                    n => format!("{} matches", n),
+                };
+                return;
+            }
+            KeyCode::Esc => {
+                self.input = None;
+                self.matches.clear();
+                self.list.select(origin);
+                return;
+            }
+            _ => return,
+        }
+
+        let query = Query::text(text);
+        self.matches = if text.is_empty() {
+            Vec::new()
+        } else {
+            self.result
+                .segments
+                .iter()
+                .enumerate()
+                .filter(|(_, seg)| query.is_match(&seg.text))
+                .map(|(i, _)| i)
+                .collect()
+        };
+        let from = origin.unwrap_or(0);
+        let first = self
+            .matches
+            .iter()
+            .find(|&&i| i >= from)
+            .or(self.matches.first());
+        self.list.select(first.copied().or(origin));
+    }
+
+    /// Selects the next (or previous) match, wrapping around the transcript.
+    fn next_match(&mut self, forward: bool) {
+        let current = self.list.selected().unwrap_or(0);
+        let target = if forward {
+            self.matches
+                .iter()
+                .find(|&&i| i > current)
+                .or(self.matches.first())
+        } else {
+            self.matches
+                .iter()
+                .rev()
+                .find(|&&i| i < current)
+                .or(self.matches.last())
+        };
+        match target {
+            Some(&i) => self.list.select(Some(i)),
+            None => self.status = "No matches; press / to search".to_string(),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        self.page = usize::from(body.height).max(1);
+
+        let title = self
+            .result
+            .video
+            .as_ref()
+            .and_then(|v| v.title.clone())
+            .unwrap_or_else(|| self.result.video_id.clone());
+        let mut details = format!(
+            "  {} · {} segments",
+            self.result.language,
+            self.result.segments.len()
+        );
+        if !self.matches.is_empty() {
+            details.push_str(&format!(" · {} matches", self.matches.len()));
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(title, Style::new().add_modifier(Modifier::BOLD)),
+                Span::raw(details),
+            ])),
+            header,
+        );
+
+        let items: Vec<ListItem> = self
+            .result
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(i, seg)| {
+                let text_style = if self.matches.binary_search(&i).is_ok() {
+                    Style::new().fg(Color::Yellow)
+                } else {
+                    Style::new()
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", format_timestamp_bracket(seg.start_seconds)),
+                        Style::new().fg(Color::Cyan),
+                    ),
+                    Span::styled(seg.text.replace('\n', " "), text_style),
+                ]))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, body, &mut self.list);
+
+        let status = match &self.input {
+            Some((text, _)) => format!("/{}", text),
+            None if !self.status.is_empty() => self.status.clone(),
+            None => HELP.to_string(),
+        };
+        frame.render_widget(Paragraph::new(status), footer);
+    }
+}
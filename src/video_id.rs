@@ -0,0 +1,175 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+/// Extracts the 11-character video ID from a YouTube URL or bare ID.
+pub fn extract_video_id(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+    static ID: OnceLock<Regex> = OnceLock::new();
+    let id_regex = ID.get_or_init(|| Regex::new(r"^[a-zA-Z0-9_-]{11}$").unwrap());
+
+    if id_regex.is_match(trimmed) {
+        return Some(trimmed.to_string());
+    }
+
+    if let Ok(url) = url::Url::parse(trimmed) {
+        let host = url.host_str().unwrap_or("");
+        let clean_host = host
+            .trim_start_matches("www.")
+            .trim_start_matches("m.")
+            .trim_start_matches("music.");
+
+        if clean_host == "youtu.be" {
+            let path = url.path().trim_start_matches('/');
+            let id = path.split('/').next().unwrap_or("");
+            if id_regex.is_match(id) {
+                return Some(id.to_string());
+            }
+        }
+
+        if clean_host == "youtube.com" {
+            if let Some(v) = url.query_pairs().find(|(k, _)| k == "v") {
+                if id_regex.is_match(&v.1) {
+                    return Some(v.1.to_string());
+                }
+            }
+
+            let segments: Vec<&str> = url.path().split('/').filter(|s| !s.is_empty()).collect();
+            let patterns = ["watch", "embed", "v", "shorts", "live", "clip"];
+
+            for i in 0..segments.len() {
+                if patterns.contains(&segments[i]) {
+                    if let Some(id) = segments.get(i + 1) {
+                        if id_regex.is_match(id) {
+                            return Some(id.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn youtube_url(input: &str) -> Option<url::Url> {
+    let url = url::Url::parse(input.trim()).ok()?;
+    let host = url.host_str()?;
+    let clean_host = host
+        .trim_start_matches("www.")
+        .trim_start_matches("m.")
+        .trim_start_matches("music.");
+    matches!(clean_host, "youtube.com" | "youtu.be").then_some(url)
+}
+
+/// Parses YouTube's time parameter syntax: `90`, `90s`, `1m30s` or `1h2m3s`.
+fn parse_youtube_time(value: &str) -> Option<f64> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return (seconds >= 0.0).then_some(seconds);
+    }
+
+    static TIME: OnceLock<Regex> = OnceLock::new();
+    let re = TIME.get_or_init(|| Regex::new(r"^(?:(\d+)h)?(?:(\d+)m)?(?:(\d+(?:\.\d+)?)s)?$").unwrap());
+    let caps = re.captures(value).filter(|c| c.get(0).is_some_and(|m| !m.is_empty()))?;
+    let part = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<f64>().ok()).unwrap_or(0.0);
+    Some(part(1) * 3600.0 + part(2) * 60.0 + part(3))
+}
+
+/// Start and end times embedded in a YouTube URL via `t`/`start`/`end` query parameters or
+/// a `#t=` fragment.
+pub fn extract_time_window(input: &str) -> (Option<f64>, Option<f64>) {
+    let Some(url) = youtube_url(input) else {
+        return (None, None);
+    };
+
+    let param = |names: &[&str]| {
+        url.query_pairs()
+            .find(|(k, _)| names.contains(&k.as_ref()))
+            .and_then(|(_, v)| parse_youtube_time(&v))
+    };
+    let fragment_start = url
+        .fragment()
+        .and_then(|f| f.strip_prefix("t="))
+        .and_then(parse_youtube_time);
+
+    (param(&["t", "start"]).or(fragment_start), param(&["end"]))
+}
+
+/// Whether the input is a `youtube.com/clip/...` URL, whose video ID and time window can
+/// only be resolved by asking YouTube.
+pub fn is_clip_url(input: &str) -> bool {
+    youtube_url(input).is_some_and(|url| url.path().starts_with("/clip/"))
+}
+
+/// Whether the input is an http(s) URL of any site.
+pub fn is_web_url(input: &str) -> bool {
+    url::Url::parse(input.trim()).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Stable key standing in for the video ID of a URL on another site, for file and cache names:
+/// the first 16 hex digits of the URL's SHA-256.
+pub fn url_key(input: &str) -> String {
+    Sha256::digest(input.trim().as_bytes())
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Whether the input is an RSS or Atom feed: YouTube's `/feeds/videos.xml` or any http(s) URL
+/// ending in `.xml`, `.rss` or `.atom`.
+pub fn is_feed_url(input: &str) -> bool {
+    let Ok(url) = url::Url::parse(input.trim()) else {
+        return false;
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let path = url.path().to_ascii_lowercase();
+    path.ends_with(".xml") || path.ends_with(".rss") || path.ends_with(".atom")
+}
+
+/// Whether the input is a playlist, channel or feed URL rather than a single video. Watch URLs
+/// that also carry a `list` parameter count as the single video.
+pub fn is_collection_url(input: &str) -> bool {
+    if is_feed_url(input) {
+        return true;
+    }
+    let Some(url) = youtube_url(input) else {
+        return false;
+    };
+    if extract_video_id(input).is_some() {
+        return false;
+    }
+
+    let first = url.path().split('/').find(|s| !s.is_empty()).unwrap_or("");
+    first == "playlist"
+        || first.starts_with('@')
+        || matches!(first, "channel" | "c" | "user")
+        || url.query_pairs().any(|(k, _)| k == "list")
+}
+
+/// URL whose flat listing yields the collection's videos. A channel's home page lists its tabs
+/// instead, so channel URLs without a tab are pointed at the videos tab.
+pub fn collection_listing_url(input: &str) -> String {
+    let Some(mut url) = youtube_url(input) else {
+        return input.trim().to_string();
+    };
+
+    let segments: Vec<String> = url
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    let channel_root = match segments.first().map(String::as_str) {
+        Some(first) if first.starts_with('@') => segments.len() == 1,
+        Some("channel" | "c" | "user") => segments.len() == 2,
+        _ => false,
+    };
+    if channel_root {
+        url.set_path(&format!("/{}/videos", segments.join("/")));
+    }
+    url.to_string()
+}
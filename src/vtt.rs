@@ -0,0 +1,273 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::transcript::{CueSettings, TranscriptSegment, Word};
+
+pub fn parse_vtt_timestamp(ts: &str) -> f64 {
+    let parts: Vec<&str> = ts.split(':').collect();
+    match parts.len() {
+        2 => {
+            let mins: f64 = parts[0].parse().unwrap_or(0.0);
+            let secs: f64 = parts[1].parse().unwrap_or(0.0);
+            mins * 60.0 + secs
+        }
+        3 => {
+            let hours: f64 = parts[0].parse().unwrap_or(0.0);
+            let mins: f64 = parts[1].parse().unwrap_or(0.0);
+            let secs: f64 = parts[2].parse().unwrap_or(0.0);
+            hours * 3600.0 + mins * 60.0 + secs
+        }
+        _ => 0.0,
+    }
+}
+
+fn named_entity(name: &str) -> Option<char> {
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "lrm" => '\u{200e}',
+        "rlm" => '\u{200f}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "eacute" => 'é',
+        "egrave" => 'è',
+        "aacute" => 'á',
+        "agrave" => 'à',
+        "iacute" => 'í',
+        "oacute" => 'ó',
+        "uacute" => 'ú',
+        "ntilde" => 'ñ',
+        "ccedil" => 'ç',
+        "auml" => 'ä',
+        "ouml" => 'ö',
+        "uuml" => 'ü',
+        "szlig" => 'ß',
+        "iexcl" => '¡',
+        "iquest" => '¿',
+        "euro" => '€',
+        "pound" => '£',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// Decodes HTML character references (`&amp;`, `&#39;`, `&#x2019;`, ...) in caption text.
+/// Unknown or malformed references are left as they are.
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    static ENTITY: OnceLock<Regex> = OnceLock::new();
+    let entity_re = ENTITY.get_or_init(|| Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z]{2,8});").unwrap());
+    entity_re
+        .replace_all(text, |caps: &regex::Captures| {
+            let body = &caps[1];
+            let decoded = if let Some(hex) = body.strip_prefix("#x").or_else(|| body.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(dec) = body.strip_prefix('#') {
+                dec.parse().ok().and_then(char::from_u32)
+            } else {
+                named_entity(body)
+            };
+            match decoded {
+                Some(c) => c.to_string(),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Parses the `name:value` settings that follow a cue's timings. Unknown settings are ignored.
+pub fn parse_cue_settings(text: &str) -> Option<CueSettings> {
+    let mut settings = CueSettings::default();
+    for (name, value) in text.split_whitespace().filter_map(|s| s.split_once(':')) {
+        let slot = match name {
+            "vertical" => &mut settings.vertical,
+            "line" => &mut settings.line,
+            "position" => &mut settings.position,
+            "size" => &mut settings.size,
+            "align" => &mut settings.align,
+            "region" => &mut settings.region,
+            _ => continue,
+        };
+        *slot = Some(value.to_string());
+    }
+    (settings != CueSettings::default()).then_some(settings)
+}
+
+/// Blocks that carry no cue text. Per the WebVTT spec their contents never contain `-->`.
+fn is_metadata_block(first_line: &str) -> bool {
+    ["NOTE", "STYLE", "REGION"].iter().any(|keyword| {
+        first_line
+            .strip_prefix(keyword)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+    })
+}
+
+/// Parses WebVTT captions into segments.
+///
+/// The input is split into blocks at empty lines. `NOTE`, `STYLE` and `REGION` blocks are
+/// skipped; in every other block the lines before the first timing line (the `WEBVTT` header or a
+/// cue identifier) are ignored, and each timing line starts a cue whose text runs until the next
+/// timing line. Lines containing only whitespace, as YouTube writes them, do not end a block.
+pub fn parse_vtt(content: &str) -> Vec<TranscriptSegment> {
+    let mut reader = VttReader::new();
+    let mut segments: Vec<TranscriptSegment> = content.lines().filter_map(|line| reader.push_line(line)).collect();
+    segments.extend(reader.finish());
+    segments
+}
+
+/// Parses WebVTT a line at a time, the way [`parse_vtt`] does, handing out each segment as soon
+/// as its cue is complete. Lets a long file or a pipe be transcribed while it is still being read.
+pub struct VttReader {
+    timestamp_re: Regex,
+    tag_re: Regex,
+    timing_re: Regex,
+    first_line: bool,
+    in_block: bool,
+    skip_block: bool,
+    cue: Option<Cue>,
+    next_index: usize,
+}
+
+impl Default for VttReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VttReader {
+    pub fn new() -> Self {
+        VttReader {
+            timestamp_re: Regex::new(r"^((?:\d+:)?\d{1,2}:\d{2}\.\d{3})\s*-->\s*((?:\d+:)?\d{1,2}:\d{2}\.\d{3})").unwrap(),
+            tag_re: Regex::new(r"<[^>]+>").unwrap(),
+            timing_re: Regex::new(r"<((?:\d+:)?\d{1,2}:\d{2}\.\d{3})>").unwrap(),
+            first_line: true,
+            in_block: false,
+            skip_block: false,
+            cue: None,
+            next_index: 0,
+        }
+    }
+
+    /// Takes the next line, without its line break. Returns the cue it completes, if any.
+    pub fn push_line(&mut self, line: &str) -> Option<TranscriptSegment> {
+        let line = if std::mem::take(&mut self.first_line) { line.strip_prefix('\u{feff}').unwrap_or(line) } else { line };
+        if line.is_empty() {
+            self.in_block = false;
+            return self.complete();
+        }
+        if !self.in_block {
+            self.in_block = true;
+            self.skip_block = is_metadata_block(line.trim());
+        }
+        if self.skip_block {
+            return None;
+        }
+
+        let line = line.trim();
+        if let Some(caps) = self.timestamp_re.captures(line) {
+            let done = self.complete();
+            self.cue = Some(Cue {
+                start: parse_vtt_timestamp(&caps[1]),
+                end: parse_vtt_timestamp(&caps[2]),
+                settings: parse_cue_settings(&line[caps.get(0).map_or(0, |m| m.end())..]),
+                lines: Vec::new(),
+                words: Vec::new(),
+            });
+            return done;
+        }
+        if let Some(cue) = self.cue.as_mut() {
+            let clean = self.tag_re.replace_all(line, "").trim().to_string();
+            if !clean.is_empty() {
+                cue.lines.push(clean);
+                cue.words.extend(timed_line_words(line, cue.start, cue.end, &self.timing_re, &self.tag_re));
+            }
+        }
+        None
+    }
+
+    /// Ends the input, returning the last cue if it has text.
+    pub fn finish(&mut self) -> Option<TranscriptSegment> {
+        self.in_block = false;
+        self.complete()
+    }
+
+    fn complete(&mut self) -> Option<TranscriptSegment> {
+        let segment = into_segment(self.cue.take()?, self.next_index)?;
+        self.next_index += 1;
+        Some(segment)
+    }
+}
+
+struct Cue {
+    start: f64,
+    end: f64,
+    settings: Option<CueSettings>,
+    lines: Vec<String>,
+    words: Vec<Word>,
+}
+
+/// Words of a cue line with inline timing tags (`so<00:00:00.480><c> today</c>`). Text before
+/// the first tag starts with the cue, and each tag marks the start of the text after it. Lines
+/// without timing tags yield no words.
+fn timed_line_words(line: &str, start: f64, end: f64, timing_re: &Regex, tag_re: &Regex) -> Vec<Word> {
+    let mut pieces = Vec::new();
+    let mut time = start;
+    let mut last = 0;
+    for caps in timing_re.captures_iter(line) {
+        let Some(tag) = caps.get(0) else { continue };
+        pieces.push((time, &line[last..tag.start()]));
+        time = parse_vtt_timestamp(&caps[1]);
+        last = tag.end();
+    }
+    if pieces.is_empty() {
+        return Vec::new();
+    }
+    pieces.push((time, &line[last..]));
+
+    let mut words = Vec::new();
+    for (i, (piece_start, text)) in pieces.iter().enumerate() {
+        let piece_end = pieces.get(i + 1).map_or(end, |p| p.0).max(*piece_start);
+        for word in tag_re.replace_all(text, "").split_whitespace() {
+            words.push(Word {
+                text: word.to_string(),
+                start: *piece_start,
+                end: piece_end,
+            });
+        }
+    }
+    words
+}
+
+fn into_segment(cue: Cue, index: usize) -> Option<TranscriptSegment> {
+    let text = cue.lines.join(" ");
+    (!text.trim().is_empty()).then(|| TranscriptSegment {
+        index,
+        text,
+        start_seconds: cue.start,
+        end_seconds: cue.end,
+        duration_seconds: cue.end - cue.start,
+        speaker: None,
+        settings: cue.settings,
+        words: (!cue.words.is_empty()).then_some(cue.words),
+        tokens: None,
+    })
+}
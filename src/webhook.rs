@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+use crate::http;
+use crate::transcript::TranscriptResult;
+
+/// Compact webhook payload: what was transcribed and where it was written.
+#[derive(Serialize)]
+pub struct WebhookSummary<'a> {
+    pub video_id: &'a str,
+    pub language: &'a str,
+    pub title: Option<&'a str>,
+    pub total_segments: usize,
+    pub format: &'a str,
+    /// File, `sqlite:` or `s3://` target the transcript was written to; `None` for stdout.
+    pub output: Option<&'a str>,
+    pub completed_at: String,
+}
+
+impl<'a> WebhookSummary<'a> {
+    pub fn new(result: &'a TranscriptResult, format: &'a str, output: Option<&'a str>) -> Self {
+        WebhookSummary {
+            video_id: &result.video_id,
+            language: &result.language,
+            title: result.video.as_ref().and_then(|v| v.title.as_deref()),
+            total_segments: result.metadata.total_segments,
+            format,
+            output,
+            completed_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// POSTs a JSON body to `url`, treating any non-2xx response as an error.
+pub fn post_json(url: &str, body: &str) -> Result<(), String> {
+    match http::agent()
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, _)) => Err(format!("HTTP {}", code)),
+        Err(e) => Err(e.to_string()),
+    }
+}
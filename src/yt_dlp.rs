@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use chrono::{NaiveDate, Utc};
+
+use crate::release::{self, verify_checksum, Release};
+
+/// GitHub repository of the official yt-dlp releases.
+pub const REPO: &str = "yt-dlp/yt-dlp";
+
+/// Checksum file published with every yt-dlp release.
+const CHECKSUMS: &str = "SHA2-256SUMS";
+
+/// yt-dlp versions older than this are reported as stale, since YouTube changes often break
+/// older extractors.
+pub const STALE_AFTER_DAYS: i64 = 90;
+
+/// The release date encoded in a yt-dlp version such as `2024.08.06` or the nightly
+/// `2024.08.06.232944`.
+pub fn release_date(version: &str) -> Option<NaiveDate> {
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+/// How many days old `version` is, if it is older than [`STALE_AFTER_DAYS`].
+pub fn stale_days(version: &str) -> Option<i64> {
+    let age = (Utc::now().date_naive() - release_date(version)?).num_days();
+    (age > STALE_AFTER_DAYS).then_some(age)
+}
+
+/// Splits a command line into words the way a POSIX shell would for plain words, `'single'` and
+/// `"double"` quotes and backslash escapes. No expansion is done.
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated ' quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("unterminated \" quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated \" quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                let word = word.get_or_insert_with(String::new);
+                word.extend(chars.next());
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Where the standalone binary is installed: `<data dir>/yt-transcriber/bin/yt-dlp`.
+pub fn managed_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("yt-transcriber")
+        .join("bin")
+        .join(format!("yt-dlp{}", std::env::consts::EXE_SUFFIX))
+}
+
+/// The standalone (Python-free) release asset for this platform, if yt-dlp publishes one.
+pub fn asset_name() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("yt-dlp_linux"),
+        ("linux", "aarch64") => Some("yt-dlp_linux_aarch64"),
+        ("linux", "arm") => Some("yt-dlp_linux_armv7l"),
+        ("macos", _) => Some("yt-dlp_macos"),
+        ("windows", "x86_64") => Some("yt-dlp.exe"),
+        ("windows", "x86") => Some("yt-dlp_x86.exe"),
+        _ => None,
+    }
+}
+
+/// Downloads the standalone binary of release `version` (the latest when `None`), verifies it
+/// against the release checksums and installs it at [`managed_path`]. Returns the installed
+/// version.
+pub fn install(version: Option<&str>) -> Result<String, String> {
+    let name = asset_name().ok_or_else(|| {
+        format!(
+            "yt-dlp publishes no standalone binary for {}-{}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+    })?;
+    let release = match version {
+        Some(version) => Release::tagged(REPO, version)?,
+        None => Release::latest(REPO)?,
+    };
+    let (Some(binary), Some(sums)) = (release.asset(name), release.asset(CHECKSUMS)) else {
+        return Err(format!(
+            "release {} has no {} with checksums",
+            release.tag, name
+        ));
+    };
+
+    let bytes = release::download(&binary.url)?;
+    let sums = release::download(&sums.url)?;
+    verify_checksum(&bytes, &String::from_utf8_lossy(&sums), name)?;
+
+    let path = managed_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    release::replace_executable(&path, &bytes).map_err(|e| e.to_string())?;
+    Ok(release.tag)
+}
+
+/// Whether yt-dlp failed because YouTube could not be reached rather than because of the video.
+pub fn is_network_error(stderr: &str) -> bool {
+    [
+        "Unable to download webpage",
+        "urlopen error",
+        "timed out",
+        "Connection reset",
+        "Temporary failure in name resolution",
+    ]
+    .iter()
+    .any(|pattern| stderr.contains(pattern))
+}
+
+/// Whether YouTube answered with HTTP 429 because of too many requests.
+pub fn is_rate_limited(stderr: &str) -> bool {
+    stderr.contains("HTTP Error 429") || stderr.contains("Too Many Requests")
+}
+
+/// Whether a failed yt-dlp run is worth retrying: the network, rate limiting or a server error,
+/// as opposed to a problem with the video itself.
+pub fn is_temporary_failure(stderr: &str) -> bool {
+    let server_error = [500, 502, 503, 504]
+        .iter()
+        .any(|code| stderr.contains(&format!("HTTP Error {}", code)));
+    !is_bot_check(stderr) && (is_network_error(stderr) || is_rate_limited(stderr) || server_error)
+}
+
+/// Whether YouTube refused to serve the video until the client proves it is not a bot.
+pub fn is_bot_check(stderr: &str) -> bool {
+    stderr.contains("confirm you're not a bot") || stderr.contains("confirm you\u{2019}re not a bot")
+}
+
+/// Whether YouTube withheld the video because of where the request came from.
+pub fn is_geo_restricted(stderr: &str) -> bool {
+    [
+        "not made this video available in your country",
+        "not available in your country",
+        "not available from your location",
+    ]
+    .iter()
+    .any(|pattern| stderr.contains(pattern))
+}
+
+/// The first country yt-dlp lists a region-restricted video as available in ("This video is
+/// available in DE, AT."), as an upper-case two-letter code.
+pub fn available_country(stderr: &str) -> Option<String> {
+    let (_, countries) = stderr.split_once("is available in ")?;
+    let country = countries.split([',', '.', ' ', '\n']).next()?;
+    (country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()))
+        .then(|| country.to_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Vec<String> {
+        split_args(line).unwrap()
+    }
+
+    #[test]
+    fn splits_plain_words() {
+        assert_eq!(
+            split("  --limit-rate  1M\t-N 4 "),
+            ["--limit-rate", "1M", "-N", "4"]
+        );
+        assert!(split("").is_empty());
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn keeps_quoted_whitespace() {
+        assert_eq!(
+            split(r#"--user-agent "Mozilla/5.0 (X11)" -o 'a b.%(ext)s'"#),
+            ["--user-agent", "Mozilla/5.0 (X11)", "-o", "a b.%(ext)s"]
+        );
+        assert_eq!(split(r#"--opt=a" "b'c d'"#), ["--opt=a bc d"]);
+    }
+
+    #[test]
+    fn keeps_empty_quoted_words() {
+        assert_eq!(split("--referer '' -v"), ["--referer", "", "-v"]);
+        assert_eq!(split(r#""""#), [""]);
+    }
+
+    #[test]
+    fn handles_backslash_escapes() {
+        assert_eq!(split(r"a\ b c\'d"), ["a b", "c'd"]);
+        assert_eq!(split(r#""say \"hi\" \\ \n""#), [r#"say "hi" \ \n"#]);
+        assert_eq!(split(r"'no \escape'"), [r"no \escape"]);
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        assert_eq!(
+            split_args("-o 'open"),
+            Err("unterminated ' quote".to_string())
+        );
+        assert_eq!(
+            split_args(r#"-o "open"#),
+            Err("unterminated \" quote".to_string())
+        );
+        assert_eq!(
+            split_args(r#""trailing \"#),
+            Err("unterminated \" quote".to_string())
+        );
+    }
+}
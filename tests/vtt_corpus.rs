@@ -0,0 +1,110 @@
+use yt_transcriber::parse_vtt;
+
+fn texts(content: &str) -> Vec<String> {
+    parse_vtt(content).into_iter().map(|s| s.text).collect()
+}
+
+#[test]
+fn youtube_manual_captions() {
+    let segments = parse_vtt(include_str!("fixtures/vtt/youtube_manual.vtt"));
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].text, "Hello and welcome");
+    assert_eq!(segments[0].start_seconds, 1.0);
+    assert_eq!(segments[0].end_seconds, 4.5);
+    assert_eq!(segments[1].text, "to the show &amp; the second line");
+    assert_eq!(segments[1].index, 1);
+}
+
+#[test]
+fn youtube_auto_captions_keep_whitespace_only_lines_inside_cues() {
+    assert_eq!(
+        texts(include_str!("fixtures/vtt/youtube_auto.vtt")),
+        [
+            "so today we're",
+            "so today we're",
+            "so today we're going to talk"
+        ]
+    );
+}
+
+#[test]
+fn note_style_and_region_blocks_are_skipped() {
+    let segments = parse_vtt(include_str!("fixtures/vtt/blocks.vtt"));
+    let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        [
+            "First cue",
+            "Kind: of magic Language: is fun",
+            "NOTE that this is cue text"
+        ]
+    );
+    assert_eq!(segments[1].start_seconds, 2.0);
+}
+
+#[test]
+fn timestamps_with_and_without_hours() {
+    let segments = parse_vtt(include_str!("fixtures/vtt/timestamps.vtt"));
+    let starts: Vec<f64> = segments.iter().map(|s| s.start_seconds).collect();
+    assert_eq!(starts, [5.25, 3723.5, 360000.0]);
+    assert_eq!(segments[0].duration_seconds, 1.75);
+}
+
+#[test]
+fn bom_and_crlf_line_endings() {
+    let content = "\u{feff}WEBVTT\r\n\r\n1\r\n00:00:01.000 --> 00:00:02.000\r\nFirst\r\n\r\n00:00:02.000 --> 00:00:03.000\r\nSecond\r\n";
+    assert_eq!(texts(content), ["First", "Second"]);
+}
+
+#[test]
+fn missing_blank_line_between_cues() {
+    let content =
+        "WEBVTT\n00:00:01.000 --> 00:00:02.000\nFirst\n00:00:02.000 --> 00:00:03.000\nSecond\n";
+    assert_eq!(texts(content), ["First", "Second"]);
+}
+
+#[test]
+fn cue_settings_are_captured() {
+    let segments = parse_vtt(include_str!("fixtures/vtt/youtube_auto.vtt"));
+    let settings = segments[0].settings.as_ref().expect("settings");
+    assert_eq!(settings.align.as_deref(), Some("start"));
+    assert_eq!(settings.position.as_deref(), Some("0%"));
+    assert_eq!(settings.line, None);
+
+    let segments = parse_vtt(include_str!("fixtures/vtt/blocks.vtt"));
+    assert_eq!(
+        segments[0]
+            .settings
+            .as_ref()
+            .and_then(|s| s.region.as_deref()),
+        Some("speaker")
+    );
+    assert!(segments[1].settings.is_none());
+}
+
+#[test]
+fn inline_timing_tags_become_words() {
+    let segments = parse_vtt(include_str!("fixtures/vtt/youtube_auto.vtt"));
+    let words = segments[0].words.as_ref().expect("words");
+    let timed: Vec<(&str, f64, f64)> = words
+        .iter()
+        .map(|w| (w.text.as_str(), w.start, w.end))
+        .collect();
+    assert_eq!(
+        timed,
+        [
+            ("so", 0.16, 0.48),
+            ("today", 0.48, 0.8),
+            ("we're", 0.8, 2.47)
+        ]
+    );
+
+    assert!(segments[1].words.is_none());
+    let texts: Vec<&str> = segments[2]
+        .words
+        .iter()
+        .flatten()
+        .map(|w| w.text.as_str())
+        .collect();
+    assert_eq!(texts, ["going", "to", "talk"]);
+}